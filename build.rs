@@ -0,0 +1,21 @@
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    // sql! and sqlx::migrate! embed these files; rebuild whenever any of them changes
+    println!("cargo:rerun-if-changed=queries");
+    println!("cargo:rerun-if-changed=migrations");
+
+    let queries = Path::new("queries");
+    let folders = fs::read_dir(queries).expect("queries/ directory missing");
+    for folder in folders.flatten().filter(|e| e.path().is_dir()) {
+        for file in fs::read_dir(folder.path()).into_iter().flatten().flatten() {
+            let path = file.path();
+            if path.extension().is_some_and(|ext| ext == "sql")
+                && fs::read_to_string(&path).map(|s| s.trim().is_empty()).unwrap_or(true)
+            {
+                panic!("SQL file {} is empty or unreadable", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+-- Speed up per-player raid statistics (/api/hub/stats), which filter by reporter and time
+CREATE INDEX IF NOT EXISTS idx_battle_reports_reported_by ON battle_reports(reported_by, created_at);
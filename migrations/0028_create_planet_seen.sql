@@ -0,0 +1,10 @@
+-- Per-admin "seen" state for /newplanets (user_id = Discord user ID)
+-- planets.status stays the global flag used when NEWPLANETS_GLOBAL=true
+CREATE TABLE planet_seen (
+    planet_id INTEGER NOT NULL,
+    user_id INTEGER NOT NULL,
+    seen_at TEXT DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (planet_id, user_id),
+    FOREIGN KEY (planet_id) REFERENCES planets(id) ON DELETE CASCADE
+);
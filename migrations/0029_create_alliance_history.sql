@@ -0,0 +1,12 @@
+-- Alliance membership changes per player (recorded by players::update_alliance)
+CREATE TABLE alliance_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    player_id INTEGER NOT NULL,
+    old_alliance_id INTEGER,
+    new_alliance_id INTEGER,
+    changed_at TEXT DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (player_id) REFERENCES players(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_alliance_history_player ON alliance_history(player_id, changed_at);
@@ -0,0 +1,6 @@
+-- Coordinate lookups filter by (galaxy, system, planet, type) and take the newest report.
+-- Including created_at lets SQLite walk the index backwards instead of sorting,
+-- which matters for the per-planet correlated subqueries in the hub overview.
+-- The old (galaxy, system, planet, type) index is a prefix of this one.
+CREATE INDEX IF NOT EXISTS idx_spy_reports_coords_time ON spy_reports(galaxy, system, planet, type, created_at);
+DROP INDEX IF EXISTS idx_spy_reports_coords;
@@ -0,0 +1,4 @@
+-- Speed up per-player expedition/recycling statistics (/api/hub/stats).
+-- battle_reports got the same index in 0027.
+CREATE INDEX IF NOT EXISTS idx_expedition_reports_reported_by ON expedition_reports(reported_by, created_at);
+CREATE INDEX IF NOT EXISTS idx_recycle_reports_reported_by ON recycle_reports(reported_by, created_at);
@@ -0,0 +1,2 @@
+-- Soft-delete for users: deleted users keep their row (and history) until purged
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
@@ -0,0 +1,3 @@
+-- Hash über Koordinaten, Zeitpunkt und Inhalt, um denselben Bericht mit anderer external_id zu erkennen
+ALTER TABLE spy_reports ADD COLUMN content_hash TEXT;
+CREATE INDEX idx_spy_reports_content_hash ON spy_reports(content_hash, created_at);
@@ -0,0 +1,45 @@
+-- spy_reports.reported_by holds the reporter's player ID like the other report tables (see 0018),
+-- but its foreign key still pointed at users(id). SQLite requires table recreation to change it.
+CREATE TABLE spy_reports_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    external_id INTEGER UNIQUE,  -- Report-ID aus pr0game
+    coordinates TEXT NOT NULL,
+    galaxy INTEGER NOT NULL,
+    system INTEGER NOT NULL,
+    planet INTEGER NOT NULL,
+    type TEXT DEFAULT 'PLANET',
+
+    resources TEXT,
+    buildings TEXT,
+    research TEXT,
+    fleet TEXT,
+    defense TEXT,
+
+    reported_by INTEGER,
+    report_time TEXT,  -- Zeitpunkt des Berichts im Spiel
+
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    content_hash TEXT,
+
+    FOREIGN KEY (reported_by) REFERENCES players(id)
+);
+
+-- Reporters without a player row would violate the new foreign key, they never resolved to a name anyway
+INSERT INTO spy_reports_new (
+    id, external_id, coordinates, galaxy, system, planet, type,
+    resources, buildings, research, fleet, defense,
+    reported_by, report_time, created_at, content_hash
+)
+SELECT
+    id, external_id, coordinates, galaxy, system, planet, type,
+    resources, buildings, research, fleet, defense,
+    CASE WHEN reported_by IN (SELECT id FROM players) THEN reported_by END,
+    report_time, created_at, content_hash
+FROM spy_reports;
+
+DROP TABLE spy_reports;
+ALTER TABLE spy_reports_new RENAME TO spy_reports;
+
+CREATE INDEX idx_spy_reports_time ON spy_reports(created_at);
+CREATE INDEX idx_spy_reports_coords_time ON spy_reports(galaxy, system, planet, type, created_at);
+CREATE INDEX idx_spy_reports_content_hash ON spy_reports(content_hash, created_at);
@@ -1,2 +1,7 @@
+-- Ohne vollen Namen wird der Tag als Name gespeichert; solange der Name nur
+-- der Platzhalter ist, zieht er bei einer Tag-Änderung mit, ein echter Name bleibt
 INSERT INTO alliances (id, name, tag) VALUES (?, ?, ?)
-ON CONFLICT(id) DO UPDATE SET tag = excluded.tag, updated_at = CURRENT_TIMESTAMP
+ON CONFLICT(id) DO UPDATE SET
+    name = CASE WHEN alliances.name = alliances.tag THEN excluded.name ELSE alliances.name END,
+    tag = excluded.tag,
+    updated_at = CURRENT_TIMESTAMP
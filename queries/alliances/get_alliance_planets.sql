@@ -1,5 +1,5 @@
 SELECT p.id, p.name, p.player_id, p.coordinates, p.galaxy, p.system, p.planet,
-       p.type, p.buildings, p.fleet, p.defense, p.resources, p.prod_h,
+       p.type, p.planet_id, p.buildings, p.fleet, p.defense, p.resources, p.prod_h,
        p.status, p.created_at, p.updated_at
 FROM planets p
 JOIN players pl ON p.player_id = pl.id
@@ -0,0 +1,5 @@
+SELECT id, name, tag,
+       COALESCE(created_at, '') AS created_at,
+       COALESCE(updated_at, '') AS updated_at
+FROM alliances
+WHERE id = ?
@@ -1,6 +1,6 @@
 SELECT
-    ps.id AS "id!",
-    ps.player_id AS "player_id!",
+    ps.id,
+    ps.player_id,
     ps.score_total,
     ps.score_economy,
     ps.score_research,
@@ -0,0 +1,8 @@
+-- Nicht gelöschte Mitglieder und deren nicht zerstörte Planeten/Monde
+SELECT
+    (SELECT COUNT(*) FROM players
+     WHERE alliance_id = ?1 AND id > 0 AND COALESCE(is_deleted, 0) = 0) AS member_count,
+    (SELECT COUNT(*) FROM planets p
+     JOIN players pl ON p.player_id = pl.id
+     WHERE pl.alliance_id = ?1 AND COALESCE(pl.is_deleted, 0) = 0
+       AND (p.status IS NULL OR p.status != 'deleted')) AS planet_count
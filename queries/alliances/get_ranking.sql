@@ -0,0 +1,10 @@
+-- Allianzen nach Summe der Gesamtpunkte ihrer (nicht gelöschten) Mitglieder
+SELECT a.id, a.name, a.tag,
+    SUM(COALESCE(p.score_total, 0)) AS total_score,
+    COUNT(p.id) AS member_count
+FROM alliances a
+JOIN players p ON p.alliance_id = a.id
+WHERE p.id > 0 AND COALESCE(p.is_deleted, 0) = 0
+GROUP BY a.id
+ORDER BY total_score DESC, a.id
+LIMIT ?
@@ -0,0 +1,3 @@
+-- Vollen Allianznamen setzen; ein echter Name wird nie durch den Tag ersetzt
+UPDATE alliances SET name = ?1, updated_at = CURRENT_TIMESTAMP
+WHERE id = ?2 AND name <> ?1 AND (?1 <> tag OR name = tag)
@@ -0,0 +1,14 @@
+-- Neueste Admin-Aktionen zuerst, mit Spielername des ausführenden Users
+SELECT
+    l.id,
+    l.actor_id,
+    p.name AS actor_name,
+    l.action,
+    l.target,
+    l.detail,
+    l.created_at
+FROM audit_log l
+LEFT JOIN users u ON l.actor_id = u.id
+LEFT JOIN players p ON u.player_id = p.id
+ORDER BY l.id DESC
+LIMIT ?
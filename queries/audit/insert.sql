@@ -0,0 +1 @@
+INSERT INTO audit_log (actor_id, action, target, detail) VALUES (?, ?, ?, ?)
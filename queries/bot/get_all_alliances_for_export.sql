@@ -1,3 +1,4 @@
 SELECT id, name, COALESCE(CAST(strftime('%s', updated_at) AS INTEGER) * 1000, 0) AS timepoint
 FROM alliances
+WHERE ?1 IS NULL OR COALESCE(CAST(strftime('%s', updated_at) AS INTEGER) * 1000, 0) > ?1
 ORDER BY id
@@ -1,4 +1,5 @@
 SELECT id, name, COALESCE(CAST(strftime('%s', updated_at) AS INTEGER) * 1000, 0) AS timepoint
 FROM players
 WHERE name IS NOT NULL
+  AND (?1 IS NULL OR COALESCE(CAST(strftime('%s', updated_at) AS INTEGER) * 1000, 0) > ?1)
 ORDER BY id
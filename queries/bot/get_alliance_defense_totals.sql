@@ -0,0 +1,11 @@
+-- Verteidigung aller Planeten/Monde der Allianz ?1, summiert pro Verteidigungs-ID
+SELECT
+    d.key AS defense_id,
+    SUM(d.value) AS amount
+FROM planets pl
+JOIN players p ON p.id = pl.player_id AND p.is_deleted = 0
+JOIN json_each(pl.defense) d
+WHERE p.alliance_id = ?1
+GROUP BY d.key
+HAVING SUM(d.value) > 0
+ORDER BY CAST(d.key AS INTEGER)
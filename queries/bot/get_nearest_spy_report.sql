@@ -1,4 +1,5 @@
-SELECT sr.created_at, sr.galaxy, sr.system, sr.planet,
+-- Neuester Planeten-Bericht im selben System, naechste Planetenposition zuerst
+SELECT sr.created_at, sr.galaxy, sr.system, sr.planet, sr.type AS planet_type,
        p.name AS player_name, a.name AS alliance_name, reporter.name AS reporter_name,
        sr.resources, sr.buildings, sr.fleet, sr.defense
 FROM spy_reports sr
@@ -7,6 +8,6 @@ LEFT JOIN planets pl ON sr.galaxy = pl.galaxy AND sr.system = pl.system
 LEFT JOIN players p ON pl.player_id = p.id
 LEFT JOIN alliances a ON p.alliance_id = a.id
 LEFT JOIN players reporter ON sr.reported_by = reporter.id
-WHERE sr.galaxy = ? AND sr.system = ? AND sr.planet = ?
-ORDER BY sr.created_at DESC
+WHERE sr.galaxy = ?1 AND sr.system = ?2 AND sr.type = 'PLANET'
+ORDER BY ABS(sr.planet - ?3), sr.created_at DESC
 LIMIT 1
@@ -1,7 +1,11 @@
 SELECT p.id, p.galaxy, p.system, p.planet, pl.name AS player_name,
-       a.tag AS alliance_tag, p.created_at
+       a.tag AS alliance_tag, p.type AS planet_type, p.created_at
 FROM planets p
 LEFT JOIN players pl ON p.player_id = pl.id
 LEFT JOIN alliances a ON pl.alliance_id = a.id
-WHERE p.status = 'new' AND p.type = 'PLANET'
-ORDER BY p.galaxy, p.system, p.planet
+WHERE p.status = 'new'
+  AND (p.type = 'PLANET' OR (?2 AND p.type = 'MOON'))
+  AND (?1 IS NULL OR NOT EXISTS (
+      SELECT 1 FROM planet_seen s WHERE s.planet_id = p.id AND s.user_id = ?1
+  ))
+ORDER BY p.galaxy, p.system, p.planet, p.type DESC
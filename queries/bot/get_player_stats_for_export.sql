@@ -0,0 +1,15 @@
+SELECT
+    p.id, p.name, p.alliance_id, a.tag AS alliance_tag,
+    p.combats_total, p.combats_won, p.combats_draw, p.combats_lost,
+    p.units_shot, p.units_lost,
+    p.honorpoints, p.honorpoints_rank,
+    p.fights_honorable, p.fights_dishonorable, p.fights_neutral,
+    p.destruction_units_killed, p.destruction_units_lost,
+    p.destruction_recycled_metal, p.destruction_recycled_crystal,
+    p.real_destruction_units_killed, p.real_destruction_units_lost,
+    p.real_destruction_recycled_metal, p.real_destruction_recycled_crystal,
+    p.updated_at
+FROM players p
+LEFT JOIN alliances a ON p.alliance_id = a.id
+WHERE p.is_deleted = 0
+ORDER BY p.id
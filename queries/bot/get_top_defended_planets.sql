@@ -0,0 +1,14 @@
+-- Planeten/Monde der Allianz ?1 mit den meisten Verteidigungsanlagen, höchstens ?2
+SELECT
+    pl.coordinates,
+    pl.type AS planet_type,
+    p.name AS player_name,
+    SUM(d.value) AS total_units
+FROM planets pl
+JOIN players p ON p.id = pl.player_id AND p.is_deleted = 0
+JOIN json_each(pl.defense) d
+WHERE p.alliance_id = ?1
+GROUP BY pl.id
+HAVING SUM(d.value) > 0
+ORDER BY total_units DESC, pl.galaxy, pl.system, pl.planet
+LIMIT ?2
@@ -1,5 +1,9 @@
-SELECT name, score_total, score_fleet, score_buildings, inactive_since
-FROM players
-WHERE inactive_since IS NOT NULL AND vacation_since IS NULL AND is_deleted = 0
-ORDER BY score_total DESC
+-- ?1 = JSON-Array mit Allianz-IDs, deren Inaktive nicht als Farmen gelistet werden
+SELECT p.name, p.score_total, p.score_fleet, p.score_buildings, p.inactive_since,
+       a.tag AS alliance_tag
+FROM players p
+LEFT JOIN alliances a ON p.alliance_id = a.id
+WHERE p.inactive_since IS NOT NULL AND p.vacation_since IS NULL AND p.is_deleted = 0
+  AND (p.alliance_id IS NULL OR p.alliance_id NOT IN (SELECT value FROM json_each(?1)))
+ORDER BY p.score_total DESC
 LIMIT 20
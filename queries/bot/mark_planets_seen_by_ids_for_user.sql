@@ -0,0 +1,2 @@
+INSERT OR IGNORE INTO planet_seen (planet_id, user_id)
+SELECT value, ? FROM json_each(?)
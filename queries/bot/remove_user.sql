@@ -1 +1,2 @@
-DELETE FROM users WHERE id = ?
+-- Soft-Delete wie users::delete, damit Admins versehentliches Entfernen rückgängig machen können
+UPDATE users SET deleted_at = datetime('now'), updated_at = datetime('now') WHERE id = ? AND deleted_at IS NULL
@@ -0,0 +1,16 @@
+-- Allianzen, deren Name oder Tag mit ?1 beginnt (LIKE-escaped, Groß-/Kleinschreibung egal),
+-- mit Anzahl und Gesamtpunkten der nicht gelöschten Mitglieder.
+-- Exakte Treffer auf ?2 zuerst, danach nach Punkten.
+SELECT
+    a.id,
+    a.name,
+    a.tag,
+    COUNT(p.id) AS member_count,
+    COALESCE(SUM(p.score_total), 0) AS total_score
+FROM alliances a
+LEFT JOIN players p ON p.alliance_id = a.id AND p.is_deleted = 0
+WHERE a.name LIKE ?1 || '%' ESCAPE '\'
+   OR a.tag LIKE ?1 || '%' ESCAPE '\'
+GROUP BY a.id
+ORDER BY (LOWER(a.tag) = LOWER(?2) OR LOWER(a.name) = LOWER(?2)) DESC, total_score DESC, a.name
+LIMIT ?3
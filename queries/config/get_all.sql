@@ -0,0 +1,2 @@
+-- Alle Konfigurationswerte (Admin-Ansicht)
+SELECT key, value FROM config ORDER BY key
@@ -0,0 +1,7 @@
+-- System-Marker (planet = 0) als "nicht gescannt" markieren, damit Coverage/Galaxy-Status
+-- das System als veraltet anzeigen. Der nächste Scan setzt updated_at wieder.
+UPDATE planets
+SET updated_at = NULL
+WHERE galaxy = ?
+  AND system = ?
+  AND planet = 0
@@ -1,4 +1,8 @@
 -- Zählt feindliche Spionageberichte für Pagination
 SELECT COUNT(*) as total
 FROM hostile_spying
-WHERE (? IS NULL OR attacker_coordinates LIKE '%' || ? || '%' OR target_coordinates LIKE '%' || ? || '%');
+WHERE (? IS NULL OR attacker_coordinates LIKE '%' || ? || '%' OR target_coordinates LIKE '%' || ? || '%')
+    -- Filter: Zeitraum (von)
+    AND (? IS NULL OR report_time >= ?)
+    -- Filter: Zeitraum (bis)
+    AND (? IS NULL OR report_time <= ?);
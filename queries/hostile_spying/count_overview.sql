@@ -1,5 +1,5 @@
 -- Zählt die Anzahl der eindeutigen Angreifer für Pagination
-SELECT COUNT(DISTINCT hs.attacker_coordinates) AS "total!"
+SELECT COUNT(DISTINCT hs.attacker_coordinates) AS total
 FROM hostile_spying hs
 LEFT JOIN planets pl ON pl.coordinates = hs.attacker_coordinates AND pl.type = 'PLANET'
 LEFT JOIN players p ON p.id = pl.player_id
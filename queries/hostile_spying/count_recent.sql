@@ -0,0 +1,4 @@
+-- Fremdspionage, die innerhalb des Zeitfensters eingegangen ist (?1 z.B. '-60 minutes')
+SELECT COUNT(*) AS total
+FROM hostile_spying
+WHERE created_at >= datetime('now', ?1);
@@ -0,0 +1,12 @@
+-- Zählt die Anzahl der eindeutigen Ziele für Pagination
+SELECT COUNT(DISTINCT hs.target_coordinates) AS total
+FROM hostile_spying hs
+LEFT JOIN planets pl ON pl.coordinates = hs.target_coordinates AND pl.type = 'PLANET'
+LEFT JOIN players p ON p.id = pl.player_id
+WHERE hs.target_coordinates IS NOT NULL
+    -- Filter: Spionierter Spieler (Name oder Koordinaten)
+    AND (? IS NULL OR p.name LIKE '%' || ? || '%' OR hs.target_coordinates LIKE '%' || ? || '%')
+    -- Filter: Zeitraum (von)
+    AND (? IS NULL OR hs.report_time >= ?)
+    -- Filter: Zeitraum (bis)
+    AND (? IS NULL OR hs.report_time <= ?);
@@ -1,7 +1,7 @@
 -- Query für GET /api/hostile-spying
--- Holt feindliche Spionageberichte mit optionalem Suchfilter und Pagination
+-- Holt feindliche Spionageberichte mit optionalem Such- und Zeitfilter und Pagination
 SELECT
-    id AS "id!",
+    id,
     external_id,
     attacker_coordinates,
     target_coordinates,
@@ -9,5 +9,9 @@ SELECT
     created_at
 FROM hostile_spying
 WHERE (? IS NULL OR attacker_coordinates LIKE '%' || ? || '%' OR target_coordinates LIKE '%' || ? || '%')
+    -- Filter: Zeitraum (von)
+    AND (? IS NULL OR report_time >= ?)
+    -- Filter: Zeitraum (bis)
+    AND (? IS NULL OR report_time <= ?)
 ORDER BY created_at DESC
 LIMIT ? OFFSET ?;
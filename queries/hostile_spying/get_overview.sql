@@ -1,10 +1,10 @@
 -- Aggregierte Fremdspionage-Übersicht
 -- Gruppiert nach Angreifer-Koordinaten, mit Spieler/Allianz-Info und Filter
 SELECT
-    hs.attacker_coordinates AS "attacker_coordinates!",
+    hs.attacker_coordinates,
     p.name AS attacker_name,
     a.tag AS attacker_alliance_tag,
-    COUNT(*) AS "spy_count!: i64",
+    COUNT(*) AS spy_count,
     MAX(hs.report_time) AS last_spy_time,
     GROUP_CONCAT(DISTINCT hs.target_coordinates) AS targets
 FROM hostile_spying hs
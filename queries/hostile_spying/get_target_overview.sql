@@ -0,0 +1,22 @@
+-- Fremdspionage gruppiert nach Ziel (unsere Planeten/Monde):
+-- wie oft und von welchen Koordinaten aus spioniert wurde
+SELECT
+    hs.target_coordinates,
+    pl.name AS target_name,
+    p.name AS target_player_name,
+    COUNT(*) AS spy_count,
+    MAX(hs.report_time) AS last_spy_time,
+    GROUP_CONCAT(DISTINCT hs.attacker_coordinates) AS attackers
+FROM hostile_spying hs
+LEFT JOIN planets pl ON pl.coordinates = hs.target_coordinates AND pl.type = 'PLANET'
+LEFT JOIN players p ON p.id = pl.player_id
+WHERE hs.target_coordinates IS NOT NULL
+    -- Filter: Spionierter Spieler (Name oder Koordinaten)
+    AND (? IS NULL OR p.name LIKE '%' || ? || '%' OR hs.target_coordinates LIKE '%' || ? || '%')
+    -- Filter: Zeitraum (von)
+    AND (? IS NULL OR hs.report_time >= ?)
+    -- Filter: Zeitraum (bis)
+    AND (? IS NULL OR hs.report_time <= ?)
+GROUP BY hs.target_coordinates
+ORDER BY spy_count DESC, MAX(hs.report_time) DESC
+LIMIT ? OFFSET ?;
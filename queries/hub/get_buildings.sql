@@ -3,4 +3,5 @@ FROM players p
          JOIN planets pl ON pl.player_id = p.id
 WHERE p.alliance_id = ?
   AND p.is_deleted = 0
+  AND pl.status != 'deleted'
   AND pl.buildings IS NOT NULL;
@@ -0,0 +1,6 @@
+SELECT galaxy, system, updated_at AS last_scan_at
+FROM planets
+WHERE planet = 0
+  AND galaxy = ?
+  AND system BETWEEN ? AND ?
+ORDER BY system
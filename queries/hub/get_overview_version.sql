@@ -0,0 +1,10 @@
+-- Cheap change marker for GET /api/hub/overview (ETag)
+-- Score diffs depend on the current time, so the hour is part of the version
+SELECT
+    (SELECT COUNT(*) FROM planets) AS planet_count,
+    (SELECT MAX(updated_at) FROM planets) AS planets_updated_at,
+    (SELECT MAX(updated_at) FROM players) AS players_updated_at,
+    (SELECT MAX(id) FROM player_scores) AS last_score_id,
+    (SELECT MAX(id) FROM spy_reports) AS last_spy_report_id,
+    (SELECT MAX(id) FROM battle_reports) AS last_battle_report_id,
+    strftime('%Y-%m-%d %H', 'now') AS current_hour
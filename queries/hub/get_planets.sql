@@ -7,4 +7,6 @@ SELECT
 FROM planets p
          JOIN players pl ON p.player_id = pl.id
 WHERE pl.alliance_id = ?
+  AND pl.is_deleted = 0
+  AND p.status != 'deleted'
 ORDER BY pl.name, p.galaxy, p.system, p.planet;
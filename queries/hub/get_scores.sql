@@ -1,6 +1,6 @@
 SELECT
-    ps.id AS "id!",
-    ps.player_id AS "player_id!",
+    ps.id,
+    ps.player_id,
     ps.score_total,
     ps.score_economy,
     ps.score_research,
@@ -15,5 +15,6 @@ SELECT
 FROM player_scores ps
 JOIN players p ON p.id = ps.player_id
 WHERE p.alliance_id = ?
+  AND p.is_deleted = 0
   AND ps.recorded_at >= datetime('now', '-56 days')
 ORDER BY ps.recorded_at DESC, ps.player_id;
@@ -0,0 +1,2 @@
+-- Statistiken für den Query-Planer aktualisieren
+ANALYZE
@@ -0,0 +1,2 @@
+-- Berichte älter als das Aufbewahrungsfenster löschen (?1 z.B. '-90 days')
+DELETE FROM hostile_spying WHERE created_at < datetime('now', ?1)
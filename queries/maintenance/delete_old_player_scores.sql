@@ -0,0 +1,2 @@
+-- Score-Verlauf älter als das Aufbewahrungsfenster löschen (?1 z.B. '-90 days')
+DELETE FROM player_scores WHERE recorded_at < datetime('now', ?1)
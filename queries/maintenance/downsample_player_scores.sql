@@ -0,0 +1,15 @@
+-- Score-Verlauf vor dem Vollauflösungsfenster (?1 z.B. '-7 days') auf einen Punkt pro Spieler und Tag reduzieren,
+-- behalten wird jeweils der letzte Eintrag des Tages. Nur ganze Tage, damit der Stichtag nicht geteilt wird.
+DELETE FROM player_scores
+WHERE recorded_at < date('now', ?1)
+  AND id NOT IN (
+      SELECT id FROM (
+          SELECT id, ROW_NUMBER() OVER (
+              PARTITION BY player_id, date(recorded_at)
+              ORDER BY recorded_at DESC, id DESC
+          ) AS rn
+          FROM player_scores
+          WHERE recorded_at < date('now', ?1)
+      )
+      WHERE rn = 1
+  )
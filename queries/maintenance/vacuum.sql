@@ -0,0 +1,2 @@
+-- Datenbankdatei kompaktieren (freie Seiten zurückgeben)
+VACUUM
@@ -0,0 +1,2 @@
+-- Prüfen ob an diesen Koordinaten schon ein Planet/Mond gespeichert ist
+SELECT COUNT(*) FROM planets WHERE coordinates = ? AND type = ?
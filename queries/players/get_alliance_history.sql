@@ -0,0 +1,9 @@
+SELECT h.id, h.player_id,
+       h.old_alliance_id, oa.tag AS old_alliance_tag,
+       h.new_alliance_id, na.tag AS new_alliance_tag,
+       h.changed_at
+FROM alliance_history h
+LEFT JOIN alliances oa ON h.old_alliance_id = oa.id
+LEFT JOIN alliances na ON h.new_alliance_id = na.id
+WHERE h.player_id = ?
+ORDER BY h.changed_at DESC, h.id DESC
@@ -0,0 +1 @@
+SELECT alliance_id FROM players WHERE id = ?
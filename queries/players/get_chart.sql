@@ -1,6 +1,6 @@
 SELECT
-    id AS "id!",
-    player_id AS "player_id!",
+    id,
+    player_id,
     score_total,
     score_economy,
     score_research,
@@ -0,0 +1,29 @@
+-- Letzter Score pro Zeitfenster; ?2 ist das strftime-Format des Fensters
+SELECT
+    id,
+    player_id,
+    score_total,
+    score_economy,
+    score_research,
+    score_military,
+    score_defense,
+    rank_total,
+    rank_economy,
+    rank_research,
+    rank_military,
+    rank_defense,
+    recorded_at
+FROM (
+    SELECT
+        *,
+        ROW_NUMBER() OVER (
+            PARTITION BY strftime(?2, recorded_at)
+            ORDER BY recorded_at DESC, id DESC
+        ) AS bucket_rank
+    FROM player_scores
+    WHERE player_id = ?1
+      AND (?3 IS NULL OR recorded_at >= ?3)
+      AND (?4 IS NULL OR recorded_at <= ?4)
+)
+WHERE bucket_rank = 1
+ORDER BY recorded_at ASC;
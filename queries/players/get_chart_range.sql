@@ -0,0 +1,19 @@
+SELECT
+    id,
+    player_id,
+    score_total,
+    score_economy,
+    score_research,
+    score_military,
+    score_defense,
+    rank_total,
+    rank_economy,
+    rank_research,
+    rank_military,
+    rank_defense,
+    recorded_at
+FROM player_scores
+WHERE player_id = ?1
+  AND (?2 IS NULL OR recorded_at >= ?2)
+  AND (?3 IS NULL OR recorded_at <= ?3)
+ORDER BY recorded_at ASC;
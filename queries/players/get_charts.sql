@@ -0,0 +1,18 @@
+-- Score-Verlauf mehrerer Spieler auf einmal (?1 = JSON-Array mit Spieler-IDs)
+SELECT
+    id,
+    player_id,
+    score_total,
+    score_economy,
+    score_research,
+    score_military,
+    score_defense,
+    rank_total,
+    rank_economy,
+    rank_research,
+    rank_military,
+    rank_defense,
+    recorded_at
+FROM player_scores
+WHERE player_id IN (SELECT value FROM json_each(?1))
+ORDER BY player_id, recorded_at ASC;
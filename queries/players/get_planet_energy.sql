@@ -0,0 +1,12 @@
+-- Energiebilanz aller Planeten/Monde eines Spielers aus dem letzten Imperium-Sync;
+-- ohne Imperium-Daten (fields_max = 0) ist die Energie NULL statt 0
+SELECT
+    coordinates,
+    type,
+    name,
+    CASE WHEN fields_max > 0 THEN energy_used END AS energy_used,
+    CASE WHEN fields_max > 0 THEN energy_max END AS energy_max,
+    updated_at
+FROM planets
+WHERE player_id = ?
+ORDER BY galaxy, system, planet, type
@@ -0,0 +1,6 @@
+-- Temperatur aller Planeten (ohne Monde) eines Spielers aus dem letzten Imperium-Sync;
+-- Planeten, die nur aus der Galaxieansicht bekannt sind (fields_max = 0), liefern NULL
+SELECT coordinates, name, CASE WHEN fields_max > 0 THEN temperature END AS temperature, updated_at
+FROM planets
+WHERE player_id = ? AND type = 'PLANET'
+ORDER BY galaxy, system, planet
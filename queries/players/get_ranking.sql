@@ -0,0 +1,16 @@
+-- Bestenliste über alle Spieler für eine Punktekategorie (?1), ohne gelöschte Spieler
+-- und ohne System-Marker (player_id = 0)
+SELECT p.id, p.name, p.alliance_id, a.name AS alliance_name, a.tag AS alliance_tag,
+    CASE ?1
+        WHEN 'total' THEN p.score_total
+        WHEN 'fleet' THEN p.score_fleet
+        WHEN 'research' THEN p.score_research
+        WHEN 'buildings' THEN p.score_buildings
+        WHEN 'defense' THEN p.score_defense
+        WHEN 'honor' THEN p.honorpoints
+    END AS score
+FROM players p
+LEFT JOIN alliances a ON p.alliance_id = a.id
+WHERE p.id > 0 AND COALESCE(p.is_deleted, 0) = 0 AND score IS NOT NULL
+ORDER BY score DESC, p.id
+LIMIT ?2
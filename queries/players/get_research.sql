@@ -0,0 +1 @@
+SELECT research FROM players WHERE id = ?
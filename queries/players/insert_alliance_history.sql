@@ -0,0 +1,2 @@
+INSERT INTO alliance_history (player_id, old_alliance_id, new_alliance_id)
+VALUES (?, ?, ?)
@@ -0,0 +1,6 @@
+-- Gleicher Inhalt unter anderer external_id innerhalb des Zeitfensters (?3 = '-N minutes')
+SELECT COUNT(*)
+FROM spy_reports
+WHERE content_hash = ?1
+  AND external_id != ?2
+  AND created_at >= datetime('now', ?3)
@@ -1,10 +1,10 @@
 SELECT
-    id AS "id!",
+    id,
     external_id,
-    coordinates AS "coordinates!",
-    galaxy AS "galaxy!",
-    system AS "system!",
-    planet AS "planet!",
+    coordinates,
+    galaxy,
+    system,
+    planet,
     type,
     resources,
     buildings,
@@ -1,11 +1,11 @@
 -- Get latest spy reports for a system (one per planet position)
 SELECT
-    id AS "id!",
+    id,
     external_id,
-    coordinates AS "coordinates!",
-    galaxy AS "galaxy!",
-    system AS "system!",
-    planet AS "planet!",
+    coordinates,
+    galaxy,
+    system,
+    planet,
     type,
     resources,
     buildings,
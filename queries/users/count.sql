@@ -0,0 +1,5 @@
+-- Zählt User für Pagination (gleicher Filter wie get_paged)
+SELECT COUNT(*)
+FROM users u
+LEFT JOIN players p ON u.player_id = p.id
+WHERE (?1 IS NULL OR p.name LIKE '%' || ?1 || '%');
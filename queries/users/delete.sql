@@ -1 +1,4 @@
-DELETE FROM users WHERE id = ?;
+-- Soft-Delete: Zeile bleibt erhalten, bis sie per purge endgültig entfernt wird
+UPDATE users
+SET deleted_at = datetime('now'), updated_at = datetime('now')
+WHERE id = ? AND deleted_at IS NULL;
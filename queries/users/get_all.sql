@@ -1,12 +1,13 @@
 SELECT
-    u.id AS "id!",
+    u.id,
     u.player_id,
     u.alliance_id,
-    u.language AS "language!",
-    u.role AS "role!: UserRole",
+    u.language,
+    u.role,
     u.last_activity_at,
     u.created_at,
     u.updated_at,
+    u.deleted_at,
     p.name AS player_name,
     a.name AS alliance_name
 FROM users u
@@ -1,12 +1,13 @@
+-- Gelöschte User können sich nicht mehr anmelden
 SELECT
-    id AS "id!",
-    api_key AS "api_key!",
+    id,
+    api_key,
     player_id,
     alliance_id,
-    language AS "language!",
-    role AS "role!: UserRole",
+    language,
+    role,
     last_activity_at,
     created_at,
     updated_at
 FROM users
-WHERE TRIM(api_key) = ?;
+WHERE TRIM(api_key) = ? AND deleted_at IS NULL;
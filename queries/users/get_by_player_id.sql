@@ -1,12 +1,12 @@
 SELECT
-    id AS "id!",
-    api_key AS "api_key!",
+    id,
+    api_key,
     player_id,
     alliance_id,
-    language AS "language!",
-    role AS "role!: UserRole",
+    language,
+    role,
     last_activity_at,
     created_at,
     updated_at
 FROM users
-WHERE player_id = ?;
+WHERE player_id = ? AND deleted_at IS NULL;
@@ -0,0 +1,12 @@
+SELECT
+    id,
+    api_key,
+    player_id,
+    alliance_id,
+    language,
+    role,
+    last_activity_at,
+    created_at,
+    updated_at
+FROM users
+WHERE id = ? AND deleted_at IS NOT NULL;
@@ -0,0 +1,20 @@
+-- Query für GET /api/admin/users
+-- Optionale Namenssuche (?1) und Pagination (?2 = Limit, -1 = alle; ?3 = Offset)
+SELECT
+    u.id,
+    u.player_id,
+    u.alliance_id,
+    u.language,
+    u.role,
+    u.last_activity_at,
+    u.created_at,
+    u.updated_at,
+    u.deleted_at,
+    p.name AS player_name,
+    a.name AS alliance_name
+FROM users u
+LEFT JOIN players p ON u.player_id = p.id
+LEFT JOIN alliances a ON u.alliance_id = a.id
+WHERE (?1 IS NULL OR p.name LIKE '%' || ?1 || '%')
+ORDER BY u.id
+LIMIT ?2 OFFSET ?3;
@@ -0,0 +1,3 @@
+UPDATE users
+SET deleted_at = NULL, updated_at = datetime('now')
+WHERE id = ? AND deleted_at IS NOT NULL;
@@ -0,0 +1,3 @@
+UPDATE users
+SET player_id = ?, updated_at = datetime('now')
+WHERE id = ? AND deleted_at IS NULL;
@@ -1,12 +1,14 @@
 use axum::{
-    extract::Request,
+    extract::{Request, State},
     http::header,
     middleware::Next,
     response::Response,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 use crate::db::models::UserRow;
+use crate::db::timestamp::parse_db_timestamp;
 use crate::db::queries::users;
+use crate::api::state::AppState;
 use super::error::AppError;
 
 #[derive(Clone)]
@@ -20,7 +22,33 @@ pub fn mask_api_key(key: &str) -> String {
     format!("{}...{}", &key[..4], &key[key.len()-4..])
 }
 
+/// Auth schemes accepted in front of the key (matched case-insensitively)
+const AUTH_SCHEMES: &[&str] = &["Bearer", "ApiKey"];
+
+/// Extract the key from a header value: `Bearer <key>`, `ApiKey <key>` or a raw key
+fn strip_auth_scheme(value: &str) -> &str {
+    let value = value.trim();
+    if let Some((scheme, key)) = value.split_once(char::is_whitespace)
+        && AUTH_SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme))
+    {
+        return key.trim();
+    }
+    value
+}
+
+/// Check whether a key last used at `last_used` has outlived the TTL.
+/// A TTL of 0 or an unparsable timestamp never expires.
+fn is_key_expired(last_used: Option<&str>, ttl_days: u32, now: chrono::NaiveDateTime) -> bool {
+    if ttl_days == 0 {
+        return false;
+    }
+    last_used
+        .and_then(parse_db_timestamp)
+        .is_some_and(|ts| now - ts > chrono::Duration::days(ttl_days as i64))
+}
+
 pub async fn auth_middleware(
+    State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
@@ -29,7 +57,7 @@ pub async fn auth_middleware(
         .get("X-API-Key")
         .or_else(|| request.headers().get(header::AUTHORIZATION))
         .and_then(|v| v.to_str().ok())
-        .map(|s| s.trim_start_matches("Bearer ").trim().to_string());
+        .map(|s| strip_auth_scheme(s).to_string());
 
     debug!(api_key_masked = ?api_key.as_ref().map(|k| mask_api_key(k)), "Auth: extracted API key");
 
@@ -38,19 +66,56 @@ pub async fn auth_middleware(
         return Err(AppError::Unauthorized);
     };
 
-    let Some(user) = users::get_by_api_key(&api_key).await? else {
+    let Some(user) = users::get_by_api_key(&state.pool, &api_key).await? else {
         debug!(api_key_masked = %mask_api_key(&api_key), "Auth: API key not found in database");
         return Err(AppError::Unauthorized);
     };
 
+    // Keys unused for longer than API_KEY_TTL_DAYS are treated as abandoned
+    let last_used = user.last_activity_at.as_deref().or(user.created_at.as_deref());
+    if is_key_expired(last_used, state.config.api_key_ttl_days, chrono::Utc::now().naive_utc()) {
+        warn!(user_id = user.id, ?last_used, "Auth: API key expired due to inactivity");
+        return Err(AppError::Unauthorized);
+    }
+
     debug!(user_id = user.id, "Auth: user authenticated");
 
+    // Attribute all further log lines of this request to the user
+    tracing::Span::current().record("user_id", user.id);
+
     // Update last activity (fire and forget)
     let user_id = user.id;
+    let pool = state.pool.clone();
     tokio::spawn(async move {
-        let _ = users::update_activity(user_id).await;
+        let _ = users::update_activity(&pool, user_id).await;
     });
 
     request.extensions_mut().insert(AuthUser(user));
     Ok(next.run(request).await)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_auth_scheme() {
+        assert_eq!(strip_auth_scheme("Bearer abc123"), "abc123");
+        assert_eq!(strip_auth_scheme("bearer abc123"), "abc123");
+        assert_eq!(strip_auth_scheme("ApiKey abc123"), "abc123");
+        assert_eq!(strip_auth_scheme("APIKEY  abc123 "), "abc123");
+        assert_eq!(strip_auth_scheme("abc123"), "abc123");
+        assert_eq!(strip_auth_scheme(" abc123 "), "abc123");
+        // Unknown schemes are not stripped
+        assert_eq!(strip_auth_scheme("Basic abc123"), "Basic abc123");
+    }
+
+    #[test]
+    fn test_key_expiry() {
+        let now = chrono::NaiveDateTime::parse_from_str("2025-03-31 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert!(!is_key_expired(Some("2020-01-01 00:00:00"), 0, now));
+        assert!(!is_key_expired(Some("2025-03-01 12:00:00"), 30, now));
+        assert!(is_key_expired(Some("2025-03-01 11:59:59"), 30, now));
+        assert!(!is_key_expired(None, 30, now));
+    }
+}
@@ -1,4 +1,5 @@
 use axum::{
+    extract::rejection::JsonRejection,
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
@@ -11,6 +12,7 @@ pub enum AppError {
     Unauthorized,
     Forbidden,
     NotFound(String),
+    MethodNotAllowed,
     BadRequest(String),
     Internal(String),
     Database(sqlx::Error),
@@ -40,6 +42,11 @@ impl IntoResponse for AppError {
                 "not_found",
                 msg,
             ),
+            AppError::MethodNotAllowed => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method_not_allowed",
+                "HTTP-Methode für diese Route nicht erlaubt".to_string(),
+            ),
             AppError::BadRequest(msg) => (
                 StatusCode::BAD_REQUEST,
                 "bad_request",
@@ -74,4 +81,10 @@ impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
         AppError::Database(e)
     }
-}
\ No newline at end of file
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::BadRequest(rejection.body_text())
+    }
+}
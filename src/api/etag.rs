@@ -0,0 +1,57 @@
+//! ETag helpers for conditional GET on read-heavy endpoints
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Build a strong ETag from values that change whenever the response changes
+pub fn compute<T: Hash>(version: &T) -> String {
+    let mut hasher = DefaultHasher::new();
+    version.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+/// Check whether the request's If-None-Match header matches the ETag
+pub fn is_fresh(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| matches_if_none_match(v, etag))
+}
+
+fn matches_if_none_match(header_value: &str, etag: &str) -> bool {
+    header_value
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Return 304 if the client already has this version, otherwise the full response
+pub fn respond<T: IntoResponse>(headers: &HeaderMap, etag: &str, body: impl FnOnce() -> T) -> Response {
+    let etag_value = HeaderValue::from_str(etag).unwrap_or_else(|_| HeaderValue::from_static("\"\""));
+
+    if is_fresh(headers, etag) {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag_value)]).into_response();
+    }
+
+    ([(header::ETAG, etag_value)], body()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = compute(&("2025-01-01 10:00:00", 42));
+        assert!(matches_if_none_match(&etag, &etag));
+        assert!(matches_if_none_match(&format!("W/{}", etag), &etag));
+        assert!(matches_if_none_match(&format!("\"other\", {}", etag), &etag));
+        assert!(matches_if_none_match("*", &etag));
+        assert!(!matches_if_none_match("\"other\"", &etag));
+    }
+}
@@ -0,0 +1,26 @@
+//! Request extractors that report failures as `AppError`
+
+use axum::extract::{FromRequest, Request};
+use axum::Json;
+use serde::de::DeserializeOwned;
+use crate::api::error::AppError;
+
+/// JSON body extractor for API requests.
+///
+/// Unlike axum's `Json`, a body that is malformed or does not match the request type
+/// (unknown enum variant, unknown field, wrong value type) is rejected with the API's
+/// 400 JSON error instead of a plain-text 422.
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        Ok(ApiJson(value))
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use tracing::warn;
+
+use crate::api::error::AppError;
+
+/// A category of pr0game element IDs (the `data-info` suffixes in the game HTML)
+pub trait GameIdCategory {
+    const NAME: &'static str;
+    const IDS: &'static [&'static str];
+}
+
+pub struct Resources;
+pub struct Buildings;
+pub struct Research;
+pub struct Fleet;
+pub struct Defense;
+
+impl GameIdCategory for Resources {
+    const NAME: &'static str = "resources";
+    // Metal, crystal, deuterium, dark matter, energy
+    const IDS: &'static [&'static str] = &["901", "902", "903", "904", "911"];
+}
+
+impl GameIdCategory for Buildings {
+    const NAME: &'static str = "buildings";
+    const IDS: &'static [&'static str] = &[
+        "1", "2", "3", "4", "6", "12", "14", "15", "21", "22", "23", "24", "31", "33", "34", "41",
+        "42", "43", "44",
+    ];
+}
+
+impl GameIdCategory for Research {
+    const NAME: &'static str = "research";
+    const IDS: &'static [&'static str] = &[
+        "106", "108", "109", "110", "111", "113", "114", "115", "117", "118", "120", "121", "122",
+        "123", "124", "131", "132", "133", "199",
+    ];
+}
+
+impl GameIdCategory for Fleet {
+    const NAME: &'static str = "fleet";
+    const IDS: &'static [&'static str] = &[
+        "202", "203", "204", "205", "206", "207", "208", "209", "210", "211", "212", "213", "214",
+        "215", "222", "225", "227",
+    ];
+}
+
+impl GameIdCategory for Defense {
+    const NAME: &'static str = "defense";
+    const IDS: &'static [&'static str] = &[
+        "401", "402", "403", "404", "405", "406", "407", "408", "502", "503",
+    ];
+}
+
+/// `{ game_id: amount }` map that only keeps known IDs with non-negative values.
+///
+/// Invalid entries are dropped while deserializing and remembered, so handlers can
+/// either ignore them or reject the request via [`GameIdMap::check`].
+pub struct GameIdMap<C> {
+    map: HashMap<String, i64>,
+    rejected: Vec<(String, i64)>,
+    _category: PhantomData<C>,
+}
+
+pub type ResourceMap = GameIdMap<Resources>;
+pub type BuildingMap = GameIdMap<Buildings>;
+pub type ResearchMap = GameIdMap<Research>;
+pub type FleetMap = GameIdMap<Fleet>;
+pub type DefenseMap = GameIdMap<Defense>;
+
+impl<C: GameIdCategory> GameIdMap<C> {
+    /// Entries dropped because of an unknown ID or a negative value
+    pub fn rejected(&self) -> &[(String, i64)] {
+        &self.rejected
+    }
+
+    /// Fail with 400 in strict mode if anything was dropped, otherwise just log it
+    pub fn check(&self, strict: bool) -> Result<(), AppError> {
+        if self.rejected.is_empty() {
+            return Ok(());
+        }
+
+        let entries = self
+            .rejected
+            .iter()
+            .map(|(id, value)| format!("{}={}", id, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if strict {
+            return Err(AppError::BadRequest(format!(
+                "Ungültige Einträge in {}: {}",
+                C::NAME,
+                entries
+            )));
+        }
+
+        warn!(category = C::NAME, entries, "Dropped invalid game ID entries");
+        Ok(())
+    }
+}
+
+impl<C> Deref for GameIdMap<C> {
+    type Target = HashMap<String, i64>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<C> fmt::Debug for GameIdMap<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.map.fmt(f)
+    }
+}
+
+impl<'de, C: GameIdCategory> Deserialize<'de> for GameIdMap<C> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = HashMap::<String, i64>::deserialize(deserializer)?;
+
+        let mut map = HashMap::with_capacity(raw.len());
+        let mut rejected = Vec::new();
+        for (id, value) in raw {
+            if value >= 0 && C::IDS.contains(&id.as_str()) {
+                map.insert(id, value);
+            } else {
+                rejected.push((id, value));
+            }
+        }
+        rejected.sort();
+
+        Ok(Self {
+            map,
+            rejected,
+            _category: PhantomData,
+        })
+    }
+}
+
+impl<C> Serialize for GameIdMap<C> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_entries_dropped() {
+        let map: FleetMap = serde_json::from_str(r#"{"202": 10, "99999": 5, "203": -5}"#).unwrap();
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("202"), Some(&10));
+        assert_eq!(map.rejected(), &[("203".to_string(), -5), ("99999".to_string(), 5)]);
+
+        assert!(map.check(false).is_ok());
+        assert!(matches!(map.check(true), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_ids_cover_locale_game_ids() {
+        let locale: serde_json::Value =
+            serde_json::from_str(include_str!("../../locales/en.json")).unwrap();
+
+        let categories: [(&str, &[&str]); 5] = [
+            ("resources", Resources::IDS),
+            ("buildings", Buildings::IDS),
+            ("research", Research::IDS),
+            ("ships", Fleet::IDS),
+            ("defense", Defense::IDS),
+        ];
+
+        for (name, ids) in categories {
+            let names = locale["gameIds"][name].as_object().unwrap();
+            // Only numeric keys are element IDs ("metal" etc. are plain labels)
+            for id in names.keys().filter(|k| k.parse::<u32>().is_ok()) {
+                assert!(ids.contains(&id.as_str()), "{name}: {id}");
+            }
+        }
+    }
+}
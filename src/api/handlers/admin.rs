@@ -1,18 +1,23 @@
 use axum::{
-    extract::{Extension, Path},
+    extract::{Extension, Path, Query, State},
     Json,
 };
 use serde::Deserialize;
+use std::time::Instant;
 use tracing::info;
 use uuid::Uuid;
 
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
+use crate::api::extract::ApiJson;
+use crate::api::state::AppState;
 use crate::api::response::{
-    AdminCheckResponse, AdminUserCreatedResponse, AdminUserInfo, AdminUsersResponse, SuccessResponse,
+    AdminCheckResponse, AdminUserCreatedResponse, BulkCreateUsersResponse, BulkUserResult, AdminUserInfo, AdminUsersResponse, MaintenanceResponse,
+    AuditLogEntry, AuditLogResponse, ConfigEntry, RawConfigResponse, SuccessResponse,
 };
 use crate::db::models::UserRole;
-use crate::db::queries::{config, players, users};
+use crate::db::queries::{audit, config, maintenance, players, users};
+use sqlx::SqlitePool;
 
 /// Helper function to check if user is admin
 fn require_admin(user: &crate::db::models::UserRow) -> Result<(), AppError> {
@@ -31,13 +36,39 @@ pub async fn check_admin(
     }))
 }
 
-/// GET /api/admin/users - List all users (admin only)
+/// GET /api/admin/users?page=&page_size=&q= - List users (admin only)
+///
+/// Without `page_size` all matching users are returned on a single page.
+#[derive(Deserialize)]
+pub struct ListUsersQuery {
+    pub q: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    pub page_size: Option<i64>,
+}
+
+fn default_page() -> i64 { 1 }
+
+const MAX_PAGE_SIZE: i64 = 200;
+
 pub async fn list_users(
     Extension(AuthUser(user)): Extension<AuthUser>,
+    Query(query): Query<ListUsersQuery>,
 ) -> Result<Json<AdminUsersResponse>, AppError> {
     require_admin(&user)?;
 
-    let user_rows = users::get_all().await?;
+    let q = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let page = query.page.max(1);
+    let total = users::count(q).await?;
+
+    let (user_rows, total_pages) = match query.page_size {
+        Some(size) => {
+            let size = size.clamp(1, MAX_PAGE_SIZE);
+            let rows = users::get_paged(q, size, (page - 1) * size).await?;
+            (rows, (total + size - 1) / size)
+        }
+        None => (users::get_paged(q, -1, 0).await?, 1),
+    };
 
     let users: Vec<AdminUserInfo> = user_rows
         .into_iter()
@@ -51,10 +82,16 @@ pub async fn list_users(
             role: u.role.as_str().to_string(),
             last_activity_at: u.last_activity_at,
             created_at: u.created_at,
+            deleted_at: u.deleted_at,
         })
         .collect();
 
-    Ok(Json(AdminUsersResponse { users }))
+    Ok(Json(AdminUsersResponse {
+        users,
+        total,
+        page,
+        total_pages,
+    }))
 }
 
 /// POST /api/admin/users - Create a new user (admin only)
@@ -69,61 +106,154 @@ pub struct CreateUserRequest {
 }
 
 pub async fn create_user(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<CreateUserRequest>,
+    ApiJson(req): ApiJson<CreateUserRequest>,
 ) -> Result<Json<AdminUserCreatedResponse>, AppError> {
     require_admin(&user)?;
 
     // Resolve player_id from name if not provided
     let player_id = match (req.player_id, req.player_name) {
         (Some(id), _) => Some(id),
-        (None, Some(name)) => {
-            let player = players::get_by_name(&name)
-                .await?
-                .ok_or_else(|| AppError::NotFound(format!("Spieler '{}' nicht gefunden", name)))?;
-            Some(player.id)
-        }
+        (None, Some(name)) => match resolve_player_name(&state.pool, &name).await? {
+            PlayerLookup::Available(id) => Some(id),
+            PlayerLookup::HasUser(_) => return Err(user_exists_error()),
+            PlayerLookup::NotFound => {
+                return Err(AppError::NotFound(format!("Spieler '{}' nicht gefunden", name)));
+            }
+        },
         (None, None) => None,
     };
 
     // Check if user already exists for this player
-    if let Some(pid) = player_id {
-        if users::get_by_player_id(pid).await?.is_some() {
-            return Err(AppError::BadRequest(
-                "Für diesen Spieler existiert bereits ein User".into(),
-            ));
-        }
+    if let Some(pid) = player_id
+        && users::get_by_player_id(&state.pool, pid).await?.is_some()
+    {
+        return Err(user_exists_error());
     }
 
+    let (user_id, api_key) = insert_user(&state.pool, user.id, player_id, req.alliance_id).await?;
+
+    Ok(Json(AdminUserCreatedResponse {
+        success: true,
+        user_id,
+        api_key,
+    }))
+}
+
+/// Which player a name refers to and whether it already has a user
+enum PlayerLookup {
+    Available(i64),
+    HasUser(i64),
+    NotFound,
+}
+
+async fn resolve_player_name(pool: &SqlitePool, name: &str) -> Result<PlayerLookup, AppError> {
+    let Some(player) = players::get_by_name(pool, name).await? else {
+        return Ok(PlayerLookup::NotFound);
+    };
+    if users::get_by_player_id(pool, player.id).await?.is_some() {
+        return Ok(PlayerLookup::HasUser(player.id));
+    }
+    Ok(PlayerLookup::Available(player.id))
+}
+
+fn user_exists_error() -> AppError {
+    AppError::BadRequest("Für diesen Spieler existiert bereits ein User".into())
+}
+
+/// Create a user with a fresh API key and record it in the audit log
+async fn insert_user(
+    pool: &SqlitePool,
+    admin_id: i64,
+    player_id: Option<i64>,
+    alliance_id: Option<i64>,
+) -> Result<(i64, String), AppError> {
     // Generate API key
     let api_key = Uuid::new_v4().to_string();
 
     // Create user
-    let user_id = users::create(&api_key, player_id, req.alliance_id).await?;
+    let user_id = users::create(pool, &api_key, player_id, alliance_id).await?;
 
     // Also ensure player exists and set alliance_id
     if let Some(pid) = player_id {
         // Get player name if we have it
-        let player_name = players::get_by_id(pid).await?.map(|p| p.name).unwrap_or_default();
-        players::ensure_exists(pid, &player_name).await?;
+        let player_name = players::get_by_id(pool, pid).await?.map(|p| p.name).unwrap_or_default();
+        players::ensure_exists(pool, pid, &player_name).await?;
 
-        if let Some(alliance_id) = req.alliance_id {
-            players::update_alliance(pid, alliance_id).await?;
+        if let Some(alliance_id) = alliance_id {
+            players::update_alliance(pool, pid, alliance_id).await?;
         }
     }
 
     info!(user_id, ?player_id, "Admin created new user");
+    let target = format!("user:{}", user_id);
+    let detail = player_id.map(|pid| format!("player_id={}", pid));
+    audit::record(pool, admin_id, "create_user", Some(&target), detail.as_deref()).await;
 
-    Ok(Json(AdminUserCreatedResponse {
-        success: true,
-        user_id,
-        api_key,
+    Ok((user_id, api_key))
+}
+
+/// POST /api/admin/users/bulk?dry_run= - Create users for a list of player names (admin only)
+///
+/// With `dry_run=true` names are only resolved, nothing is created and no keys are issued.
+#[derive(Deserialize)]
+pub struct BulkCreateUsersRequest {
+    pub names: Vec<String>,
+    /// Alliance ID applied to every created user (optional)
+    pub alliance_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkCreateQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+const MAX_BULK_NAMES: usize = 200;
+
+pub async fn bulk_create_users(
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    Query(query): Query<BulkCreateQuery>,
+    ApiJson(req): ApiJson<BulkCreateUsersRequest>,
+) -> Result<Json<BulkCreateUsersResponse>, AppError> {
+    require_admin(&user)?;
+
+    if req.names.len() > MAX_BULK_NAMES {
+        return Err(AppError::BadRequest(format!("Maximal {} Namen pro Anfrage", MAX_BULK_NAMES)));
+    }
+
+    let mut results = Vec::with_capacity(req.names.len());
+    for name in req.names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        let (status, player_id, created) = match resolve_player_name(&state.pool, name).await? {
+            PlayerLookup::NotFound => ("not_found", None, None),
+            PlayerLookup::HasUser(id) => ("skipped", Some(id), None),
+            PlayerLookup::Available(id) if query.dry_run => ("would_create", Some(id), None),
+            PlayerLookup::Available(id) => {
+                ("created", Some(id), Some(insert_user(&state.pool, user.id, Some(id), req.alliance_id).await?))
+            }
+        };
+        let (user_id, api_key) = created.unzip();
+        results.push(BulkUserResult {
+            name: name.to_string(),
+            status,
+            player_id,
+            user_id,
+            api_key,
+        });
+    }
+
+    Ok(Json(BulkCreateUsersResponse {
+        dry_run: query.dry_run,
+        results,
     }))
 }
 
-/// DELETE /api/admin/users/{id} - Delete a user (admin only)
+/// DELETE /api/admin/users/{id} - Soft-delete a user (admin only)
 pub async fn delete_user(
     Path(user_id): Path<i64>,
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     require_admin(&user)?;
@@ -142,6 +272,60 @@ pub async fn delete_user(
     }
 
     info!(user_id, admin_id = user.id, "Admin deleted user");
+    audit::record(&state.pool, user.id, "delete_user", Some(&format!("user:{}", user_id)), None).await;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// POST /api/admin/users/{id}/restore - Undo a soft delete (admin only)
+pub async fn restore_user(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    require_admin(&user)?;
+
+    let Some(deleted) = users::get_deleted_by_id(&state.pool, user_id).await? else {
+        return Err(AppError::NotFound("Gelöschter User nicht gefunden".into()));
+    };
+
+    // The player may have been given a new user since the delete
+    if let Some(pid) = deleted.player_id
+        && users::get_by_player_id(&state.pool, pid).await?.is_some()
+    {
+        return Err(user_exists_error());
+    }
+
+    if !users::restore(&state.pool, user_id).await? {
+        return Err(AppError::NotFound("Gelöschter User nicht gefunden".into()));
+    }
+
+    info!(user_id, admin_id = user.id, "Admin restored user");
+    audit::record(&state.pool, user.id, "restore_user", Some(&format!("user:{}", user_id)), None).await;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// DELETE /api/admin/users/{id}/purge - Permanently delete a user (admin only)
+pub async fn purge_user(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    require_admin(&user)?;
+
+    if user_id == user.id {
+        return Err(AppError::BadRequest(
+            "Du kannst dich nicht selbst löschen".into(),
+        ));
+    }
+
+    if !users::purge(user_id).await? {
+        return Err(AppError::NotFound("User nicht gefunden".into()));
+    }
+
+    info!(user_id, admin_id = user.id, "Admin purged user");
+    audit::record(&state.pool, user.id, "purge_user", Some(&format!("user:{}", user_id)), None).await;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -154,8 +338,9 @@ pub struct UpdateRoleRequest {
 
 pub async fn update_user_role(
     Path(user_id): Path<i64>,
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<UpdateRoleRequest>,
+    ApiJson(req): ApiJson<UpdateRoleRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     require_admin(&user)?;
 
@@ -163,7 +348,10 @@ pub async fn update_user_role(
     if user_id == user.id && req.role != "admin" {
         // Check if there are other admins
         let all_users = users::get_all().await?;
-        let admin_count = all_users.iter().filter(|u| u.role == UserRole::Admin).count();
+        let admin_count = all_users
+            .iter()
+            .filter(|u| u.role == UserRole::Admin && u.deleted_at.is_none())
+            .count();
         if admin_count <= 1 {
             return Err(AppError::BadRequest(
                 "Du bist der letzte Admin und kannst dich nicht selbst herabstufen".into(),
@@ -184,6 +372,46 @@ pub async fn update_user_role(
     }
 
     info!(user_id, ?role, admin_id = user.id, "Admin updated user role");
+    let detail = format!("role={}", role.as_str());
+    audit::record(&state.pool, user.id, "update_role", Some(&format!("user:{}", user_id)), Some(&detail)).await;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// PUT /api/admin/users/{id}/player - Reassign a user to another player (admin only)
+#[derive(Deserialize)]
+pub struct UpdatePlayerRequest {
+    pub player_id: i64,
+}
+
+pub async fn update_user_player(
+    Path(user_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    ApiJson(req): ApiJson<UpdatePlayerRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    require_admin(&user)?;
+
+    if players::get_by_id(&state.pool, req.player_id).await?.is_none() {
+        return Err(AppError::NotFound(format!("Spieler {} nicht gefunden", req.player_id)));
+    }
+
+    // A player can only belong to one user
+    if let Some(existing) = users::get_by_player_id(&state.pool, req.player_id).await?
+        && existing.id != user_id
+    {
+        return Err(AppError::BadRequest(
+            "Für diesen Spieler existiert bereits ein User".into(),
+        ));
+    }
+
+    if !users::set_player_id(user_id, req.player_id).await? {
+        return Err(AppError::NotFound("User nicht gefunden".into()));
+    }
+
+    info!(user_id, player_id = req.player_id, admin_id = user.id, "Admin reassigned user to player");
+    let detail = format!("player_id={}", req.player_id);
+    audit::record(&state.pool, user.id, "update_player", Some(&format!("user:{}", user_id)), Some(&detail)).await;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -191,6 +419,7 @@ pub async fn update_user_role(
 /// GET /api/admin/users/{id}/apikey - Get API key for a user (admin only)
 pub async fn get_user_api_key(
     Path(user_id): Path<i64>,
+    State(state): State<AppState>,
     Extension(AuthUser(admin)): Extension<AuthUser>,
 ) -> Result<Json<ApiKeyResponse>, AppError> {
     require_admin(&admin)?;
@@ -206,7 +435,7 @@ pub async fn get_user_api_key(
         .and_then(|u| u.player_id)
         .ok_or_else(|| AppError::NotFound("User nicht gefunden".into()))?;
 
-    let user_row = users::get_by_player_id(player_id)
+    let user_row = users::get_by_player_id(&state.pool, player_id)
         .await?
         .ok_or_else(|| AppError::NotFound("User nicht gefunden".into()))?;
 
@@ -229,32 +458,308 @@ pub struct UpdateConfigRequest {
 }
 
 pub async fn update_config(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<UpdateConfigRequest>,
+    ApiJson(req): ApiJson<UpdateConfigRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     require_admin(&user)?;
 
     // Validate values
     if let Some(galaxies) = req.galaxies {
-        if galaxies < 1 || galaxies > 20 {
+        if !(1..=20).contains(&galaxies) {
             return Err(AppError::BadRequest("Galaxien muss zwischen 1 und 20 sein".into()));
         }
         config::set_config("galaxies", &galaxies.to_string()).await?;
         info!(galaxies, admin_id = user.id, "Admin updated galaxies config");
+        audit::record(&state.pool, user.id, "update_config", Some("galaxies"), Some(&galaxies.to_string())).await;
     }
 
     if let Some(systems) = req.systems {
-        if systems < 1 || systems > 999 {
+        if !(1..=999).contains(&systems) {
             return Err(AppError::BadRequest("Systeme muss zwischen 1 und 999 sein".into()));
         }
         config::set_config("systems", &systems.to_string()).await?;
         info!(systems, admin_id = user.id, "Admin updated systems config");
+        audit::record(&state.pool, user.id, "update_config", Some("systems"), Some(&systems.to_string())).await;
     }
 
     if let Some(galaxy_wrapped) = req.galaxy_wrapped {
         config::set_config("galaxy_wrapped", if galaxy_wrapped { "true" } else { "false" }).await?;
         info!(galaxy_wrapped, admin_id = user.id, "Admin updated galaxy_wrapped config");
+        audit::record(&state.pool, user.id, "update_config", Some("galaxy_wrapped"), Some(&galaxy_wrapped.to_string())).await;
     }
 
     Ok(Json(SuccessResponse { success: true }))
 }
+
+/// Value format a raw config key accepts
+enum ConfigValueKind {
+    /// Integer within the inclusive range
+    Int(i64, i64),
+    Bool,
+    /// Free text up to `MAX_RAW_CONFIG_VALUE_LEN` characters
+    Text,
+}
+
+/// Config keys that may be written through the raw config endpoint.
+/// Entries ending in `.` allow every key below that prefix (e.g. `custom.motd`).
+const RAW_CONFIG_KEYS: &[(&str, ConfigValueKind)] = &[
+    ("galaxies", ConfigValueKind::Int(1, 20)),
+    ("systems", ConfigValueKind::Int(1, 999)),
+    ("galaxy_wrapped", ConfigValueKind::Bool),
+    ("custom.", ConfigValueKind::Text),
+];
+
+const MAX_RAW_CONFIG_VALUE_LEN: usize = 1000;
+
+/// Look up the value format for a key, exact keys first, then prefixes
+fn raw_config_kind(key: &str) -> Option<&'static ConfigValueKind> {
+    RAW_CONFIG_KEYS.iter().find_map(|(pattern, kind)| {
+        let matches = if pattern.ends_with('.') {
+            key.strip_prefix(pattern).is_some_and(|rest| {
+                !rest.is_empty() && rest.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.')
+            })
+        } else {
+            key == *pattern
+        };
+        matches.then_some(kind)
+    })
+}
+
+/// Validate a raw config value for its key (same ranges as `update_config`)
+fn validate_config_value(key: &str, value: &str) -> Result<(), AppError> {
+    let Some(kind) = raw_config_kind(key) else {
+        let allowed: Vec<String> = RAW_CONFIG_KEYS
+            .iter()
+            .map(|(pattern, _)| if pattern.ends_with('.') { format!("{}*", pattern) } else { pattern.to_string() })
+            .collect();
+        return Err(AppError::BadRequest(format!(
+            "Unbekannter Schlüssel '{}'. Erlaubt: {}",
+            key,
+            allowed.join(", ")
+        )));
+    };
+
+    let valid = match kind {
+        ConfigValueKind::Int(min, max) => value.parse::<i64>().is_ok_and(|v| (*min..=*max).contains(&v)),
+        ConfigValueKind::Bool => matches!(value, "true" | "false" | "1" | "0"),
+        ConfigValueKind::Text => !value.is_empty() && value.chars().count() <= MAX_RAW_CONFIG_VALUE_LEN,
+    };
+
+    if !valid {
+        return Err(AppError::BadRequest(format!("Ungültiger Wert für '{}': '{}'", key, value)));
+    }
+    Ok(())
+}
+
+/// GET /api/admin/config/raw - All config key/value rows (admin only)
+pub async fn get_raw_config(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<RawConfigResponse>, AppError> {
+    require_admin(&user)?;
+
+    let entries = config::get_all()
+        .await?
+        .into_iter()
+        .map(|row| ConfigEntry { key: row.key, value: row.value })
+        .collect();
+
+    Ok(Json(RawConfigResponse { entries }))
+}
+
+/// PUT /api/admin/config/raw - Set a single config key (admin only)
+#[derive(Deserialize)]
+pub struct RawConfigRequest {
+    pub key: String,
+    pub value: String,
+}
+
+pub async fn update_raw_config(
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    ApiJson(req): ApiJson<RawConfigRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    require_admin(&user)?;
+
+    let value = req.value.trim();
+    validate_config_value(&req.key, value)?;
+
+    config::set_config(&req.key, value).await?;
+    info!(key = %req.key, value, admin_id = user.id, "Admin updated raw config");
+    audit::record(&state.pool, user.id, "update_config", Some(&req.key), Some(value)).await;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// POST /api/admin/maintenance - Run VACUUM and ANALYZE (admin only)
+///
+/// sqlx runs SQLite statements on its own worker thread, so the runtime is not blocked
+/// while the database file is rebuilt.
+pub async fn run_maintenance(
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<MaintenanceResponse>, AppError> {
+    require_admin(&user)?;
+
+    let start = Instant::now();
+    maintenance::vacuum(&state.pool).await?;
+    let vacuum_ms = start.elapsed().as_millis() as u64;
+
+    let start = Instant::now();
+    maintenance::analyze(&state.pool).await?;
+    let analyze_ms = start.elapsed().as_millis() as u64;
+
+    info!(vacuum_ms, analyze_ms, admin_id = user.id, "Admin ran database maintenance");
+    audit::record(&state.pool, user.id, "maintenance", None, None).await;
+
+    Ok(Json(MaintenanceResponse {
+        success: true,
+        vacuum_ms,
+        analyze_ms,
+    }))
+}
+
+/// GET /api/admin/audit?limit=N - Most recent admin actions (admin only)
+#[derive(Deserialize)]
+pub struct AuditQuery {
+    #[serde(default = "default_audit_limit")]
+    pub limit: i64,
+}
+
+fn default_audit_limit() -> i64 { 100 }
+
+pub async fn get_audit_log(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditLogResponse>, AppError> {
+    require_admin(&user)?;
+
+    let limit = query.limit.clamp(1, 1000);
+    let entries = audit::get_recent(limit)
+        .await?
+        .into_iter()
+        .map(|row| AuditLogEntry {
+            id: row.id,
+            actor_id: row.actor_id,
+            actor_name: row.actor_name,
+            action: row.action,
+            target: row.target,
+            detail: row.detail,
+            created_at: row.created_at.unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(AuditLogResponse { entries }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use crate::api::test_support::{create_admin, create_player, post_json, test_app, ADMIN_API_KEY};
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value("galaxies", "9").is_ok());
+        assert!(validate_config_value("galaxies", "21").is_err());
+        assert!(validate_config_value("galaxy_wrapped", "yes").is_err());
+        assert!(validate_config_value("custom.motd", "Willkommen").is_ok());
+        assert!(validate_config_value("custom.motd", "").is_err());
+        assert!(validate_config_value("custom.", "x").is_err());
+        assert!(validate_config_value("custom.Motd", "x").is_err());
+        assert!(validate_config_value("database_url", "x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_restore_refuses_player_with_active_user() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            "INSERT INTO users (id, api_key, player_id, deleted_at) VALUES (10, 'old-key', 42, datetime('now'));
+             INSERT INTO users (id, api_key, player_id) VALUES (11, 'new-key', 42)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, body) = post_json(&app, "/api/admin/users/10/restore", "{}", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "Für diesen Spieler existiert bereits ein User");
+
+        sqlx::query("UPDATE users SET deleted_at = datetime('now') WHERE id = 11")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let (status, _) = post_json(&app, "/api/admin/users/10/restore", "{}", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_create_users_dry_run_then_create() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            "INSERT INTO players (id, name) VALUES (43, 'Alice');
+             INSERT INTO users (api_key, player_id) VALUES ('alice-key', 43)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let users_count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap()
+        };
+        let body = r#"{"names": ["Bob", "Alice", "Nobody"]}"#;
+
+        let (status, dry) = post_json(&app, "/api/admin/users/bulk?dry_run=true", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(dry["dry_run"], true);
+        let statuses: Vec<&str> = dry["results"].as_array().unwrap().iter().map(|r| r["status"].as_str().unwrap()).collect();
+        assert_eq!(statuses, ["would_create", "skipped", "not_found"]);
+        assert!(dry["results"].as_array().unwrap().iter().all(|r| r["api_key"].is_null()));
+        // Nothing created: still only the admin and Alice
+        assert_eq!(users_count().await, 2);
+
+        let (status, real) = post_json(&app, "/api/admin/users/bulk", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(real["dry_run"], false);
+        assert_eq!(real["results"][0]["status"], "created");
+        assert_eq!(real["results"][0]["player_id"], 42);
+        assert!(real["results"][0]["api_key"].is_string());
+        assert_eq!(real["results"][1]["status"], "skipped");
+        assert_eq!(users_count().await, 3);
+    }
+    #[tokio::test]
+    async fn test_maintenance_runs_analyze_and_is_audited() {
+        let (app, pool) = test_app().await;
+        let admin_id = create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query("INSERT INTO users (api_key, player_id) VALUES ('user-key', 42)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (status, _) = post_json(&app, "/api/admin/maintenance", "{}", Some("user-key")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, body) = post_json(&app, "/api/admin/maintenance", "{}", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["success"], true);
+        assert!(body["vacuum_ms"].is_u64() && body["analyze_ms"].is_u64());
+
+        // ANALYZE writes the planner statistics into sqlite_stat1
+        let stats: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_stat1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(stats > 0);
+
+        let audited: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE actor_id = ? AND action = 'maintenance'")
+            .bind(admin_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(audited, 1);
+    }
+}
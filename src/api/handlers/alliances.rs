@@ -1,12 +1,37 @@
 use axum::{
-    extract::{Path, Extension},
+    extract::{Path, Query, Extension, State},
     Json,
 };
+use serde::Deserialize;
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
-use crate::api::response::{self, PlanetResponse, ChartResponse};
+use crate::api::response::{
+    self, PlanetResponse, ChartResponse, AllianceDetailsResponse, AllianceRankingEntry, AllianceRankingResponse,
+};
+use crate::api::state::AppState;
 use crate::db::queries::alliances;
 
+/// GET /api/alliances/{id}
+pub async fn get_alliance(
+    State(state): State<AppState>,
+    Path(alliance_id): Path<i64>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<AllianceDetailsResponse>, AppError> {
+    let alliance = alliances::get_by_id(&state.pool, alliance_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Allianz nicht gefunden".into()))?;
+    let (member_count, planet_count) = alliances::get_counts(&state.pool, alliance_id).await?;
+
+    Ok(Json(AllianceDetailsResponse {
+        id: alliance.id,
+        name: alliance.name,
+        tag: alliance.tag,
+        created_at: alliance.created_at,
+        member_count,
+        planet_count,
+    }))
+}
+
 /// GET /api/alliances/{id}/planets
 pub async fn get_planets(
     Path(alliance_id): Path<i64>,
@@ -28,3 +53,62 @@ pub async fn get_chart(
     };
     Ok(Json(response))
 }
+
+/// Query params for GET /api/alliances/ranking
+#[derive(Deserialize)]
+pub struct AllianceRankingQuery {
+    #[serde(default = "default_ranking_limit")]
+    pub limit: i64,
+}
+
+fn default_ranking_limit() -> i64 {
+    50
+}
+
+/// GET /api/alliances/ranking?limit=50
+pub async fn get_ranking(
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+    Query(query): Query<AllianceRankingQuery>,
+) -> Result<Json<AllianceRankingResponse>, AppError> {
+    let limit = query.limit.clamp(1, 500);
+    let rows = alliances::get_ranking(limit).await?;
+
+    let alliances = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| AllianceRankingEntry {
+            rank: i as i64 + 1,
+            id: row.id,
+            name: row.name,
+            tag: row.tag,
+            total_score: row.total_score,
+            member_count: row.member_count,
+        })
+        .collect();
+
+    Ok(Json(AllianceRankingResponse { alliances }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::test_support::{create_admin, create_player, get_json, test_app, ADMIN_API_KEY};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn test_get_alliance() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+
+        let (status, body) = get_json(&app, "/api/alliances/1", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "Test Alliance");
+        assert_eq!(body["tag"], "TA");
+        assert_eq!(body["member_count"], 1);
+        assert_eq!(body["planet_count"], 1);
+
+        let (status, body) = get_json(&app, "/api/alliances/99", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "not_found");
+    }
+}
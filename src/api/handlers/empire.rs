@@ -1,26 +1,33 @@
-use axum::{extract::Extension, Json};
+use axum::{extract::{Extension, State}, Json};
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
-use crate::api::response::SuccessResponse;
-use crate::db::queries::{planets, players};
+use crate::api::extract::ApiJson;
+use crate::api::game_ids::{BuildingMap, DefenseMap, FleetMap, ResearchMap, ResourceMap};
+use crate::api::response::{parse_coordinates, EmpireSyncResponse};
+use crate::api::state::AppState;
+use crate::db::queries::{alliances, planets, players};
+use crate::get_pool;
 use serde::Deserialize;
-use std::collections::HashMap;
 
 /// POST /api/empire
 /// Bulk sync all empire data at once
 #[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EmpireSyncRequest {
     /// Player ID from pr0game
     pub player_id: i64,
     /// Player name
     pub player_name: String,
+    /// Full name of the player's alliance, if the page shows it
+    pub alliance_name: Option<String>,
     /// Research levels (global for player)
-    pub research: HashMap<String, i64>,
+    pub research: ResearchMap,
     /// All planets with their data
     pub planets: Vec<EmpirePlanet>,
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EmpirePlanet {
     /// Planet ID from pr0game (from <option value="XXX">)
     pub external_id: i64,
@@ -36,18 +43,19 @@ pub struct EmpirePlanet {
     /// Points from empire page
     pub points: i64,
     /// Current resources
-    pub resources: HashMap<String, i64>,
+    pub resources: ResourceMap,
     /// Production per hour
     pub production: EmpireProduction,
     /// Building levels
-    pub buildings: HashMap<String, i64>,
+    pub buildings: BuildingMap,
     /// Fleet counts
-    pub fleet: HashMap<String, i64>,
+    pub fleet: FleetMap,
     /// Defense counts
-    pub defense: HashMap<String, i64>,
+    pub defense: DefenseMap,
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
 pub struct EmpireProduction {
     pub metal: i64,
     pub crystal: i64,
@@ -57,9 +65,10 @@ pub struct EmpireProduction {
 }
 
 pub async fn sync_empire(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<EmpireSyncRequest>,
-) -> Result<Json<SuccessResponse>, AppError> {
+    ApiJson(req): ApiJson<EmpireSyncRequest>,
+) -> Result<Json<EmpireSyncResponse>, AppError> {
     // Use player_id from request, or fall back to user's player_id
     let player_id = if req.player_id > 0 {
         req.player_id
@@ -69,37 +78,47 @@ pub async fn sync_empire(
 
     tracing::info!(player_id, planets_count = req.planets.len(), "Empire sync");
 
+    // Validate all coordinates and game IDs up front so a bad entry doesn't leave a half-applied sync
+    let coordinates = req
+        .planets
+        .iter()
+        .map(|p| parse_coordinates(&p.coordinates))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let strict = state.config.strict_game_ids;
+    req.research.check(strict)?;
+    for planet in &req.planets {
+        planet.resources.check(strict)?;
+        planet.buildings.check(strict)?;
+        planet.fleet.check(strict)?;
+        planet.defense.check(strict)?;
+    }
+
     // 1. Ensure player exists and update research
-    players::ensure_exists(player_id, &req.player_name).await?;
+    let pool = get_pool().await;
+    players::ensure_exists(pool, player_id, &req.player_name).await?;
 
     // Update alliance_id from authenticated user
     if let Some(alliance_id) = user.alliance_id {
-        players::update_alliance(player_id, alliance_id).await?;
+        players::update_alliance(pool, player_id, alliance_id).await?;
+        if let Some(alliance_name) = &req.alliance_name {
+            alliances::update_name(pool, alliance_id, alliance_name).await?;
+        }
     }
 
     let research_json = serde_json::to_string(&req.research)
         .map_err(|e| AppError::Internal(e.to_string()))?;
-    players::update_research(player_id, &research_json).await?;
+    let research_updated = players::update_research(pool, player_id, &research_json).await?;
 
     // 2. Sync each planet
-    for planet in &req.planets {
-        // Parse coordinates
-        let parts: Vec<&str> = planet.coordinates.split(':').collect();
-        if parts.len() != 3 {
-            tracing::warn!(coords = planet.coordinates, "Invalid coordinates, skipping");
-            continue;
-        }
-
-        let galaxy: i64 = parts[0].parse().unwrap_or(0);
-        let system: i64 = parts[1].parse().unwrap_or(0);
-        let position: i64 = parts[2].parse().unwrap_or(0);
-
+    let mut new_planets = 0;
+    for (planet, (galaxy, system, position)) in req.planets.iter().zip(coordinates) {
         // Upsert planet with full data
-        planets::upsert_empire(
+        let created = planets::upsert_empire(
             player_id,
             planet.external_id,
             &planet.name,
-            &planet.coordinates,
+            &format!("{}:{}:{}", galaxy, system, position),
             galaxy,
             system,
             position,
@@ -113,8 +132,60 @@ pub async fn sync_empire(
             &planet.fleet,
             &planet.defense,
         ).await?;
+        if created {
+            new_planets += 1;
+        }
     }
 
-    tracing::info!(player_id, "Empire sync complete");
-    Ok(Json(SuccessResponse { success: true }))
+    tracing::info!(player_id, new_planets, research_updated, "Empire sync complete");
+    Ok(Json(EmpireSyncResponse {
+        success: true,
+        planets_synced: req.planets.len(),
+        research_updated,
+        new_planets,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_support::{create_admin, post_json, test_app, ADMIN_API_KEY};
+    use axum::http::StatusCode;
+
+    fn planet(extra: &str) -> String {
+        format!(
+            r#"{{"external_id": 1, "name": "Home", "coordinates": "1:2:3", "fields_used": 10,
+                "fields_max": 163, "temperature": 20, "points": 100, "resources": {{}},
+                "production": {{"metal": 1, "crystal": 1, "deuterium": 1, "energy_used": 0, "energy_max": 0}},
+                "buildings": {{}}, "fleet": {{}}, "defense": {{}}{extra}}}"#
+        )
+    }
+
+    #[test]
+    fn test_empire_sync_rejects_unknown_fields() {
+        let request = |planet: String, extra: &str| {
+            serde_json::from_str::<EmpireSyncRequest>(&format!(
+                r#"{{"player_id": 1, "player_name": "Bob", "research": {{}}, "planets": [{planet}]{extra}}}"#
+            ))
+        };
+
+        assert!(request(planet(""), "").is_ok());
+        assert!(request(planet(""), r#", "alliance": 5"#).is_err());
+        assert!(request(planet(r#", "moon": true"#), "").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_empire_sync_unknown_field_returns_400() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+
+        let body = format!(
+            r#"{{"player_id": 1, "player_name": "Bob", "research": {{}}, "planets": [{}]}}"#,
+            planet(r#", "moon": true"#)
+        );
+        let (status, body) = post_json(&app, "/api/empire", &body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+        assert!(body["message"].as_str().unwrap().contains("moon"));
+    }
 }
@@ -0,0 +1,77 @@
+use axum::extract::{Extension, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::api::auth::AuthUser;
+use crate::api::response::NewPlanetsEvent;
+use crate::api::state::AppState;
+
+/// GET /api/events/planets - Server-Sent Events for planets stored by galaxy scans
+///
+/// Each event is a `new_planets` message carrying a `NewPlanetsEvent` as JSON.
+pub async fn planet_events(
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    tracing::debug!(user_id = user.id, "SSE: planet events subscribed");
+    Sse::new(planet_event_stream(state.planet_events.subscribe())).keep_alive(KeepAlive::default())
+}
+
+/// Turn broadcast messages into SSE events. Lagging clients skip what they missed
+/// instead of being disconnected; the stream ends when the sender is gone.
+fn planet_event_stream(
+    rx: broadcast::Receiver<NewPlanetsEvent>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let event = Event::default()
+                        .event("new_planets")
+                        .json_data(&event)
+                        .unwrap_or_else(|_| Event::default().comment("serialization failed"));
+                    return Some((Ok(event), rx));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "SSE: planet event subscriber lagged");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn event(system: i64) -> NewPlanetsEvent {
+        NewPlanetsEvent { galaxy: 1, system, planets: Vec::new() }
+    }
+
+    #[tokio::test]
+    async fn test_stream_forwards_events_until_closed() {
+        let (tx, rx) = broadcast::channel(4);
+        let stream = planet_event_stream(rx);
+        tx.send(event(1)).unwrap();
+        tx.send(event(2)).unwrap();
+        drop(tx);
+
+        assert_eq!(stream.count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_skips_lagged_events() {
+        let (tx, rx) = broadcast::channel(2);
+        let stream = planet_event_stream(rx);
+        for system in 1..=5 {
+            tx.send(event(system)).unwrap();
+        }
+        drop(tx);
+
+        // Only the last `capacity` events survive
+        assert_eq!(stream.count().await, 2);
+    }
+}
@@ -0,0 +1,45 @@
+use axum::{
+    extract::{Extension, Query},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use crate::api::auth::AuthUser;
+use crate::api::error::AppError;
+use crate::db::models::UserRole;
+use crate::db::queries::bot;
+
+/// Lets consumers detect changes to the positional export format
+const EXPORT_VERSION_HEADER: header::HeaderName = header::HeaderName::from_static("x-export-version");
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// Only export data changed after this timepoint (Unix ms)
+    pub since: Option<i64>,
+}
+
+/// GET /api/export - Galaxy export, same format as the bot's /export (admin only)
+pub async fn get_export(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    if user.role != UserRole::Admin {
+        return Err(AppError::Forbidden);
+    }
+
+    let json_data = match query.since {
+        Some(since) => bot::build_export_json_since(since).await?,
+        None => bot::build_export_json().await?,
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"galaxy_export.json\"".to_string()),
+            (EXPORT_VERSION_HEADER, bot::EXPORT_VERSION.to_string()),
+        ],
+        json_data,
+    )
+        .into_response())
+}
@@ -1,11 +1,13 @@
 use axum::{
-    extract::{Path, Extension},
+    extract::{Path, Extension, State},
     Json,
 };
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
-use crate::api::response::{self, GalaxySystemResponse, GalaxyPlanetInfo, GalaxySpyReport};
-use crate::db::queries::{galaxy, spy_reports};
+use crate::api::state::AppState;
+use crate::api::response::{self, GalaxySystemResponse, GalaxyPlanetInfo, GalaxyMoonInfo, GalaxySpyReport, SuccessResponse};
+use crate::db::models::{PlanetRow, UserRole};
+use crate::db::queries::{audit, galaxy, spy_reports};
 use crate::get_pool;
 use sqlx::Row;
 
@@ -18,17 +20,7 @@ pub async fn get_system(
 
     // Get planets from DB
     let planets_rows = galaxy::get_system(galaxy_num, system_num).await?;
-    let planets: Vec<GalaxyPlanetInfo> = planets_rows.into_iter().map(|p| {
-        GalaxyPlanetInfo {
-            id: p.id,
-            name: p.name,
-            player_id: p.player_id,
-            coordinates: p.coordinates,
-            planet: p.planet,
-            r#type: p.r#type.unwrap_or_else(|| "PLANET".to_string()),
-            planet_id: p.planet_id,
-        }
-    }).collect();
+    let planets = fold_moons(planets_rows);
 
     // Get spy reports for this system
     let spy_reports_rows = spy_reports::get_by_system(galaxy_num, system_num).await?;
@@ -36,7 +28,7 @@ pub async fn get_system(
         GalaxySpyReport {
             planet: r.planet,
             r#type: r.r#type.unwrap_or_else(|| "PLANET".to_string()),
-            resources: response::parse_json_map(&r.resources),
+            resources: response::parse_json_map(&r.resources, &format!("spy report {}:{}:{}", galaxy_num, system_num, r.planet)),
             report_time: r.report_time,
             created_at: r.created_at,
         }
@@ -58,3 +50,114 @@ pub async fn get_system(
         last_scan_at,
     }))
 }
+
+/// POST /api/galaxy/{galaxy}/{system}/invalidate - Flag a system for re-scan (admin only)
+pub async fn invalidate_system(
+    Path((galaxy_num, system_num)): Path<(i64, i64)>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    if user.role != UserRole::Admin {
+        return Err(AppError::Forbidden);
+    }
+
+    if !galaxy::invalidate(galaxy_num, system_num).await? {
+        return Err(AppError::NotFound("System wurde noch nicht gescannt".into()));
+    }
+
+    let target = format!("system:{}:{}", galaxy_num, system_num);
+    audit::record(&state.pool, user.id, "invalidate_system", Some(&target), None).await;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// One entry per position: moons are attached to the planet at the same position
+/// (`has_moon`/`moon`). A moon without a known planet stays a separate MOON entry.
+fn fold_moons(rows: Vec<PlanetRow>) -> Vec<GalaxyPlanetInfo> {
+    let (moons, planets): (Vec<PlanetRow>, Vec<PlanetRow>) = rows
+        .into_iter()
+        .partition(|p| p.r#type.as_deref() == Some("MOON"));
+
+    let mut result: Vec<GalaxyPlanetInfo> = planets
+        .into_iter()
+        .map(|p| GalaxyPlanetInfo {
+            id: p.id,
+            name: p.name,
+            player_id: p.player_id,
+            coordinates: p.coordinates,
+            planet: p.planet,
+            r#type: "PLANET".to_string(),
+            planet_id: p.planet_id,
+            has_moon: false,
+            moon: None,
+        })
+        .collect();
+
+    for moon in moons {
+        match result.iter_mut().find(|p| p.planet == moon.planet && p.r#type == "PLANET") {
+            Some(planet) => {
+                planet.has_moon = true;
+                planet.moon = Some(GalaxyMoonInfo {
+                    id: moon.id,
+                    name: moon.name,
+                    planet_id: moon.planet_id,
+                });
+            }
+            None => result.push(GalaxyPlanetInfo {
+                id: moon.id,
+                name: moon.name,
+                player_id: moon.player_id,
+                coordinates: moon.coordinates,
+                planet: moon.planet,
+                r#type: "MOON".to_string(),
+                planet_id: moon.planet_id,
+                has_moon: false,
+                moon: None,
+            }),
+        }
+    }
+
+    result.sort_by_key(|p| p.planet);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: i64, planet: i64, kind: &str) -> PlanetRow {
+        PlanetRow {
+            id,
+            name: Some(format!("{kind} {planet}")),
+            player_id: 1,
+            coordinates: format!("1:2:{planet}"),
+            galaxy: 1,
+            system: 2,
+            planet,
+            r#type: Some(kind.to_string()),
+            planet_id: Some(id * 10),
+            buildings: None,
+            fleet: None,
+            defense: None,
+            resources: None,
+            prod_h: None,
+            status: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_fold_moons() {
+        // Rows come ordered by (planet, type), so the moon sorts before its planet
+        let rows = vec![row(2, 4, "MOON"), row(1, 4, "PLANET"), row(3, 7, "PLANET"), row(4, 9, "MOON")];
+        let planets = fold_moons(rows);
+
+        assert_eq!(planets.iter().map(|p| p.id).collect::<Vec<_>>(), vec![1, 3, 4]);
+        assert!(planets[0].has_moon);
+        assert_eq!(planets[0].moon.as_ref().map(|m| m.planet_id), Some(Some(20)));
+        assert!(!planets[1].has_moon);
+        // Orphan moon stays its own entry
+        assert_eq!(planets[2].r#type, "MOON");
+    }
+}
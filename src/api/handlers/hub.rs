@@ -1,10 +1,20 @@
-use axum::{extract::Extension, Json};
+use axum::{
+    extract::{Extension, Query, State},
+    http::HeaderMap,
+    response::Response,
+    Json,
+};
 use chrono::Timelike;
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
+use crate::api::etag;
+use crate::api::state::AppState;
+use crate::api::handlers::players;
 use crate::api::response::*;
 use crate::db::queries::{hub, config};
-use crate::get_pool;
+use crate::db::timestamp::parse_db_timestamp;
+use crate::{get_pool, i18n};
+use serde::Deserialize;
 use std::collections::HashMap;
 use sqlx::Row;
 
@@ -20,12 +30,15 @@ pub async fn get_planets(
     let response = HubPlanetsResponse {
         planets: planets
             .into_iter()
-            .map(|p| HubPlanetInfo {
-                player_id: p.player_id.unwrap_or(0),
-                player_name: p.player_name.unwrap_or_default(),
-                coordinates: p.coordinates.unwrap_or_default(),
-                buildings: p.buildings.as_ref().and_then(|s| serde_json::from_str(s).ok()),
-                points: p.points.unwrap_or(0),
+            .map(|p| {
+                let coordinates = p.coordinates.unwrap_or_default();
+                HubPlanetInfo {
+                    player_id: p.player_id.unwrap_or(0),
+                    player_name: p.player_name.unwrap_or_default(),
+                    buildings: parse_json_map(&p.buildings, &format!("planet {}", coordinates)),
+                    coordinates,
+                    points: p.points.unwrap_or(0),
+                }
             })
             .collect(),
     };
@@ -48,7 +61,7 @@ pub async fn get_research(
             .map(|r| HubResearchInfo {
                 id: r.id.unwrap_or(0),
                 name: r.name.clone().unwrap_or_default(),
-                research: r.research.as_ref().and_then(|s| serde_json::from_str(s).ok()),
+                research: r.research.map(|m| m.into_map(&format!("research of player {}", r.id.unwrap_or(0)))),
             })
             .collect(),
     };
@@ -68,21 +81,20 @@ pub async fn get_max_research(
     let mut result: HashMap<String, MaxResearchInfo> = HashMap::new();
 
     for row in rows {
-        if let Some(research_json) = &row.research {
-            if let Ok(research) = serde_json::from_str::<HashMap<String, i64>>(research_json) {
-                for (tech_id, level) in research {
-                    result.entry(tech_id.clone())
-                        .and_modify(|e| {
-                            if level > e.max_level {
-                                e.max_level = level;
-                                e.player_name = row.name.clone().unwrap_or_default();
-                            }
-                        })
-                        .or_insert(MaxResearchInfo {
-                            max_level: level,
-                            player_name: row.name.clone().unwrap_or_default(),
-                        });
-                }
+        if let Some(research) = row.research {
+            let research = research.into_map(&format!("research of player {}", row.id.unwrap_or(0)));
+            for (tech_id, level) in research {
+                result.entry(tech_id.clone())
+                    .and_modify(|e| {
+                        if level > e.max_level {
+                            e.max_level = level;
+                            e.player_name = row.name.clone().unwrap_or_default();
+                        }
+                    })
+                    .or_insert(MaxResearchInfo {
+                        max_level: level,
+                        player_name: row.name.clone().unwrap_or_default(),
+                    });
             }
         }
     }
@@ -105,8 +117,8 @@ pub async fn get_fleet(
 
     for r in rows {
         let player_id = r.player_id.unwrap_or(0);
-        let fleet: HashMap<String, i64> = r.fleet.as_ref()
-            .and_then(|s| serde_json::from_str(s).ok())
+        let fleet = r.fleet
+            .map(|m| m.into_map(&format!("fleet of player {}", player_id)))
             .unwrap_or_default();
 
         // Add fleet to total
@@ -142,14 +154,7 @@ pub async fn get_galaxy_status(
     let rows = hub::get_galaxy_status().await?;
 
     let systems: Vec<GalaxySystemInfo> = rows.into_iter().map(|r| {
-        // SQLite stores timestamps as "YYYY-MM-DD HH:MM:SS", not RFC3339
-        let age_hours = r.last_scan_at.as_ref().and_then(|ts| {
-            chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S").ok().map(|dt| {
-                let now = chrono::Utc::now().naive_utc();
-                let duration = now.signed_duration_since(dt);
-                duration.num_hours()
-            })
-        });
+        let age_hours = r.last_scan_at.as_deref().and_then(scan_age_hours);
 
         GalaxySystemInfo {
             galaxy: r.galaxy,
@@ -162,32 +167,70 @@ pub async fn get_galaxy_status(
     Ok(Json(HubGalaxyResponse { systems }))
 }
 
+/// Hours elapsed since a scan timestamp
+fn scan_age_hours(ts: &str) -> Option<i64> {
+    parse_db_timestamp(ts).map(|dt| {
+        let now = chrono::Utc::now().naive_utc();
+        now.signed_duration_since(dt).num_hours()
+    })
+}
+
+#[derive(Deserialize)]
+pub struct CoverageQuery {
+    pub galaxy: i64,
+    pub from: i64,
+    pub to: i64,
+}
+
+/// Maximum number of systems per coverage request
+const MAX_COVERAGE_RANGE: i64 = 999;
+
+/// GET /api/hub/coverage?galaxy=G&from=S1&to=S2 - Scan coverage for a system range
+pub async fn get_coverage(
+    State(state): State<AppState>,
+    Query(query): Query<CoverageQuery>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<HubCoverageResponse>, AppError> {
+    if query.galaxy < 1 || query.from < 1 || query.to < query.from {
+        return Err(AppError::BadRequest("Ungültiger Systembereich".into()));
+    }
+    if query.to - query.from >= MAX_COVERAGE_RANGE {
+        return Err(AppError::BadRequest(
+            format!("Maximal {} Systeme pro Anfrage", MAX_COVERAGE_RANGE),
+        ));
+    }
+
+    let rows = hub::get_coverage(&state.pool, query.galaxy, query.from, query.to).await?;
+    let mut scans: HashMap<i64, Option<String>> = rows
+        .into_iter()
+        .map(|r| (r.system, r.last_scan_at))
+        .collect();
+
+    // One entry per system in range, including systems never scanned
+    let systems: Vec<CoverageSystemInfo> = (query.from..=query.to).map(|system| {
+        let last_scan_at = scans.remove(&system).flatten();
+        CoverageSystemInfo {
+            system,
+            scanned: last_scan_at.is_some(),
+            age_hours: last_scan_at.as_deref().and_then(scan_age_hours),
+            last_scan_at,
+        }
+    }).collect();
+
+    Ok(Json(HubCoverageResponse { galaxy: query.galaxy, systems }))
+}
+
 /// GET /api/hub/statview
 pub async fn get_stat_view(
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<HubStatViewResponse>, AppError> {
     let rows = hub::get_stat_view().await?;
 
+    let now = chrono::Utc::now().naive_utc();
     let stat_views: Vec<StatViewInfo> = rows.into_iter().map(|r| {
-        // SQLite stores timestamps as "YYYY-MM-DD HH:MM:SS", not RFC3339
-        // Check if sync is within current 6-hour window (0-6, 6-12, 12-18, 18-24)
-        let is_synced = r.last_sync_at.as_ref().map(|ts| {
-            chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S").ok().map(|dt| {
-                let now = chrono::Utc::now().naive_utc();
-
-                // Calculate current 6-hour window start
-                let current_hour = now.time().hour() as i64;
-                let window_start_hour = (current_hour / 6) * 6;  // 0, 6, 12, or 18
-
-                // Build window start datetime (today at window_start_hour:00:00)
-                let today = now.date();
-                let window_start = today.and_hms_opt(window_start_hour as u32, 0, 0)
-                    .unwrap_or(now);
-
-                // Sync is valid if it happened after window start
-                dt >= window_start
-            }).unwrap_or(false)
-        }).unwrap_or(false);
+        let is_synced = r.last_sync_at.as_deref()
+            .and_then(parse_db_timestamp)
+            .is_some_and(|dt| is_in_current_sync_window(dt, now));
 
         StatViewInfo {
             stat_type: r.stat_type,
@@ -199,6 +242,33 @@ pub async fn get_stat_view(
     Ok(Json(HubStatViewResponse { stat_views }))
 }
 
+/// Length of a stats sync window (0-6, 6-12, 12-18, 18-24 UTC)
+const SYNC_WINDOW_HOURS: u32 = 6;
+
+/// Start of the sync window `now` falls into.
+///
+/// Derived by stepping back from `now` instead of rebuilding it from `now.date()`, so the
+/// result stays right when `now` sits right after midnight.
+fn sync_window_start(now: chrono::NaiveDateTime) -> chrono::NaiveDateTime {
+    let into_window = chrono::Duration::hours((now.hour() % SYNC_WINDOW_HOURS) as i64)
+        + chrono::Duration::minutes(now.minute() as i64)
+        + chrono::Duration::seconds(now.second() as i64)
+        + chrono::Duration::nanoseconds(now.nanosecond() as i64);
+    now - into_window
+}
+
+/// A sync counts if it happened in the window `now` falls into.
+///
+/// In the day's first window the previous day's last window (18-24) still counts, so a
+/// sync from just before midnight isn't shown as missing right after it.
+fn is_in_current_sync_window(last_sync: chrono::NaiveDateTime, now: chrono::NaiveDateTime) -> bool {
+    let mut start = sync_window_start(now);
+    if now.hour() < SYNC_WINDOW_HOURS {
+        start -= chrono::Duration::hours(SYNC_WINDOW_HOURS as i64);
+    }
+    last_sync >= start
+}
+
 /// GET /api/hub/scores
 pub async fn get_scores(
     Extension(AuthUser(user)): Extension<AuthUser>,
@@ -232,21 +302,20 @@ pub async fn get_buildings(
     let mut result: HashMap<String, MaxBuildingInfo> = HashMap::new();
 
     for row in rows {
-        if let Some(buildings_json) = &row.buildings {
-            if let Ok(buildings) = serde_json::from_str::<HashMap<String, i64>>(buildings_json) {
-                for (building_id, level) in buildings {
-                    result.entry(building_id.clone())
-                        .and_modify(|e| {
-                            if level > e.max_level {
-                                e.max_level = level;
-                                e.player_name = row.player_name.clone().unwrap_or_default();
-                            }
-                        })
-                        .or_insert(MaxBuildingInfo {
-                            max_level: level,
-                            player_name: row.player_name.clone().unwrap_or_default(),
-                        });
-                }
+        let context = format!("planet {}", row.coordinates.as_deref().unwrap_or("?"));
+        if let Some(buildings) = parse_json_map(&row.buildings, &context) {
+            for (building_id, level) in buildings {
+                result.entry(building_id.clone())
+                    .and_modify(|e| {
+                        if level > e.max_level {
+                            e.max_level = level;
+                            e.player_name = row.player_name.clone().unwrap_or_default();
+                        }
+                    })
+                    .or_insert(MaxBuildingInfo {
+                        max_level: level,
+                        player_name: row.player_name.clone().unwrap_or_default(),
+                    });
             }
         }
     }
@@ -258,6 +327,24 @@ pub async fn get_buildings(
 pub async fn get_config(
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<HubConfigResponse>, AppError> {
+    Ok(Json(universe_config().await?))
+}
+
+/// GET /api/bootstrap - Universe config, languages and the current user in one call
+pub async fn get_bootstrap(
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<BootstrapResponse>, AppError> {
+    let (config, user) = tokio::try_join!(universe_config(), players::me_response(get_pool().await, user))?;
+
+    Ok(Json(BootstrapResponse {
+        config,
+        languages: i18n::SUPPORTED_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+        user,
+    }))
+}
+
+/// Universe size from the config table, with pr0game defaults for missing keys
+async fn universe_config() -> Result<HubConfigResponse, AppError> {
     let rows = config::get_universe_config().await?;
 
     let mut galaxies = 9i64;
@@ -273,7 +360,7 @@ pub async fn get_config(
         }
     }
 
-    Ok(Json(HubConfigResponse { galaxies, systems, galaxy_wrapped }))
+    Ok(HubConfigResponse { galaxies, systems, galaxy_wrapped })
 }
 
 /// GET /api/hub/stats - Raid, Expo, Recycling statistics
@@ -300,6 +387,7 @@ pub async fn get_stats(
             r#"SELECT p.id, p.name
                FROM players p
                WHERE p.alliance_id = ?
+                 AND p.is_deleted = 0
                ORDER BY p.name"#
         )
         .bind(alliance_id)
@@ -443,9 +531,27 @@ async fn get_recycle_stats(pool: &sqlx::SqlitePool, user_id: i64, last_24h: bool
 }
 
 /// GET /api/hub/overview - Planet overview with player data for filtering
+///
+/// Supports conditional GET: clients sending a matching If-None-Match get 304.
 pub async fn get_overview(
+    headers: HeaderMap,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-) -> Result<Json<HubOverviewResponse>, AppError> {
+) -> Result<Response, AppError> {
+    let version = hub::get_overview_version().await?;
+    let etag = etag::compute(&(
+        version.planet_count,
+        version.planets_updated_at,
+        version.players_updated_at,
+        version.last_score_id,
+        version.last_spy_report_id,
+        version.last_battle_report_id,
+        version.current_hour,
+    ));
+
+    if etag::is_fresh(&headers, &etag) {
+        return Ok(etag::respond(&headers, &etag, || ()));
+    }
+
     let pool = get_pool().await;
 
     // Query planets with score diffs calculated from player_scores
@@ -570,5 +676,164 @@ pub async fn get_overview(
         }
     }).collect();
 
-    Ok(Json(HubOverviewResponse { planets }))
+    Ok(etag::respond(&headers, &etag, || Json(HubOverviewResponse { planets })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use crate::api::test_support::{create_admin, get_json, test_app, ADMIN_API_KEY};
+    use crate::db::connection::connect_memory;
+
+    fn at(ts: &str) -> chrono::NaiveDateTime {
+        parse_db_timestamp(ts).unwrap()
+    }
+
+    #[test]
+    fn test_sync_window_just_after_midnight() {
+        let now = at("2026-03-10 00:05:00");
+        assert_eq!(sync_window_start(now), at("2026-03-10 00:00:00"));
+        assert!(is_in_current_sync_window(at("2026-03-10 00:01:00"), now));
+        // Previous day's 18-24 window still counts early in the day
+        assert!(is_in_current_sync_window(at("2026-03-09 23:55:00"), now));
+        assert!(is_in_current_sync_window(at("2026-03-09 18:00:00"), now));
+        assert!(!is_in_current_sync_window(at("2026-03-09 17:59:59"), now));
+    }
+
+    #[test]
+    fn test_sync_window_grace_ends_with_first_window() {
+        let now = at("2026-03-10 06:05:00");
+        assert!(is_in_current_sync_window(at("2026-03-10 06:00:00"), now));
+        assert!(!is_in_current_sync_window(at("2026-03-10 05:59:59"), now));
+        assert!(!is_in_current_sync_window(at("2026-03-09 23:55:00"), now));
+    }
+
+    #[test]
+    fn test_sync_window_just_before_midnight() {
+        let now = at("2026-03-10 23:55:00");
+        assert_eq!(sync_window_start(now), at("2026-03-10 18:00:00"));
+        assert!(is_in_current_sync_window(at("2026-03-10 23:50:00"), now));
+        assert!(is_in_current_sync_window(at("2026-03-10 18:00:00"), now));
+        assert!(!is_in_current_sync_window(at("2026-03-10 17:59:59"), now));
+        assert!(!is_in_current_sync_window(at("2026-03-09 23:55:00"), now));
+    }
+
+    #[tokio::test]
+    async fn test_raid_stats_match_full_scan() {
+        let pool = connect_memory().await;
+
+        sqlx::query("INSERT INTO players (id, name) VALUES (1, 'Raider'), (2, 'Other')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let reports = [
+            (1, 1, 1000, 500, 100, "datetime('now', '-2 hours')"),
+            (2, 1, 2000, 0, 300, "datetime('now', '-3 days')"),
+            (3, 2, 9999, 9999, 9999, "datetime('now')"),
+            (4, 1, 0, 700, 0, "datetime('now', '-30 hours')"),
+        ];
+        for (external_id, reporter, metal, crystal, deuterium, created_at) in reports {
+            sqlx::query(&format!(
+                "INSERT INTO battle_reports
+                    (external_id, coordinates, galaxy, system, planet, metal, crystal, deuterium, reported_by, created_at)
+                 VALUES (?, '1:1:1', 1, 1, 1, ?, ?, ?, ?, {})",
+                created_at
+            ))
+            .bind(external_id)
+            .bind(metal)
+            .bind(crystal)
+            .bind(deuterium)
+            .bind(reporter)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        for last_24h in [false, true] {
+            let stats = get_raid_stats(&pool, 1, last_24h).await.unwrap();
+
+            let time_filter = if last_24h {
+                "AND created_at > datetime('now', '-24 hours')"
+            } else {
+                ""
+            };
+            let scan = sqlx::query(&format!(
+                "SELECT COUNT(*) AS count,
+                        COALESCE(SUM(metal), 0) AS metal,
+                        COALESCE(SUM(crystal), 0) AS crystal,
+                        COALESCE(SUM(deuterium), 0) AS deuterium
+                 FROM battle_reports NOT INDEXED
+                 WHERE reported_by = 1 {}",
+                time_filter
+            ))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+            assert_eq!(stats.count, scan.get::<i64, _>("count"));
+            assert_eq!(stats.metal, scan.get::<i64, _>("metal"));
+            assert_eq!(stats.crystal, scan.get::<i64, _>("crystal"));
+            assert_eq!(stats.deuterium, scan.get::<i64, _>("deuterium"));
+        }
+
+        let all_time = get_raid_stats(&pool, 1, false).await.unwrap();
+        assert_eq!(all_time.count, 3);
+        assert_eq!(all_time.count_24h, 1);
+        assert_eq!(all_time.metal, 3000);
+    }
+
+    #[tokio::test]
+    async fn test_activity_stats_use_reporter_index() {
+        let pool = connect_memory().await;
+
+        for (table, index) in [
+            ("expedition_reports", "idx_expedition_reports_reported_by"),
+            ("battle_reports", "idx_battle_reports_reported_by"),
+            ("recycle_reports", "idx_recycle_reports_reported_by"),
+        ] {
+            let plan: Vec<String> = sqlx::query(&format!(
+                "EXPLAIN QUERY PLAN SELECT COUNT(*) FROM {} \
+                 WHERE reported_by = ? AND created_at > datetime('now', '-24 hours')",
+                table
+            ))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<String, _>("detail"))
+            .collect();
+
+            assert!(plan.iter().any(|d| d.contains(index)), "{table}: {plan:?}");
+        }
+    }
+    #[tokio::test]
+    async fn test_coverage_lists_every_system_in_range() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        sqlx::query(
+            "INSERT INTO players (id, name) VALUES (0, '');
+             INSERT INTO planets (name, player_id, coordinates, galaxy, system, planet, type)
+             VALUES ('SCANNED', 0, '1:3:0', 1, 3, 0, 'PLANET')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, body) = get_json(&app, "/api/hub/coverage?galaxy=1&from=2&to=4", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["galaxy"], 1);
+        let systems = body["systems"].as_array().unwrap();
+        let scanned: Vec<(i64, bool)> = systems
+            .iter()
+            .map(|s| (s["system"].as_i64().unwrap(), s["scanned"].as_bool().unwrap()))
+            .collect();
+        assert_eq!(scanned, [(2, false), (3, true), (4, false)]);
+        assert_eq!(systems[1]["age_hours"], 0);
+        assert!(systems[0]["last_scan_at"].is_null());
+
+        let (status, _) = get_json(&app, "/api/hub/coverage?galaxy=1&from=4&to=2", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
 }
@@ -1,6 +1,7 @@
 use axum::{extract::Extension, Json};
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
+use crate::api::extract::ApiJson;
 use crate::api::response::*;
 use crate::db::queries::messages;
 use serde::Deserialize;
@@ -13,7 +14,7 @@ pub struct CheckMessagesRequest {
 /// POST /api/messages
 pub async fn check_messages(
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<CheckMessagesRequest>,
+    ApiJson(req): ApiJson<CheckMessagesRequest>,
 ) -> Result<Json<MessageCheckResponse>, AppError> {
     let existing = messages::get_existing_ids(&req.message_ids).await?;
 
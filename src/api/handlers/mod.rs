@@ -1,8 +1,11 @@
 pub mod admin;
+pub mod alliances;
 pub mod hub;
 pub mod players;
 pub mod planets;
 pub mod reports;
 pub mod galaxy;
 pub mod empire;
+pub mod events;
+pub mod export;
 pub mod statistics;
\ No newline at end of file
@@ -1,10 +1,13 @@
-use axum::{extract::Extension, Json};
+use axum::{extract::{Extension, State}, Json};
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
-use crate::api::response::{SuccessResponse, PlanetsNewResponse};
+use crate::api::extract::ApiJson;
+use crate::api::game_ids::{BuildingMap, DefenseMap, FleetMap, ResourceMap};
+use crate::api::response::{parse_coordinates, NewPlanetInfo, NewPlanetsEvent, SuccessResponse, PlanetsNewResponse};
+use crate::api::state::AppState;
+use crate::db::models::PlanetType;
 use crate::db::queries::{alliances, planets, players};
 use serde::Deserialize;
-use std::collections::HashMap;
 
 /// POST /api/planets
 #[derive(Deserialize)]
@@ -17,23 +20,17 @@ pub struct CreatePlanetRequest {
 
 pub async fn create_planet(
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<CreatePlanetRequest>,
+    ApiJson(req): ApiJson<CreatePlanetRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    let parts: Vec<&str> = req.coordinates.split(':').collect();
-    if parts.len() != 3 {
-        return Err(AppError::BadRequest("Ungültige Koordinaten".into()));
-    }
-
-    let galaxy: i64 = parts[0].parse().map_err(|_| AppError::BadRequest("Ungültige Galaxy".into()))?;
-    let system: i64 = parts[1].parse().map_err(|_| AppError::BadRequest("Ungültiges System".into()))?;
-    let planet: i64 = parts[2].parse().map_err(|_| AppError::BadRequest("Ungültiger Planet".into()))?;
+    let (galaxy, system, planet) = parse_coordinates(&req.coordinates)?;
+    let coordinates = format!("{}:{}:{}", galaxy, system, planet);
 
     // Upsert planet
-    planets::upsert(req.player_id, &req.coordinates, galaxy, system, planet, "PLANET", req.planet_name.as_deref(), None).await?;
+    planets::upsert(req.player_id, &coordinates, galaxy, system, planet, PlanetType::Planet.as_str(), req.planet_name.as_deref(), None).await?;
 
     // Upsert moon if provided
     if req.moon_name.is_some() {
-        planets::upsert(req.player_id, &req.coordinates, galaxy, system, planet, "MOON", req.moon_name.as_deref(), None).await?;
+        planets::upsert(req.player_id, &coordinates, galaxy, system, planet, PlanetType::Moon.as_str(), req.moon_name.as_deref(), None).await?;
     }
 
     Ok(Json(SuccessResponse { success: true }))
@@ -61,34 +58,37 @@ pub struct PlanetInput {
     pub moon_id: Option<i64>,  // pr0game internal moon ID
     pub alliance_id: Option<i64>,  // Alliance ID from galaxy page
     pub alliance_tag: Option<String>,  // Alliance tag from galaxy page
+    pub alliance_name: Option<String>,  // Full alliance name from the galaxy tooltip
 }
 
 #[derive(Deserialize)]
 pub struct DestroyedInput {
     pub position: i64,
-    pub r#type: String,  // "PLANET" or "MOON"
+    pub r#type: PlanetType,
 }
 
 pub async fn create_planets_batch(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<PlanetsNewRequest>,
+    ApiJson(req): ApiJson<PlanetsNewRequest>,
 ) -> Result<Json<PlanetsNewResponse>, AppError> {
     let mut created = 0i64;
     let mut skipped = 0i64;
     let mut deleted = 0i64;
+    let mut new_planets = Vec::new();
 
     // Ensure system marker player exists (player_id=0 for system markers)
-    players::ensure_exists(0, "System").await?;
+    players::ensure_exists(&state.pool, 0, "System").await?;
 
     // Always update system marker (position=0) to track when system was last scanned
     let marker_coords = format!("{}:{}:0", req.galaxy, req.system);
     let marker_name = if req.planets.is_empty() && req.destroyed.is_empty() { "EMPTY" } else { "SCANNED" };
-    planets::upsert(0, &marker_coords, req.galaxy, req.system, 0, "PLANET", Some(marker_name), None).await?;
+    planets::upsert(0, &marker_coords, req.galaxy, req.system, 0, PlanetType::Planet.as_str(), Some(marker_name), None).await?;
 
     // Mark destroyed planets/moons as deleted (keep in DB for history)
     for d in req.destroyed {
         let coordinates = format!("{}:{}:{}", req.galaxy, req.system, d.position);
-        planets::mark_deleted(&coordinates, &d.r#type).await?;
+        planets::mark_deleted(&coordinates, d.r#type.as_str()).await?;
         deleted += 1;
     }
 
@@ -104,24 +104,46 @@ pub async fn create_planets_batch(
 
         // Ensure player exists before inserting planet (FK constraint)
         let player_name = p.player_name.as_deref().unwrap_or("Unknown");
-        players::ensure_exists(player_id, player_name).await?;
+        players::ensure_exists(&state.pool, player_id, player_name).await?;
 
         // Ensure alliance exists and update player's alliance if provided
         if let (Some(alliance_id), Some(alliance_tag)) = (p.alliance_id, &p.alliance_tag) {
-            alliances::ensure_exists(alliance_id, alliance_tag).await?;
-            players::update_alliance(player_id, alliance_id).await?;
+            alliances::ensure_exists(&state.pool, alliance_id, alliance_tag).await?;
+            if let Some(alliance_name) = &p.alliance_name {
+                alliances::update_name(&state.pool, alliance_id, alliance_name).await?;
+            }
+            players::update_alliance(&state.pool, player_id, alliance_id).await?;
         }
 
         let coordinates = format!("{}:{}:{}", req.galaxy, req.system, p.position);
-        planets::upsert(player_id, &coordinates, req.galaxy, req.system, p.position, "PLANET", p.planet_name.as_deref(), p.planet_id).await?;
-        created += 1;
-
+        let mut stored = vec![(PlanetType::Planet, p.planet_name, p.planet_id)];
         if p.has_moon.unwrap_or(false) {
-            planets::upsert(player_id, &coordinates, req.galaxy, req.system, p.position, "MOON", p.moon_name.as_deref(), p.moon_id).await?;
+            stored.push((PlanetType::Moon, p.moon_name, p.moon_id));
+        }
+        for (planet_type, name, pr0_id) in stored {
+            let is_new = planets::upsert(player_id, &coordinates, req.galaxy, req.system, p.position, planet_type.as_str(), name.as_deref(), pr0_id).await?;
             created += 1;
+            if is_new {
+                new_planets.push(NewPlanetInfo {
+                    coordinates: coordinates.clone(),
+                    position: p.position,
+                    planet_type: planet_type.as_str().to_string(),
+                    player_id,
+                    name,
+                });
+            }
         }
     }
 
+    // Nobody listening is not an error
+    if !new_planets.is_empty() {
+        let _ = state.planet_events.send(NewPlanetsEvent {
+            galaxy: req.galaxy,
+            system: req.system,
+            planets: new_planets,
+        });
+    }
+
     tracing::debug!("Planets batch: created={}, skipped={}, deleted={}, marker={}", created, skipped, deleted, marker_name);
     Ok(Json(PlanetsNewResponse { success: true, created, deleted }))
 }
@@ -130,18 +152,20 @@ pub async fn create_planets_batch(
 #[derive(Deserialize)]
 pub struct BuildingsRequest {
     pub coordinates: String,
-    pub r#type: String,
-    pub buildings: HashMap<String, i64>,
+    pub r#type: PlanetType,
+    pub buildings: BuildingMap,
 }
 
 pub async fn update_buildings(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<BuildingsRequest>,
+    ApiJson(req): ApiJson<BuildingsRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    req.buildings.check(state.config.strict_game_ids)?;
     let buildings_json = serde_json::to_string(&req.buildings)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    planets::update_buildings(&req.coordinates, &req.r#type, &buildings_json).await?;
+    planets::update_buildings(&req.coordinates, req.r#type.as_str(), &buildings_json).await?;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -150,18 +174,20 @@ pub async fn update_buildings(
 #[derive(Deserialize)]
 pub struct FleetRequest {
     pub coordinates: String,
-    pub r#type: String,
-    pub fleet: HashMap<String, i64>,
+    pub r#type: PlanetType,
+    pub fleet: FleetMap,
 }
 
 pub async fn update_fleet(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<FleetRequest>,
+    ApiJson(req): ApiJson<FleetRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    req.fleet.check(state.config.strict_game_ids)?;
     let fleet_json = serde_json::to_string(&req.fleet)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    planets::update_fleet(&req.coordinates, &req.r#type, &fleet_json).await?;
+    planets::update_fleet(&req.coordinates, req.r#type.as_str(), &fleet_json).await?;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -170,18 +196,20 @@ pub async fn update_fleet(
 #[derive(Deserialize)]
 pub struct DefenseRequest {
     pub coordinates: String,
-    pub r#type: String,
-    pub defense: HashMap<String, i64>,
+    pub r#type: PlanetType,
+    pub defense: DefenseMap,
 }
 
 pub async fn update_defense(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<DefenseRequest>,
+    ApiJson(req): ApiJson<DefenseRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    req.defense.check(state.config.strict_game_ids)?;
     let defense_json = serde_json::to_string(&req.defense)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    planets::update_defense(&req.coordinates, &req.r#type, &defense_json).await?;
+    planets::update_defense(&req.coordinates, req.r#type.as_str(), &defense_json).await?;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -190,18 +218,38 @@ pub async fn update_defense(
 #[derive(Deserialize)]
 pub struct ResourcesRequest {
     pub coordinates: String,
-    pub r#type: String,
-    pub resources: HashMap<String, i64>,
+    pub r#type: PlanetType,
+    pub resources: ResourceMap,
 }
 
 pub async fn update_resources(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<ResourcesRequest>,
+    ApiJson(req): ApiJson<ResourcesRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    req.resources.check(state.config.strict_game_ids)?;
     let resources_json = serde_json::to_string(&req.resources)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    planets::update_resources(&req.coordinates, &req.r#type, &resources_json).await?;
+    planets::update_resources(&req.coordinates, req.r#type.as_str(), &resources_json).await?;
 
     Ok(Json(SuccessResponse { success: true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planet_type_validated() {
+        let ok: BuildingsRequest =
+            serde_json::from_str(r#"{"coordinates": "1:2:3", "type": "MOON", "buildings": {}}"#).unwrap();
+        assert_eq!(ok.r#type, PlanetType::Moon);
+
+        // Lowercase or unknown types must not reach the database
+        for bad in ["planet", "STATION"] {
+            let json = format!(r#"{{"coordinates": "1:2:3", "type": "{bad}", "buildings": {{}}}}"#);
+            assert!(serde_json::from_str::<BuildingsRequest>(&json).is_err(), "{bad}");
+        }
+    }
+}
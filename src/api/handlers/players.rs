@@ -1,29 +1,57 @@
 use axum::{
-    extract::{Path, Extension},
+    extract::{Path, Query, Extension, State},
+    http::header,
+    response::{IntoResponse, Response},
     Json,
 };
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
+use crate::api::extract::ApiJson;
+use crate::api::state::AppState;
 use crate::api::response::{
-    self, PlayerResponse, AllianceInfo, CombatStats, PlayerStatus,
-    PlanetResponse, ChartResponse, SuccessResponse, LoginResponse, LoginUserInfo,
+    self, parse_coordinates, PlayerResponse, AllianceInfo, CombatStats, PlayerStatus,
+    PlanetResponse, ChartResponse, SuccessResponse, LoginResponse, LoginUserInfo, MeResponse,
     PlayerDataResponse, PlayersStatsResponse, ResearchResponse,
-    OverviewResponse, OverviewPlanetInfo, OverviewSpyReport,
+    OverviewResponse, OverviewPlanetInfo, OverviewSpyReport, AllianceHistoryResponse, AllianceChange,
+    PlayerResourcesResponse, PlanetResources, PlayerEnergyResponse, PlanetEnergy,
+    PlanetInsightsResponse, PlanetInsight, PlayerExportResponse, SpyReportsResponse,
+    BattleReportHistoryResponse,
 };
-use crate::db::queries::{alliances, players, spy_reports, users};
+use crate::api::handlers::reports::{battle_report_item, spy_report_info};
+use crate::db::models::{PlayerWithAlliance, UserRole, UserRow};
+use crate::db::queries::{alliances, battle_reports, players, spy_reports, users};
+use crate::get_pool;
 use serde::Deserialize;
+use sqlx::SqlitePool;
 use std::collections::HashMap;
 
+/// Whether `user` may see the detailed data (research, planets) of a player.
+/// With ALLIANCE_PRIVATE only the player themself, alliance members and admins may;
+/// everyone else still gets the public name/alliance/score info.
+fn can_view_details(user: &UserRow, player_id: i64, alliance_id: Option<i64>, alliance_private: bool) -> bool {
+    !alliance_private
+        || user.role == UserRole::Admin
+        || user.player_id == Some(player_id)
+        || (user.alliance_id.is_some() && user.alliance_id == alliance_id)
+}
+
 /// GET /api/players/{id}
 pub async fn get_player(
     Path(player_id): Path<i64>,
-    Extension(AuthUser(_user)): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
 ) -> Result<Json<PlayerResponse>, AppError> {
-    let player = players::get_by_id(player_id)
+    let player = players::get_by_id(&state.pool, player_id)
         .await?
         .ok_or_else(|| AppError::NotFound("Spieler nicht gefunden".into()))?;
+    let details = can_view_details(&user, player.id, player.alliance_id, state.config.alliance_private);
+    Ok(Json(player_response(player, details)))
+}
 
-    let response = PlayerResponse {
+/// Public player info, with research only if `details` may be seen
+fn player_response(player: PlayerWithAlliance, details: bool) -> PlayerResponse {
+    let context = format!("player {}", player.id);
+    PlayerResponse {
         id: player.id,
         name: player.name,
         alliance: player.alliance_name.as_ref().map(|name| AllianceInfo {
@@ -32,8 +60,8 @@ pub async fn get_player(
             tag: player.alliance_tag.clone().unwrap_or_default(),
         }),
         main_coordinates: player.main_coordinates,
-        research: response::parse_json_map(&player.research),
-        scores: response::parse_scores(&player.scores),
+        research: response::parse_json_map(&player.research, &context).filter(|_| details),
+        scores: response::parse_scores(&player.scores, &context),
         combat_stats: CombatStats {
             total: player.combats_total.unwrap_or(0),
             won: player.combats_won.unwrap_or(0),
@@ -47,40 +75,376 @@ pub async fn get_player(
             inactive_since: player.inactive_since,
             vacation_since: player.vacation_since,
         },
+    }
+}
+
+/// Reports per planet/moon included in a player export
+const EXPORT_REPORTS_PER_PLANET: i64 = 10;
+
+/// GET /api/players/{id}/export.json - Player, planets, research and recent reports as download
+pub async fn export_player(
+    Path(player_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Response, AppError> {
+    let player = players::get_by_id(&state.pool, player_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Spieler nicht gefunden".into()))?;
+    // Planets and reports are details, so the whole export needs them visible
+    if !can_view_details(&user, player.id, player.alliance_id, state.config.alliance_private) {
+        return Err(AppError::Forbidden);
+    }
+
+    let planets = players::get_planets(&state.pool, player_id).await?;
+
+    let mut spy = Vec::new();
+    let mut battles = Vec::new();
+    for planet in &planets {
+        let planet_type = planet.r#type.as_deref().unwrap_or("PLANET");
+        let reports = spy_reports::get_by_coordinates(
+            &state.pool, planet.galaxy, planet.system, planet.planet, planet_type, EXPORT_REPORTS_PER_PLANET,
+        ).await?;
+        if !reports.is_empty() {
+            spy.push(SpyReportsResponse {
+                coordinates: planet.coordinates.clone(),
+                r#type: planet_type.to_string(),
+                reports: reports.into_iter().map(spy_report_info).collect(),
+            });
+        }
+
+        // Battle reports have no planet type, moons share them with their planet
+        if planet_type == "MOON" {
+            continue;
+        }
+        let reports = battle_reports::get_history_with_reporter(
+            &state.pool, planet.galaxy, planet.system, planet.planet, EXPORT_REPORTS_PER_PLANET,
+        ).await?;
+        if !reports.is_empty() {
+            battles.push(BattleReportHistoryResponse {
+                coordinates: planet.coordinates.clone(),
+                reports: reports.into_iter().map(battle_report_item).collect(),
+            });
+        }
+    }
+
+    let export = PlayerExportResponse {
+        exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        player: player_response(player, true),
+        planets: planets.into_iter().map(response::planet_to_response).collect(),
+        spy_reports: spy,
+        battle_reports: battles,
     };
+    let body = serde_json::to_string(&export).map_err(|e| AppError::Internal(e.to_string()))?;
 
-    Ok(Json(response))
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"player_{}.json\"", player_id)),
+        ],
+        body,
+    )
+        .into_response())
 }
 
 /// GET /api/players/{id}/planets
 pub async fn get_player_planets(
     Path(player_id): Path<i64>,
-    Extension(AuthUser(_user)): Extension<AuthUser>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
 ) -> Result<Json<Vec<PlanetResponse>>, AppError> {
-    let planets = players::get_planets(player_id).await?;
+    ensure_details_visible(&state, &user, player_id).await?;
+
+    let planets = players::get_planets(&state.pool, player_id).await?;
     let response: Vec<PlanetResponse> = planets.into_iter().map(response::planet_to_response).collect();
     Ok(Json(response))
 }
 
-/// GET /api/players/{id}/chart
+/// GET /api/players/{id}/resources - Resources on all planets from the last empire sync
+pub async fn get_player_resources(
+    Path(player_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<PlayerResourcesResponse>, AppError> {
+    ensure_details_visible(&state, &user, player_id).await?;
+
+    let planets: Vec<PlanetResources> = players::get_planets(&state.pool, player_id)
+        .await?
+        .into_iter()
+        .map(|p| PlanetResources {
+            resources: p.resources
+                .map(|m| m.into_map(&format!("resources of planet {} of player {}", p.coordinates, player_id)))
+                .unwrap_or_default(),
+            coordinates: p.coordinates,
+            r#type: p.r#type.unwrap_or_else(|| "PLANET".to_string()),
+            updated_at: p.updated_at,
+        })
+        .collect();
+
+    Ok(Json(PlayerResourcesResponse {
+        player_id,
+        total: sum_resources(&planets),
+        planets,
+    }))
+}
+
+/// GET /api/players/{id}/energy - Energy balance of all planets from the last empire sync
+pub async fn get_player_energy(
+    Path(player_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<PlayerEnergyResponse>, AppError> {
+    ensure_details_visible(&state, &user, player_id).await?;
+
+    let planets: Vec<PlanetEnergy> = players::get_planet_energy(&state.pool, player_id)
+        .await?
+        .into_iter()
+        .map(|p| {
+            let balance = p.energy_max.zip(p.energy_used).map(|(max, used)| max - used);
+            PlanetEnergy {
+                coordinates: p.coordinates,
+                r#type: p.r#type.unwrap_or_else(|| "PLANET".to_string()),
+                name: p.name,
+                energy_used: p.energy_used,
+                energy_max: p.energy_max,
+                balance,
+                deficit: balance.is_some_and(|b| b < 0),
+                updated_at: p.updated_at,
+            }
+        })
+        .collect();
+
+    Ok(Json(PlayerEnergyResponse {
+        player_id,
+        deficit_count: planets.iter().filter(|p| p.deficit).count(),
+        planets,
+    }))
+}
+
+/// GET /api/players/{id}/planets/insights - Production hints per planet based on its temperature
+pub async fn get_planet_insights(
+    Path(player_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<PlanetInsightsResponse>, AppError> {
+    ensure_details_visible(&state, &user, player_id).await?;
+
+    let cold = state.config.planet_cold_temperature;
+    let hot = state.config.planet_hot_temperature;
+    let planets: Vec<PlanetInsight> = players::get_planet_temperatures(&state.pool, player_id)
+        .await?
+        .into_iter()
+        .map(|p| {
+            let (climate, hints) = match p.temperature {
+                Some(temperature) => {
+                    let (climate, hints) = classify_temperature(temperature, cold, hot);
+                    (Some(climate), hints.to_vec())
+                }
+                None => (None, Vec::new()),
+            };
+            PlanetInsight {
+                coordinates: p.coordinates,
+                name: p.name,
+                temperature: p.temperature,
+                climate,
+                hints,
+                updated_at: p.updated_at,
+            }
+        })
+        .collect();
+
+    Ok(Json(PlanetInsightsResponse {
+        player_id,
+        cold_temperature: cold,
+        hot_temperature: hot,
+        planets,
+    }))
+}
+
+/// Climate and hint codes of a planet: deuterium synthesizers produce more the colder
+/// a planet is, solar satellites more the hotter it is
+fn classify_temperature(temperature: i64, cold: i64, hot: i64) -> (&'static str, &'static [&'static str]) {
+    if temperature <= cold {
+        ("cold", &["good_for_deuterium", "weak_solar_satellites"])
+    } else if temperature >= hot {
+        ("hot", &["good_for_solar_satellites", "weak_deuterium"])
+    } else {
+        ("temperate", &[])
+    }
+}
+
+/// Add up the resources of all planets per resource ID
+fn sum_resources(planets: &[PlanetResources]) -> HashMap<String, i64> {
+    let mut total = HashMap::new();
+    for planet in planets {
+        for (id, amount) in &planet.resources {
+            *total.entry(id.clone()).or_insert(0) += amount;
+        }
+    }
+    total
+}
+
+/// With ALLIANCE_PRIVATE, reject detail requests for players outside the caller's alliance
+async fn ensure_details_visible(state: &AppState, user: &UserRow, player_id: i64) -> Result<(), AppError> {
+    if !state.config.alliance_private {
+        return Ok(());
+    }
+
+    let player = players::get_by_id(&state.pool, player_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Spieler nicht gefunden".into()))?;
+    if can_view_details(user, player.id, player.alliance_id, true) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden)
+    }
+}
+
+/// Chart query params shared by the chart endpoints
+#[derive(Deserialize)]
+pub struct ChartQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub resolution: Option<ChartResolution>,
+}
+
+/// Downsampling bucket for chart data (raw when omitted)
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartResolution {
+    Hour,
+    Day,
+    Week,
+}
+
+impl ChartResolution {
+    /// SQLite strftime format that identifies a bucket
+    fn bucket_format(self) -> &'static str {
+        match self {
+            ChartResolution::Hour => "%Y-%m-%d %H",
+            ChartResolution::Day => "%Y-%m-%d",
+            ChartResolution::Week => "%Y-%W",
+        }
+    }
+}
+
+/// Parse a chart range bound (`YYYY-MM-DD` or `YYYY-MM-DDTHH:MM:SS`) into the
+/// `recorded_at` format. A date-only `to` covers the whole day.
+fn parse_chart_bound(value: &str, end_of_day: bool) -> Option<String> {
+    let value = value.trim();
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day { "23:59:59" } else { "00:00:00" };
+        return Some(format!("{} {}", date.format("%Y-%m-%d"), time));
+    }
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(value, fmt).ok())
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Load a player's chart honoring range and resolution params
+async fn load_chart(pool: &SqlitePool, player_id: i64, query: &ChartQuery) -> Result<ChartResponse, AppError> {
+    let from = query.from.as_deref()
+        .map(|v| parse_chart_bound(v, false)
+            .ok_or_else(|| AppError::BadRequest("Ungültiges Datum für 'from'".into())))
+        .transpose()?;
+    let to = query.to.as_deref()
+        .map(|v| parse_chart_bound(v, true)
+            .ok_or_else(|| AppError::BadRequest("Ungültiges Datum für 'to'".into())))
+        .transpose()?;
+
+    if let (Some(from), Some(to)) = (&from, &to)
+        && from > to
+    {
+        return Err(AppError::BadRequest("'from' muss vor 'to' liegen".into()));
+    }
+
+    let scores = match query.resolution {
+        Some(resolution) => {
+            players::get_chart_bucketed(
+                pool, player_id, resolution.bucket_format(), from.as_deref(), to.as_deref(),
+            ).await?
+        }
+        None if from.is_none() && to.is_none() => players::get_chart(pool, player_id).await?,
+        None => players::get_chart_range(pool, player_id, from.as_deref(), to.as_deref()).await?,
+    };
+
+    Ok(ChartResponse {
+        scores: scores.into_iter().map(response::score_to_chart_point).collect(),
+    })
+}
+
+/// GET /api/players/{id}/chart?from=&to=&resolution=
 pub async fn get_player_chart(
     Path(player_id): Path<i64>,
+    Query(query): Query<ChartQuery>,
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<ChartResponse>, AppError> {
-    let scores = players::get_chart(player_id).await?;
-    let response = ChartResponse {
-        scores: scores.into_iter().map(response::score_to_chart_point).collect(),
-    };
-    Ok(Json(response))
+    Ok(Json(load_chart(&state.pool, player_id, &query).await?))
+}
+
+/// POST /api/players/charts
+#[derive(Deserialize)]
+pub struct ChartsRequest {
+    pub ids: Vec<i64>,
+}
+
+const MAX_CHART_IDS: usize = 20;
+
+/// POST /api/players/charts - Score history of several players at once, keyed by player ID
+pub async fn get_player_charts(
+    State(state): State<AppState>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+    ApiJson(req): ApiJson<ChartsRequest>,
+) -> Result<Json<HashMap<i64, ChartResponse>>, AppError> {
+    let mut ids = req.ids;
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() > MAX_CHART_IDS {
+        return Err(AppError::BadRequest(format!("Maximal {} Spieler pro Anfrage", MAX_CHART_IDS)));
+    }
+
+    // Every requested player gets an entry, even without any scores yet
+    let mut charts: HashMap<i64, ChartResponse> = ids
+        .iter()
+        .map(|&id| (id, ChartResponse { scores: Vec::new() }))
+        .collect();
+    for row in players::get_charts(&state.pool, &ids).await? {
+        if let Some(chart) = charts.get_mut(&row.player_id) {
+            chart.scores.push(response::score_to_chart_point(row));
+        }
+    }
+
+    Ok(Json(charts))
+}
+
+/// GET /api/players/{id}/alliance-history
+pub async fn get_alliance_history(
+    Path(player_id): Path<i64>,
+    State(state): State<AppState>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<AllianceHistoryResponse>, AppError> {
+    let rows = players::get_alliance_history(&state.pool, player_id).await?;
+    let changes = rows.into_iter().map(|r| AllianceChange {
+        old_alliance_id: r.old_alliance_id,
+        old_alliance_tag: r.old_alliance_tag,
+        new_alliance_id: r.new_alliance_id,
+        new_alliance_tag: r.new_alliance_tag,
+        changed_at: r.changed_at.unwrap_or_default(),
+    }).collect();
+
+    Ok(Json(AllianceHistoryResponse { player_id, changes }))
 }
 
 /// POST /api/players
 #[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct UpsertPlayerRequest {
     pub id: i64,
     pub name: String,
     pub alliance_id: Option<i64>,
     pub alliance_tag: Option<String>,
+    pub alliance_name: Option<String>,
     pub main_coordinates: Option<String>,
     pub notice: Option<String>,
     // Scores
@@ -119,15 +483,19 @@ pub struct UpsertPlayerRequest {
 }
 
 pub async fn upsert_player(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<UpsertPlayerRequest>,
+    ApiJson(req): ApiJson<UpsertPlayerRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // Ensure alliance exists if both alliance_id and alliance_tag are provided
     if let (Some(alliance_id), Some(alliance_tag)) = (req.alliance_id, &req.alliance_tag) {
-        alliances::ensure_exists(alliance_id, alliance_tag).await?;
+        alliances::ensure_exists(&state.pool, alliance_id, alliance_tag).await?;
+        if let Some(alliance_name) = &req.alliance_name {
+            alliances::update_name(&state.pool, alliance_id, alliance_name).await?;
+        }
     }
 
-    players::upsert_full(&req).await?;
+    players::upsert_full(&state.pool, &req).await?;
 
     Ok(Json(SuccessResponse { success: true }))
 }
@@ -147,12 +515,48 @@ pub async fn login(
     }))
 }
 
+/// GET /api/me - Authenticated user with player/alliance names and role
+pub async fn get_me(
+    State(state): State<AppState>,
+    Extension(AuthUser(user)): Extension<AuthUser>,
+) -> Result<Json<MeResponse>, AppError> {
+    Ok(Json(me_response(&state.pool, user).await?))
+}
+
+/// Resolve player and alliance names for the authenticated user
+pub async fn me_response(pool: &SqlitePool, user: UserRow) -> Result<MeResponse, AppError> {
+    let player_name = match user.player_id {
+        Some(pid) => players::get_by_id(pool, pid).await?.map(|p| p.name),
+        None => None,
+    };
+
+    let alliance = match user.alliance_id {
+        Some(aid) => alliances::get_by_id(pool, aid).await?.map(|a| AllianceInfo {
+            id: a.id,
+            name: a.name,
+            tag: a.tag,
+        }),
+        None => None,
+    };
+
+    Ok(MeResponse {
+        id: user.id,
+        player_id: user.player_id,
+        player_name,
+        alliance,
+        role: user.role.as_str().to_string(),
+        is_admin: user.role == UserRole::Admin,
+        language: user.language,
+    })
+}
+
 /// GET /api/players/{id}/chart7days
 pub async fn get_player_chart_7days(
     Path(player_id): Path<i64>,
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<ChartResponse>, AppError> {
-    let scores = players::get_chart_7days(player_id).await?;
+    let scores = players::get_chart_7days(&state.pool, player_id).await?;
     let response = ChartResponse {
         scores: scores.into_iter().map(response::score_to_chart_point).collect(),
     };
@@ -161,30 +565,30 @@ pub async fn get_player_chart_7days(
 
 /// GET /api/players/chart - Own player chart
 pub async fn get_own_chart(
+    Query(query): Query<ChartQuery>,
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
 ) -> Result<Json<ChartResponse>, AppError> {
     let player_id = user.player_id
         .ok_or_else(|| AppError::BadRequest("Kein Spieler zugeordnet".into()))?;
-    let scores = players::get_chart(player_id).await?;
-    let response = ChartResponse {
-        scores: scores.into_iter().map(response::score_to_chart_point).collect(),
-    };
-    Ok(Json(response))
+    Ok(Json(load_chart(&state.pool, player_id, &query).await?))
 }
 
 /// GET /api/players/data - Own player data
 pub async fn get_own_data(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
 ) -> Result<Json<PlayerDataResponse>, AppError> {
     let player_id = user.player_id;
 
     let (player, planets, research) = if let Some(pid) = player_id {
-        let player = players::get_by_id(pid).await?;
-        let planets_rows = players::get_planets(pid).await?;
+        let player = players::get_by_id(&state.pool, pid).await?;
+        let planets_rows = players::get_planets(&state.pool, pid).await?;
         let planets: Vec<PlanetResponse> = planets_rows.into_iter()
             .map(response::planet_to_response)
             .collect();
-        let research = player.as_ref().and_then(|p| response::parse_json_map(&p.research));
+        let context = format!("player {}", pid);
+        let research = player.as_ref().and_then(|p| response::parse_json_map(&p.research, &context));
 
         let player_response = player.map(|p| PlayerResponse {
             id: p.id,
@@ -195,8 +599,8 @@ pub async fn get_own_data(
                 tag: p.alliance_tag.clone().unwrap_or_default(),
             }),
             main_coordinates: p.main_coordinates,
-            research: response::parse_json_map(&p.research),
-            scores: response::parse_scores(&p.scores),
+            research: research.clone(),
+            scores: response::parse_scores(&p.scores, &context),
             combat_stats: CombatStats {
                 total: p.combats_total.unwrap_or(0),
                 won: p.combats_won.unwrap_or(0),
@@ -239,8 +643,9 @@ pub struct PlayerStatInput {
 }
 
 pub async fn post_stats(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<StatsRequest>,
+    ApiJson(req): ApiJson<StatsRequest>,
 ) -> Result<Json<PlayersStatsResponse>, AppError> {
     let stats: Vec<players::PlayerStats> = req.players.iter().map(|p| {
         players::PlayerStats {
@@ -256,7 +661,7 @@ pub async fn post_stats(
         }
     }).collect();
 
-    let updated = players::upsert_stats(&stats).await?;
+    let updated = players::upsert_stats(&state.pool, &stats).await?;
 
     Ok(Json(PlayersStatsResponse { success: true, updated }))
 }
@@ -268,19 +673,21 @@ pub struct GetStatsRequest {
 }
 
 pub async fn get_stats(
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<GetStatsRequest>,
+    ApiJson(req): ApiJson<GetStatsRequest>,
 ) -> Result<Json<Vec<PlayerResponse>>, AppError> {
-    let player_rows = players::get_by_ids(&req.ids).await?;
+    let player_rows = players::get_by_ids(&state.pool, &req.ids).await?;
 
     let response: Vec<PlayerResponse> = player_rows.into_iter().map(|p| {
+        let context = format!("player {}", p.id);
         PlayerResponse {
             id: p.id,
             name: p.name,
             alliance: None,
             main_coordinates: p.main_coordinates,
-            research: response::parse_json_map(&p.research),
-            scores: response::parse_scores(&p.scores),
+            research: response::parse_json_map(&p.research, &context),
+            scores: response::parse_scores(&p.scores, &context),
             combat_stats: CombatStats {
                 total: p.combats_total,
                 won: p.combats_won,
@@ -303,9 +710,10 @@ pub async fn get_stats(
 /// POST /api/players/{id}/delete
 pub async fn delete_player(
     Path(player_id): Path<i64>,
+    State(state): State<AppState>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    players::mark_deleted(player_id).await?;
+    players::mark_deleted(&state.pool, player_id).await?;
     Ok(Json(SuccessResponse { success: true }))
 }
 
@@ -322,8 +730,9 @@ pub struct ResearchInput {
 }
 
 pub async fn post_research(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<ResearchRequest>,
+    ApiJson(req): ApiJson<ResearchRequest>,
 ) -> Result<Json<ResearchResponse>, AppError> {
     let player_id = user.player_id
         .ok_or_else(|| AppError::BadRequest("Kein Spieler zugeordnet".into()))?;
@@ -335,7 +744,7 @@ pub async fn post_research(
     let research_json = serde_json::to_string(&research_map)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
-    players::update_research(player_id, &research_json).await?;
+    players::update_research(&state.pool, player_id, &research_json).await?;
 
     Ok(Json(ResearchResponse { success: true, research: research_map }))
 }
@@ -351,20 +760,13 @@ pub struct OverviewRequest {
 
 pub async fn get_overview(
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<OverviewRequest>,
+    ApiJson(req): ApiJson<OverviewRequest>,
 ) -> Result<Json<OverviewResponse>, AppError> {
     // Calculate distances from each own planet to target
     let mut planets: Vec<OverviewPlanetInfo> = Vec::new();
 
     for own_coord in &req.own_planets {
-        let parts: Vec<&str> = own_coord.split(':').collect();
-        if parts.len() != 3 {
-            continue;
-        }
-
-        let own_galaxy: i64 = parts[0].parse().unwrap_or(0);
-        let own_system: i64 = parts[1].parse().unwrap_or(0);
-        let own_planet: i64 = parts[2].parse().unwrap_or(0);
+        let (own_galaxy, own_system, own_planet) = parse_coordinates(own_coord)?;
 
         // Calculate distance (simplified OGame formula)
         let distance = calculate_distance(
@@ -374,12 +776,12 @@ pub async fn get_overview(
 
         // Get last spy report for target
         let spy_report = spy_reports::get_by_coordinates(
-            req.galaxy, req.system, req.planet, "PLANET", 1
+            get_pool().await, req.galaxy, req.system, req.planet, "PLANET", 1
         ).await.ok().and_then(|reports| {
             reports.into_iter().next().map(|r| OverviewSpyReport {
                 id: r.id,
                 created_at: r.created_at.unwrap_or_default(),
-                resources: r.resources.as_ref().and_then(|s| serde_json::from_str(s).ok()),
+                resources: response::parse_json_map(&r.resources, &format!("spy report {}", r.id)),
             })
         });
 
@@ -428,7 +830,7 @@ pub struct UpdateLanguageRequest {
 
 pub async fn update_language(
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<UpdateLanguageRequest>,
+    ApiJson(req): ApiJson<UpdateLanguageRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // Validate language (only allow known languages)
     let valid_languages = ["de", "en"];
@@ -440,3 +842,218 @@ pub async fn update_language(
 
     Ok(Json(SuccessResponse { success: true }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::test_support::{create_admin, create_player, get, get_json, post_json, test_app, ADMIN_API_KEY};
+    use axum::http::StatusCode;
+
+    #[test]
+    fn test_can_view_details() {
+        let user = UserRow {
+            id: 1,
+            api_key: String::new(),
+            player_id: Some(10),
+            alliance_id: Some(5),
+            language: "de".to_string(),
+            role: UserRole::User,
+            last_activity_at: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        assert!(can_view_details(&user, 20, Some(6), false));
+        assert!(can_view_details(&user, 10, None, true));
+        assert!(can_view_details(&user, 20, Some(5), true));
+        assert!(!can_view_details(&user, 20, Some(6), true));
+        assert!(!can_view_details(&user, 20, None, true));
+
+        let admin = UserRow { role: UserRole::Admin, ..user };
+        assert!(can_view_details(&admin, 20, Some(6), true));
+    }
+
+    #[test]
+    fn test_upsert_player_rejects_unknown_fields() {
+        let ok = serde_json::from_str::<UpsertPlayerRequest>(r#"{"id": 1, "name": "Bob", "score_total": 5}"#);
+        assert!(ok.is_ok());
+
+        let typo = serde_json::from_str::<UpsertPlayerRequest>(r#"{"id": 1, "name": "Bob", "score_totl": 5}"#);
+        assert!(typo.err().unwrap().to_string().contains("unknown field `score_totl`"));
+    }
+
+    #[tokio::test]
+    async fn test_upsert_player_unknown_field_returns_400() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+
+        let body = r#"{"id": 1, "name": "Bob", "score_totl": 5}"#;
+        let (status, body) = post_json(&app, "/api/players", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+        assert!(body["message"].as_str().unwrap().contains("score_totl"));
+    }
+
+    #[test]
+    fn test_sum_resources() {
+        let planet = |coordinates: &str, resources: &[(&str, i64)]| PlanetResources {
+            coordinates: coordinates.to_string(),
+            r#type: "PLANET".to_string(),
+            resources: resources.iter().map(|(id, n)| (id.to_string(), *n)).collect(),
+            updated_at: None,
+        };
+        let planets = [
+            planet("1:2:3", &[("901", 100), ("902", 50)]),
+            planet("1:2:4", &[("901", 20), ("903", 7)]),
+        ];
+
+        let total = sum_resources(&planets);
+        assert_eq!(total.get("901"), Some(&120));
+        assert_eq!(total.get("902"), Some(&50));
+        assert_eq!(total.get("903"), Some(&7));
+    }
+
+    #[test]
+    fn test_classify_temperature() {
+        assert_eq!(classify_temperature(-60, -20, 80).0, "cold");
+        assert_eq!(classify_temperature(-20, -20, 80).1, &["good_for_deuterium", "weak_solar_satellites"]);
+        assert_eq!(classify_temperature(30, -20, 80), ("temperate", &[][..]));
+        assert_eq!(classify_temperature(80, -20, 80).0, "hot");
+    }
+
+    #[tokio::test]
+    async fn test_get_planet_insights_skips_planets_without_empire_data() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            "INSERT INTO planets (name, player_id, coordinates, galaxy, system, planet, type, fields_max, temperature)
+             VALUES ('Colony', 42, '1:1:2', 1, 1, 2, 'PLANET', 163, -40)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, body) = get_json(&app, "/api/players/42/planets/insights", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        // Home at 1:1:1 is only known from the galaxy view
+        assert_eq!(body["planets"][0]["temperature"], serde_json::Value::Null);
+        assert_eq!(body["planets"][0]["climate"], serde_json::Value::Null);
+        assert_eq!(body["planets"][0]["hints"], serde_json::json!([]));
+        assert_eq!(body["planets"][1]["temperature"], -40);
+        assert_eq!(body["planets"][1]["climate"], "cold");
+    }
+
+    #[tokio::test]
+    async fn test_get_player_end_to_end() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+
+        let (status, body) = get_json(&app, "/api/players/42", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], 42);
+        assert_eq!(body["name"], "Bob");
+        assert_eq!(body["alliance"]["tag"], "TA");
+        assert_eq!(body["status"]["is_deleted"], false);
+
+        let (status, body) = get_json(&app, "/api/players/7", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "not_found");
+
+        let (status, _) = get_json(&app, "/api/players/42", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = get_json(&app, "/api/players/42", Some("wrong-key")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_get_player_energy() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            "INSERT INTO planets (name, player_id, coordinates, galaxy, system, planet, type, fields_max, energy_used, energy_max)
+             VALUES ('Colony', 42, '1:1:2', 1, 1, 2, 'PLANET', 163, 500, 320)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, body) = get_json(&app, "/api/players/42/energy", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["deficit_count"], 1);
+        assert_eq!(body["planets"][0]["coordinates"], "1:1:1");
+        // Home at 1:1:1 is only known from the galaxy view
+        assert_eq!(body["planets"][0]["energy_max"], serde_json::Value::Null);
+        assert_eq!(body["planets"][0]["balance"], serde_json::Value::Null);
+        assert_eq!(body["planets"][0]["deficit"], false);
+        assert_eq!(body["planets"][1]["name"], "Colony");
+        assert_eq!(body["planets"][1]["balance"], -180);
+        assert_eq!(body["planets"][1]["deficit"], true);
+    }
+
+    #[tokio::test]
+    async fn test_get_player_charts() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            "INSERT INTO player_scores (player_id, score_total, recorded_at) VALUES
+                (42, 100, '2026-10-01 10:00:00'),
+                (42, 150, '2026-10-02 10:00:00')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let (status, body) = post_json(&app, "/api/players/charts", r#"{"ids": [42, 7, 42]}"#, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["42"]["scores"][0]["score_total"], 100);
+        assert_eq!(body["42"]["scores"][1]["score_total"], 150);
+        assert_eq!(body["7"]["scores"].as_array().unwrap().len(), 0);
+
+        let ids: Vec<i64> = (1..=21).collect();
+        let too_many = serde_json::json!({ "ids": ids }).to_string();
+        let (status, _) = post_json(&app, "/api/players/charts", &too_many, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_player() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+        create_player(&pool, 42, "Bob").await;
+        sqlx::query(
+            r#"INSERT INTO spy_reports (external_id, coordinates, galaxy, system, planet, type, resources)
+               VALUES (1, '1:1:1', 1, 1, 1, 'PLANET', '{"901": 500}')"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO battle_reports (external_id, coordinates, galaxy, system, planet, metal)
+             VALUES (1, '1:1:1', 1, 1, 1, 1000)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let response = get(&app, "/api/players/42/export.json", Some(ADMIN_API_KEY)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"player_42.json\""
+        );
+
+        let (_, body) = get_json(&app, "/api/players/42/export.json", Some(ADMIN_API_KEY)).await;
+        assert_eq!(body["player"]["name"], "Bob");
+        assert_eq!(body["planets"].as_array().unwrap().len(), 1);
+        assert_eq!(body["spy_reports"][0]["coordinates"], "1:1:1");
+        assert_eq!(body["spy_reports"][0]["reports"][0]["resources"]["901"], 500);
+        assert_eq!(body["battle_reports"][0]["reports"][0]["metal"], 1000);
+
+        let (status, _) = get_json(&app, "/api/players/7/export.json", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,10 +1,17 @@
 use axum::{
-    extract::{Path, Query, Extension},
+    extract::{Path, Query, Extension, State},
     Json,
 };
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
+use crate::api::extract::ApiJson;
+use crate::api::game_ids::{BuildingMap, DefenseMap, FleetMap, ResearchMap, ResourceMap};
 use crate::api::response::{self, *};
+use crate::api::spy_analysis;
+use crate::api::state::AppState;
+use crate::{get_pool, CONFIG};
+use crate::db::models::{BattleReportHistoryRow, SpyReportRow};
+use crate::db::timestamp::DB_TIMESTAMP_FORMAT;
 use crate::db::queries::{spy_reports, battle_reports, expedition_reports, recycle_reports, hostile_spying};
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -24,60 +31,113 @@ pub struct SpyReportQuery {
 fn default_type() -> String { "PLANET".into() }
 fn default_lines() -> i64 { 10 }
 
+impl SpyReportQuery {
+    /// `type` normalized to PLANET/MOON, so a moon report at the same coordinates
+    /// is never mixed up with the planet's (`?type=moon` would otherwise match nothing)
+    fn planet_type(&self) -> Result<&'static str, AppError> {
+        normalize_planet_type(&self.r#type)
+            .ok_or_else(|| AppError::BadRequest(format!("Ungültiger Typ: {}", self.r#type)))
+    }
+}
+
+fn normalize_planet_type(value: &str) -> Option<&'static str> {
+    match value.trim().to_ascii_uppercase().as_str() {
+        "PLANET" => Some("PLANET"),
+        "MOON" => Some("MOON"),
+        _ => None,
+    }
+}
+
+/// Convert a stored spy report, logging corrupt JSON columns with the report ID
+pub(crate) fn spy_report_info(r: SpyReportRow) -> SpyReportInfo {
+    let context = format!("spy report {}", r.id);
+    SpyReportInfo {
+        id: r.id,
+        created_at: r.created_at.unwrap_or_default(),
+        resources: response::parse_json_map(&r.resources, &context),
+        buildings: response::parse_json_map(&r.buildings, &context),
+        research: response::parse_json_map(&r.research, &context),
+        fleet: response::parse_json_map(&r.fleet, &context),
+        defense: response::parse_json_map(&r.defense, &context),
+    }
+}
+
 /// GET /api/spy-reports/{galaxy}/{system}/{planet}
 pub async fn get_spy_reports(
     Path((galaxy, system, planet)): Path<(i64, i64, i64)>,
     Query(query): Query<SpyReportQuery>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<SpyReportsResponse>, AppError> {
+    let planet_type = query.planet_type()?;
     let reports = spy_reports::get_by_coordinates(
-        galaxy, system, planet, &query.r#type, query.lines
+        get_pool().await, galaxy, system, planet, planet_type, query.lines
     ).await?;
 
     let response = SpyReportsResponse {
         coordinates: format!("{}:{}:{}", galaxy, system, planet),
-        r#type: query.r#type,
+        r#type: planet_type.to_string(),
         reports: reports
             .into_iter()
-            .map(|r| SpyReportInfo {
-                id: r.id,
-                created_at: r.created_at.unwrap_or_default(),
-                resources: response::parse_json_map(&r.resources),
-                buildings: response::parse_json_map(&r.buildings),
-                research: response::parse_json_map(&r.research),
-                fleet: response::parse_json_map(&r.fleet),
-                defense: response::parse_json_map(&r.defense),
-            })
+            .map(spy_report_info)
             .collect(),
     };
 
     Ok(Json(response))
 }
 
+/// GET /api/spy-reports/{galaxy}/{system}/{planet}/latest - Newest report with player, alliance and reporter names
+pub async fn get_latest_spy_report(
+    Path((galaxy, system, planet)): Path<(i64, i64, i64)>,
+    Query(query): Query<SpyReportQuery>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<LatestSpyReportResponse>, AppError> {
+    let planet_type = query.planet_type()?;
+    let report = spy_reports::get_latest(galaxy, system, planet, planet_type)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Kein Spionagebericht gefunden".into()))?;
+
+    Ok(Json(LatestSpyReportResponse {
+        coordinates: format!("{}:{}:{}", galaxy, system, planet),
+        r#type: planet_type.to_string(),
+        created_at: report.created_at,
+        player_name: report.player_name,
+        alliance_name: report.alliance_name,
+        reporter_name: report.reporter_name,
+        resources: report.resources,
+        buildings: report.buildings,
+        fleet: report.fleet,
+        defense: report.defense,
+    }))
+}
+
 /// GET /api/spy-reports/{galaxy}/{system}/{planet}/history - Extended history with reporter info
 pub async fn get_spy_report_history(
     Path((galaxy, system, planet)): Path<(i64, i64, i64)>,
     Query(query): Query<SpyReportQuery>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<SpyReportHistoryResponse>, AppError> {
+    let planet_type = query.planet_type()?;
     let reports = spy_reports::get_history_with_reporter(
-        galaxy, system, planet, &query.r#type, query.lines
+        galaxy, system, planet, planet_type, query.lines
     ).await?;
 
     let response = SpyReportHistoryResponse {
         coordinates: format!("{}:{}:{}", galaxy, system, planet),
-        r#type: query.r#type,
+        r#type: planet_type.to_string(),
         reports: reports
             .into_iter()
-            .map(|r| SpyReportHistoryItem {
-                id: r.id,
-                created_at: r.created_at.unwrap_or_default(),
-                reporter_name: r.reporter_name,
-                resources: response::parse_json_map(&r.resources),
-                buildings: response::parse_json_map(&r.buildings),
-                research: response::parse_json_map(&r.research),
-                fleet: response::parse_json_map(&r.fleet),
-                defense: response::parse_json_map(&r.defense),
+            .map(|r| {
+                let context = format!("spy report {}", r.id);
+                SpyReportHistoryItem {
+                    id: r.id,
+                    created_at: r.created_at.unwrap_or_default(),
+                    reporter_name: r.reporter_name,
+                    resources: response::parse_json_map(&r.resources, &context),
+                    buildings: response::parse_json_map(&r.buildings, &context),
+                    research: response::parse_json_map(&r.research, &context),
+                    fleet: response::parse_json_map(&r.fleet, &context),
+                    defense: response::parse_json_map(&r.defense, &context),
+                }
             })
             .collect(),
     };
@@ -85,6 +145,73 @@ pub async fn get_spy_report_history(
     Ok(Json(response))
 }
 
+/// GET /api/spy-reports/{galaxy}/{system}/{planet}/delta - Compare the two newest reports
+pub async fn get_spy_report_delta(
+    Path((galaxy, system, planet)): Path<(i64, i64, i64)>,
+    Query(query): Query<SpyReportQuery>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<SpyReportDeltaResponse>, AppError> {
+    let planet_type = query.planet_type()?;
+    let mut reports = spy_reports::get_by_coordinates(
+        get_pool().await, galaxy, system, planet, planet_type, 2
+    ).await?
+        .into_iter()
+        .map(spy_report_info);
+
+    let latest = reports.next();
+    let previous = reports.next();
+
+    let delta = match (&latest, &previous) {
+        (Some(l), Some(p)) => Some(SpyReportDelta {
+            hours: spy_analysis::hours_between(&p.created_at, &l.created_at),
+            resources: spy_analysis::diff_maps(l.resources.as_ref(), p.resources.as_ref()),
+            buildings: spy_analysis::diff_maps(l.buildings.as_ref(), p.buildings.as_ref()),
+            research: spy_analysis::diff_maps(l.research.as_ref(), p.research.as_ref()),
+            fleet: spy_analysis::diff_maps(l.fleet.as_ref(), p.fleet.as_ref()),
+            defense: spy_analysis::diff_maps(l.defense.as_ref(), p.defense.as_ref()),
+        }),
+        _ => None,
+    };
+
+    Ok(Json(SpyReportDeltaResponse {
+        coordinates: format!("{}:{}:{}", galaxy, system, planet),
+        r#type: planet_type.to_string(),
+        latest,
+        previous,
+        delta,
+    }))
+}
+
+/// GET /api/spy-reports/{galaxy}/{system}/{planet}/growth - Resource growth estimate
+/// from the last `lines` reports, projected to now
+pub async fn get_spy_report_growth(
+    Path((galaxy, system, planet)): Path<(i64, i64, i64)>,
+    Query(query): Query<SpyReportQuery>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<SpyReportGrowthResponse>, AppError> {
+    let planet_type = query.planet_type()?;
+    let reports = spy_reports::get_history_with_reporter(
+        galaxy, system, planet, planet_type, query.lines
+    ).await?;
+
+    let samples = spy_analysis::resource_samples(&reports);
+
+    let estimate = spy_analysis::estimate_growth(&samples, chrono::Utc::now().naive_utc())
+        .ok_or_else(|| AppError::NotFound("Keine Spionageberichte mit Ressourcen gefunden".into()))?;
+
+    Ok(Json(SpyReportGrowthResponse {
+        coordinates: format!("{}:{}:{}", galaxy, system, planet),
+        r#type: planet_type.to_string(),
+        reports_used: samples.len(),
+        intervals_used: estimate.intervals_used,
+        last_report_at: estimate.last_at.format(DB_TIMESTAMP_FORMAT).to_string(),
+        hours_since_last_report: estimate.hours_since_last,
+        rates_per_hour: estimate.rates_per_hour,
+        last_resources: estimate.last,
+        projected_resources: estimate.projected,
+    }))
+}
+
 #[derive(Deserialize)]
 pub struct CreateSpyReportRequest {
     pub id: i64,
@@ -93,31 +220,64 @@ pub struct CreateSpyReportRequest {
     pub planet: i64,
     pub r#type: String,
     pub report_time: Option<String>,
-    pub resources: Option<HashMap<String, i64>>,
-    pub buildings: Option<HashMap<String, i64>>,
-    pub research: Option<HashMap<String, i64>>,
-    pub fleet: Option<HashMap<String, i64>>,
-    pub defense: Option<HashMap<String, i64>>,
+    pub resources: Option<ResourceMap>,
+    pub buildings: Option<BuildingMap>,
+    pub research: Option<ResearchMap>,
+    pub fleet: Option<FleetMap>,
+    pub defense: Option<DefenseMap>,
+}
+
+impl CreateSpyReportRequest {
+    fn check_game_ids(&self, strict: bool) -> Result<(), AppError> {
+        if let Some(m) = &self.resources { m.check(strict)?; }
+        if let Some(m) = &self.buildings { m.check(strict)?; }
+        if let Some(m) = &self.research { m.check(strict)?; }
+        if let Some(m) = &self.fleet { m.check(strict)?; }
+        if let Some(m) = &self.defense { m.check(strict)?; }
+        Ok(())
+    }
 }
 
 /// POST /api/spy-reports
 pub async fn create_spy_report(
+    State(state): State<AppState>,
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<CreateSpyReportRequest>,
+    ApiJson(req): ApiJson<CreateSpyReportRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
+    req.check_game_ids(state.config.strict_game_ids)?;
+
+    let resources = response::to_json(&req.resources);
+    let buildings = response::to_json(&req.buildings);
+    let fleet = response::to_json(&req.fleet);
+    let defense = response::to_json(&req.defense);
+    let content_hash = spy_reports::content_hash(
+        &format!("{}:{}:{}", req.galaxy, req.system, req.planet),
+        &req.r#type,
+        req.report_time.as_deref(),
+        &[resources.as_deref(), buildings.as_deref(), fleet.as_deref(), defense.as_deref()],
+    );
+
+    // Same report re-parsed under a new ID: don't add another history entry
+    let window = CONFIG.spy_dedup_window_minutes;
+    if window > 0 && spy_reports::has_recent_duplicate(&content_hash, req.id, window).await? {
+        tracing::debug!(external_id = req.id, "Skipping duplicate spy report");
+        return Ok(Json(SuccessResponse { success: true }));
+    }
+
     spy_reports::upsert(
         req.id,
         req.galaxy,
         req.system,
         req.planet,
         &req.r#type,
-        response::to_json(&req.resources).as_deref(),
-        response::to_json(&req.buildings).as_deref(),
+        resources.as_deref(),
+        buildings.as_deref(),
         response::to_json(&req.research).as_deref(),
-        response::to_json(&req.fleet).as_deref(),
-        response::to_json(&req.defense).as_deref(),
+        fleet.as_deref(),
+        defense.as_deref(),
         Some(user.player_id.unwrap_or(1)),
         req.report_time.as_deref(),
+        &content_hash,
     ).await?;
 
     Ok(Json(SuccessResponse { success: true }))
@@ -147,7 +307,7 @@ pub struct CreateBattleReportRequest {
 /// POST /api/battle-reports
 pub async fn create_battle_report(
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<CreateBattleReportRequest>,
+    ApiJson(req): ApiJson<CreateBattleReportRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     battle_reports::upsert(
         req.id,
@@ -182,32 +342,33 @@ pub async fn get_battle_report_history(
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<BattleReportHistoryResponse>, AppError> {
     let reports = battle_reports::get_history_with_reporter(
-        galaxy, system, planet, query.lines
+        get_pool().await, galaxy, system, planet, query.lines
     ).await?;
 
     let response = BattleReportHistoryResponse {
         coordinates: format!("{}:{}:{}", galaxy, system, planet),
-        reports: reports
-            .into_iter()
-            .map(|r| BattleReportHistoryItem {
-                id: r.id,
-                report_id: r.report_id.unwrap_or_default(),
-                created_at: r.created_at.unwrap_or_default(),
-                reporter_name: r.reporter_name,
-                attacker_lost: r.attacker_lost.unwrap_or(0),
-                defender_lost: r.defender_lost.unwrap_or(0),
-                metal: r.metal.unwrap_or(0),
-                crystal: r.crystal.unwrap_or(0),
-                deuterium: r.deuterium.unwrap_or(0),
-                debris_metal: r.debris_metal.unwrap_or(0),
-                debris_crystal: r.debris_crystal.unwrap_or(0),
-            })
-            .collect(),
+        reports: reports.into_iter().map(battle_report_item).collect(),
     };
 
     Ok(Json(response))
 }
 
+pub(crate) fn battle_report_item(r: BattleReportHistoryRow) -> BattleReportHistoryItem {
+    BattleReportHistoryItem {
+        id: r.id,
+        report_id: r.report_id.unwrap_or_default(),
+        created_at: r.created_at.unwrap_or_default(),
+        reporter_name: r.reporter_name,
+        attacker_lost: r.attacker_lost.unwrap_or(0),
+        defender_lost: r.defender_lost.unwrap_or(0),
+        metal: r.metal.unwrap_or(0),
+        crystal: r.crystal.unwrap_or(0),
+        deuterium: r.deuterium.unwrap_or(0),
+        debris_metal: r.debris_metal.unwrap_or(0),
+        debris_crystal: r.debris_crystal.unwrap_or(0),
+    }
+}
+
 // ============================================================================
 // Expedition Reports
 // ============================================================================
@@ -225,7 +386,7 @@ pub struct CreateExpeditionReportRequest {
 /// POST /api/expedition-reports
 pub async fn create_expedition_report(
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<CreateExpeditionReportRequest>,
+    ApiJson(req): ApiJson<CreateExpeditionReportRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     expedition_reports::upsert(
         req.id,
@@ -260,7 +421,7 @@ pub struct CreateRecycleReportRequest {
 /// POST /api/recycle-reports
 pub async fn create_recycle_report(
     Extension(AuthUser(user)): Extension<AuthUser>,
-    Json(req): Json<CreateRecycleReportRequest>,
+    ApiJson(req): ApiJson<CreateRecycleReportRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     recycle_reports::upsert(
         req.id,
@@ -293,7 +454,7 @@ pub struct CreateHostileSpyingRequest {
 /// POST /api/hostile-spying
 pub async fn create_hostile_spying(
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<CreateHostileSpyingRequest>,
+    ApiJson(req): ApiJson<CreateHostileSpyingRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     hostile_spying::upsert(
         req.id,
@@ -308,25 +469,39 @@ pub async fn create_hostile_spying(
 #[derive(Deserialize)]
 pub struct HostileSpyingQuery {
     pub search: Option<String>,
+    pub time_from: Option<String>,
+    pub time_to: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
+    #[serde(default = "default_page_size")]
+    pub page_size: i64,
 }
 
 fn default_page() -> i64 { 1 }
 
-const PAGE_SIZE: i64 = 20;
+fn default_page_size() -> i64 { 20 }
+
+const MAX_PAGE_SIZE: i64 = 100;
+
+/// Clamp a requested page size to 1..=MAX_PAGE_SIZE
+fn clamp_page_size(page_size: i64) -> i64 {
+    page_size.clamp(1, MAX_PAGE_SIZE)
+}
 
 /// GET /api/hostile-spying
 pub async fn get_hostile_spying(
     Query(query): Query<HostileSpyingQuery>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<HostileSpyingResponse>, AppError> {
-    let offset = (query.page - 1) * PAGE_SIZE;
+    let page_size = clamp_page_size(query.page_size);
+    let offset = (query.page - 1) * page_size;
     let search = query.search.as_deref();
+    let time_from = query.time_from.as_deref();
+    let time_to = query.time_to.as_deref();
 
-    let rows = hostile_spying::get(search, PAGE_SIZE, offset).await?;
-    let total = hostile_spying::count(search).await?;
-    let total_pages = (total + PAGE_SIZE - 1) / PAGE_SIZE;
+    let rows = hostile_spying::get(search, time_from, time_to, page_size, offset).await?;
+    let total = hostile_spying::count(search, time_from, time_to).await?;
+    let total_pages = (total + page_size - 1) / page_size;
 
     let data: Vec<HostileSpyingInfo> = rows.into_iter().map(|r| HostileSpyingInfo {
         id: r.id,
@@ -350,6 +525,8 @@ pub struct HostileSpyingOverviewQuery {
     pub time_to: Option<String>,
     #[serde(default = "default_page")]
     pub page: i64,
+    #[serde(default = "default_page_size")]
+    pub page_size: i64,
 }
 
 /// GET /api/hostile-spying/overview - Aggregated view grouped by attacker
@@ -357,14 +534,15 @@ pub async fn get_hostile_spying_overview(
     Query(query): Query<HostileSpyingOverviewQuery>,
     Extension(AuthUser(_user)): Extension<AuthUser>,
 ) -> Result<Json<HostileSpyingOverviewResponse>, AppError> {
-    let offset = (query.page - 1) * PAGE_SIZE;
+    let page_size = clamp_page_size(query.page_size);
+    let offset = (query.page - 1) * page_size;
 
     let rows = hostile_spying::get_overview(
         query.attacker.as_deref(),
         query.target.as_deref(),
         query.time_from.as_deref(),
         query.time_to.as_deref(),
-        PAGE_SIZE,
+        page_size,
         offset,
     ).await?;
 
@@ -375,7 +553,7 @@ pub async fn get_hostile_spying_overview(
         query.time_to.as_deref(),
     ).await?;
 
-    let total_pages = (total + PAGE_SIZE - 1) / PAGE_SIZE;
+    let total_pages = (total + page_size - 1) / page_size;
 
     let data: Vec<HostileSpyingOverviewInfo> = rows.into_iter().map(|r| {
         // Parse targets from comma-separated string
@@ -399,3 +577,91 @@ pub async fn get_hostile_spying_overview(
         total_pages,
     }))
 }
+
+#[derive(Deserialize)]
+pub struct HostileSpyingTargetsQuery {
+    pub target: Option<String>,
+    pub time_from: Option<String>,
+    pub time_to: Option<String>,
+    #[serde(default = "default_page")]
+    pub page: i64,
+    #[serde(default = "default_page_size")]
+    pub page_size: i64,
+}
+
+/// GET /api/hostile-spying/targets - Aggregated view grouped by spied planet, most spied first
+pub async fn get_hostile_spying_targets(
+    Query(query): Query<HostileSpyingTargetsQuery>,
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+) -> Result<Json<HostileSpyingTargetsResponse>, AppError> {
+    let page_size = clamp_page_size(query.page_size);
+    let offset = (query.page - 1) * page_size;
+    let target = query.target.as_deref();
+    let time_from = query.time_from.as_deref();
+    let time_to = query.time_to.as_deref();
+
+    let rows = hostile_spying::get_target_overview(target, time_from, time_to, page_size, offset).await?;
+    let total = hostile_spying::count_target_overview(target, time_from, time_to).await?;
+    let total_pages = (total + page_size - 1) / page_size;
+
+    let data: Vec<HostileSpyingTargetInfo> = rows.into_iter().map(|r| HostileSpyingTargetInfo {
+        target_coordinates: r.target_coordinates,
+        target_name: r.target_name,
+        target_player_name: r.target_player_name,
+        spy_count: r.spy_count,
+        last_spy_time: r.last_spy_time,
+        // Parse attackers from comma-separated string
+        attackers: r.attackers
+            .map(|a| a.split(',').map(|s| s.trim().to_string()).collect())
+            .unwrap_or_default(),
+    }).collect();
+
+    Ok(Json(HostileSpyingTargetsResponse {
+        data,
+        page: query.page,
+        total_pages,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use crate::api::test_support::{create_admin, post_json, test_app_with_config, ADMIN_API_KEY};
+    use crate::Config;
+
+    #[test]
+    fn test_normalize_planet_type() {
+        assert_eq!(normalize_planet_type("moon"), Some("MOON"));
+        assert_eq!(normalize_planet_type(" Planet "), Some("PLANET"));
+        assert_eq!(normalize_planet_type("DEBRIS"), None);
+    }
+
+    #[test]
+    fn test_clamp_page_size() {
+        assert_eq!(clamp_page_size(default_page_size()), 20);
+        assert_eq!(clamp_page_size(0), 1);
+        assert_eq!(clamp_page_size(-5), 1);
+        assert_eq!(clamp_page_size(500), MAX_PAGE_SIZE);
+    }
+
+    #[tokio::test]
+    async fn test_strict_game_ids_reject_invalid_entries_with_400() {
+        let (app, pool) = test_app_with_config(Config { strict_game_ids: true, ..Config::default() }).await;
+        create_admin(&pool).await;
+
+        let body = r#"{"id": 1, "galaxy": 1, "system": 2, "planet": 3, "type": "PLANET",
+            "report_time": "2026-03-10 12:00:00", "resources": {"901": -5, "99999": 7}}"#;
+        let (status, body) = post_json(&app, "/api/spy-reports", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+        let message = body["message"].as_str().unwrap();
+        assert!(message.contains("901=-5") && message.contains("99999=7"), "{message}");
+
+        let body = r#"{"id": 2, "galaxy": 1, "system": 2, "planet": 3, "type": "PLANET",
+            "report_time": "2026-03-10 12:00:00", "resources": {"901": "viel"}}"#;
+        let (status, body) = post_json(&app, "/api/spy-reports", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+    }
+}
@@ -1,8 +1,11 @@
-use axum::{Extension, Json};
+use axum::{extract::Query, Extension, Json};
 use serde::Deserialize;
 use crate::api::auth::AuthUser;
 use crate::api::error::AppError;
-use crate::api::response::SuccessResponse;
+use crate::api::extract::ApiJson;
+use crate::api::response::{AllianceInfo, RankingEntry, RankingResponse, SuccessResponse};
+use crate::db::models::StatType;
+use crate::db::queries::players;
 use crate::get_pool;
 use tracing::debug;
 
@@ -22,16 +25,16 @@ pub struct PlayerStatRow {
 
 #[derive(Debug, Deserialize)]
 pub struct StatsSyncRequest {
-    pub stat_type: String, // total, fleet, research, buildings, defense, honor
+    pub stat_type: StatType,
     pub players: Vec<PlayerStatRow>,
 }
 
 /// POST /api/statistics/sync
 pub async fn sync_statistics(
     Extension(AuthUser(_user)): Extension<AuthUser>,
-    Json(req): Json<StatsSyncRequest>,
+    ApiJson(req): ApiJson<StatsSyncRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
-    debug!(stat_type = %req.stat_type, count = req.players.len(), "Syncing statistics");
+    debug!(stat_type = ?req.stat_type, count = req.players.len(), "Syncing statistics");
 
     let pool = get_pool().await;
 
@@ -66,26 +69,25 @@ pub async fn sync_statistics(
         }
 
         // Update score based on stat_type
-        let query = match req.stat_type.as_str() {
-            "total" => {
+        let query = match req.stat_type {
+            StatType::Total => {
                 "UPDATE players SET score_total = ?, score_total_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            "fleet" => {
+            StatType::Fleet => {
                 "UPDATE players SET score_fleet = ?, score_fleet_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            "research" => {
+            StatType::Research => {
                 "UPDATE players SET score_research = ?, score_research_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            "buildings" => {
+            StatType::Buildings => {
                 "UPDATE players SET score_buildings = ?, score_buildings_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            "defense" => {
+            StatType::Defense => {
                 "UPDATE players SET score_defense = ?, score_defense_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            "honor" => {
+            StatType::Honor => {
                 "UPDATE players SET honorpoints = ?, honorpoints_rank = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
             }
-            _ => continue,
         };
 
         sqlx::query(query)
@@ -96,7 +98,7 @@ pub async fn sync_statistics(
             .await?;
 
         // Insert into player_scores history (only for total score to avoid too many entries)
-        if req.stat_type == "total" {
+        if req.stat_type == StatType::Total {
             sqlx::query(
                 "INSERT INTO player_scores (player_id, score_total, rank_total, recorded_at)
                  VALUES (?, ?, ?, CURRENT_TIMESTAMP)"
@@ -112,3 +114,74 @@ pub async fn sync_statistics(
     debug!("Statistics sync complete");
     Ok(Json(SuccessResponse { success: true }))
 }
+
+/// Query params for GET /api/ranking
+#[derive(Deserialize)]
+pub struct RankingQuery {
+    #[serde(default = "default_ranking_stat")]
+    pub stat: StatType,
+    #[serde(default = "default_ranking_limit")]
+    pub limit: i64,
+}
+
+fn default_ranking_stat() -> StatType {
+    StatType::Total
+}
+
+fn default_ranking_limit() -> i64 {
+    100
+}
+
+/// GET /api/ranking?stat=total&limit=100
+pub async fn get_ranking(
+    Extension(AuthUser(_user)): Extension<AuthUser>,
+    Query(query): Query<RankingQuery>,
+) -> Result<Json<RankingResponse>, AppError> {
+    let limit = query.limit.clamp(1, 1000);
+    let rows = players::get_ranking(get_pool().await, query.stat, limit).await?;
+
+    let players = rows
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| RankingEntry {
+            rank: i as i64 + 1,
+            id: row.id,
+            name: row.name,
+            alliance: row.alliance_name.map(|name| AllianceInfo {
+                id: row.alliance_id.unwrap_or(0),
+                name,
+                tag: row.alliance_tag.unwrap_or_default(),
+            }),
+            score: row.score,
+        })
+        .collect();
+
+    Ok(Json(RankingResponse { stat: query.stat, players }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use crate::api::test_support::{create_admin, post_json, test_app, ADMIN_API_KEY};
+
+    #[test]
+    fn test_unknown_stat_type_rejected() {
+        let ok: StatsSyncRequest = serde_json::from_str(r#"{"stat_type": "total", "players": []}"#).unwrap();
+        assert_eq!(ok.stat_type, StatType::Total);
+
+        let typo = serde_json::from_str::<StatsSyncRequest>(r#"{"stat_type": "totals", "players": []}"#);
+        assert!(typo.is_err());
+    }
+    #[tokio::test]
+    async fn test_unknown_stat_type_returns_400() {
+        let (app, pool) = test_app().await;
+        create_admin(&pool).await;
+
+        let body = r#"{"stat_type": "totals", "players": []}"#;
+        let (status, body) = post_json(&app, "/api/statistics/sync", body, Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+        assert!(body["message"].as_str().unwrap().contains("totals"));
+    }
+}
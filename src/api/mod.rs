@@ -1,5 +1,12 @@
 pub mod auth;
 pub mod error;
+pub mod etag;
+pub mod extract;
+pub mod game_ids;
 pub mod response;
+pub mod spy_analysis;
+pub mod state;
+#[cfg(test)]
+pub mod test_support;
 pub mod handlers;
 pub mod routes;
\ No newline at end of file
@@ -1,14 +1,34 @@
 use serde::Serialize;
 use std::collections::HashMap;
-use crate::db::models::{PlanetRow, PlayerScoreRow};
+use crate::api::error::AppError;
+use crate::db::json_map::parse_stored_json;
+use crate::db::models::{PlanetRow, PlayerScoreRow, StatType};
 
 // ============================================================================
 // Helper Functions (shared across handlers)
 // ============================================================================
 
-/// Parse JSON string to HashMap
-pub fn parse_json_map(json: &Option<String>) -> Option<HashMap<String, i64>> {
-    json.as_ref().and_then(|s| serde_json::from_str(s).ok())
+/// Parse a stored JSON map; corrupt JSON is logged with `context` and becomes an empty map
+pub fn parse_json_map(json: &Option<String>, context: &str) -> Option<HashMap<String, i64>> {
+    json.as_deref().map(|s| parse_stored_json(s, context).unwrap_or_default())
+}
+
+/// Parse "galaxy:system:planet" into its parts, tolerating surrounding whitespace
+/// and the "[1:2:3]" form shown in game
+pub fn parse_coordinates(coordinates: &str) -> Result<(i64, i64, i64), AppError> {
+    let invalid = || AppError::BadRequest(format!("Ungültige Koordinaten: '{}'", coordinates));
+
+    let trimmed = coordinates.trim();
+    let inner = trimmed
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .unwrap_or(trimmed);
+
+    let mut parts = inner.split(':').map(|p| p.trim().parse::<i64>());
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(galaxy)), Some(Ok(system)), Some(Ok(planet)), None) => Ok((galaxy, system, planet)),
+        _ => Err(invalid()),
+    }
 }
 
 /// Serialize optional value to JSON string
@@ -16,10 +36,10 @@ pub fn to_json<T: serde::Serialize>(v: &Option<T>) -> Option<String> {
     v.as_ref().and_then(|v| serde_json::to_string(v).ok())
 }
 
-/// Parse JSON scores to ScoresInfo
-pub fn parse_scores(json: &Option<String>) -> Option<ScoresInfo> {
-    json.as_ref().and_then(|s| {
-        serde_json::from_str::<HashMap<String, i64>>(s).ok().map(|m| ScoresInfo {
+/// Parse JSON scores to ScoresInfo (corrupt JSON is logged with `context`)
+pub fn parse_scores(json: &Option<String>, context: &str) -> Option<ScoresInfo> {
+    json.as_deref().and_then(|s| {
+        parse_stored_json::<HashMap<String, i64>>(s, context).map(|m| ScoresInfo {
             total: *m.get("total").unwrap_or(&0),
             economy: *m.get("economy").unwrap_or(&0),
             research: *m.get("research").unwrap_or(&0),
@@ -31,14 +51,15 @@ pub fn parse_scores(json: &Option<String>) -> Option<ScoresInfo> {
 
 /// Convert PlanetRow to PlanetResponse
 pub fn planet_to_response(p: PlanetRow) -> PlanetResponse {
+    let context = format!("planet {} of player {}", p.coordinates, p.player_id);
     PlanetResponse {
         id: p.id,
         coordinates: p.coordinates,
         r#type: p.r#type.unwrap_or_else(|| "PLANET".to_string()),
-        buildings: parse_json_map(&p.buildings),
-        fleet: parse_json_map(&p.fleet),
-        defense: parse_json_map(&p.defense),
-        resources: parse_json_map(&p.resources),
+        buildings: p.buildings.map(|m| m.into_map(&format!("buildings of {context}"))),
+        fleet: p.fleet.map(|m| m.into_map(&format!("fleet of {context}"))),
+        defense: p.defense.map(|m| m.into_map(&format!("defense of {context}"))),
+        resources: p.resources.map(|m| m.into_map(&format!("resources of {context}"))),
     }
 }
 
@@ -70,6 +91,18 @@ pub struct PlayerResponse {
     pub status: PlayerStatus,
 }
 
+/// GET /api/players/{id}/export.json - Everything known about a player in one file
+#[derive(Serialize)]
+pub struct PlayerExportResponse {
+    pub exported_at: String,
+    pub player: PlayerResponse,
+    pub planets: Vec<PlanetResponse>,
+    /// Latest spy reports per planet/moon, left out where there are none
+    pub spy_reports: Vec<SpyReportsResponse>,
+    /// Latest battle reports per coordinate, left out where there are none
+    pub battle_reports: Vec<BattleReportHistoryResponse>,
+}
+
 #[derive(Serialize)]
 pub struct AllianceInfo {
     pub id: i64,
@@ -77,6 +110,21 @@ pub struct AllianceInfo {
     pub tag: String,
 }
 
+#[derive(Serialize)]
+pub struct AllianceHistoryResponse {
+    pub player_id: i64,
+    pub changes: Vec<AllianceChange>,
+}
+
+#[derive(Serialize)]
+pub struct AllianceChange {
+    pub old_alliance_id: Option<i64>,
+    pub old_alliance_tag: Option<String>,
+    pub new_alliance_id: Option<i64>,
+    pub new_alliance_tag: Option<String>,
+    pub changed_at: String,
+}
+
 #[derive(Serialize)]
 pub struct ScoresInfo {
     pub total: i64,
@@ -118,6 +166,66 @@ pub struct PlanetResponse {
     pub resources: Option<HashMap<String, i64>>,
 }
 
+/// Last synced resources of all planets of a player
+#[derive(Serialize)]
+pub struct PlayerResourcesResponse {
+    pub player_id: i64,
+    pub total: HashMap<String, i64>,
+    pub planets: Vec<PlanetResources>,
+}
+
+#[derive(Serialize)]
+pub struct PlanetResources {
+    pub coordinates: String,
+    pub r#type: String,
+    pub resources: HashMap<String, i64>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlayerEnergyResponse {
+    pub player_id: i64,
+    /// Number of planets using more energy than they produce
+    pub deficit_count: usize,
+    pub planets: Vec<PlanetEnergy>,
+}
+
+#[derive(Serialize)]
+pub struct PlanetEnergy {
+    pub coordinates: String,
+    pub r#type: String,
+    pub name: Option<String>,
+    /// Energy values are `None` until the planet was synced from the empire page
+    pub energy_used: Option<i64>,
+    pub energy_max: Option<i64>,
+    /// `energy_max - energy_used`, negative when production is throttled
+    pub balance: Option<i64>,
+    pub deficit: bool,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlanetInsightsResponse {
+    pub player_id: i64,
+    /// Thresholds the planets were classified with (°C)
+    pub cold_temperature: i64,
+    pub hot_temperature: i64,
+    pub planets: Vec<PlanetInsight>,
+}
+
+#[derive(Serialize)]
+pub struct PlanetInsight {
+    pub coordinates: String,
+    pub name: Option<String>,
+    /// `None` until the planet was synced from the empire page
+    pub temperature: Option<i64>,
+    /// "cold", "temperate" or "hot", `None` without a temperature
+    pub climate: Option<&'static str>,
+    /// Hint codes for the frontend to translate, e.g. "good_for_deuterium"
+    pub hints: Vec<&'static str>,
+    pub updated_at: Option<String>,
+}
+
 // ============================================================================
 // Galaxy
 // ============================================================================
@@ -138,6 +246,16 @@ pub struct GalaxyPlanetInfo {
     pub planet: i64,
     pub r#type: String,
     pub planet_id: Option<i64>,  // pr0game internal planet ID for sync comparison
+    pub has_moon: bool,
+    pub moon: Option<GalaxyMoonInfo>,
+}
+
+/// Moon folded into the planet entry at the same position
+#[derive(Serialize)]
+pub struct GalaxyMoonInfo {
+    pub id: i64,
+    pub name: Option<String>,
+    pub planet_id: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -245,6 +363,54 @@ pub struct SpyReportInfo {
     pub defense: Option<HashMap<String, i64>>,
 }
 
+/// Newest spy report with the names shown by the Discord /spy embed
+#[derive(Serialize)]
+pub struct LatestSpyReportResponse {
+    pub coordinates: String,
+    pub r#type: String,
+    pub created_at: Option<String>,
+    pub player_name: Option<String>,
+    pub alliance_name: Option<String>,
+    pub reporter_name: Option<String>,
+    pub resources: HashMap<String, i64>,
+    pub buildings: HashMap<String, i64>,
+    pub fleet: HashMap<String, i64>,
+    pub defense: HashMap<String, i64>,
+}
+
+#[derive(Serialize)]
+pub struct SpyReportDeltaResponse {
+    pub coordinates: String,
+    pub r#type: String,
+    pub latest: Option<SpyReportInfo>,
+    pub previous: Option<SpyReportInfo>,
+    /// `latest - previous` per category; null unless two reports exist
+    pub delta: Option<SpyReportDelta>,
+}
+
+#[derive(Serialize)]
+pub struct SpyReportDelta {
+    pub hours: Option<f64>,
+    pub resources: HashMap<String, i64>,
+    pub buildings: HashMap<String, i64>,
+    pub research: HashMap<String, i64>,
+    pub fleet: HashMap<String, i64>,
+    pub defense: HashMap<String, i64>,
+}
+
+#[derive(Serialize)]
+pub struct SpyReportGrowthResponse {
+    pub coordinates: String,
+    pub r#type: String,
+    pub reports_used: usize,
+    pub intervals_used: usize,
+    pub last_report_at: String,
+    pub hours_since_last_report: f64,
+    pub rates_per_hour: HashMap<String, f64>,
+    pub last_resources: HashMap<String, i64>,
+    pub projected_resources: HashMap<String, i64>,
+}
+
 // ============================================================================
 // Generic
 // ============================================================================
@@ -254,6 +420,15 @@ pub struct SuccessResponse {
     pub success: bool,
 }
 
+#[derive(Serialize)]
+pub struct EmpireSyncResponse {
+    pub success: bool,
+    pub planets_synced: usize,
+    pub research_updated: bool,
+    /// Planets that weren't known before this sync
+    pub new_planets: usize,
+}
+
 #[derive(Serialize)]
 pub struct MessageCheckResponse {
     pub new_ids: Vec<i64>,
@@ -277,6 +452,17 @@ pub struct LoginUserInfo {
     pub language: String,
 }
 
+#[derive(Serialize)]
+pub struct MeResponse {
+    pub id: i64,
+    pub player_id: Option<i64>,
+    pub player_name: Option<String>,
+    pub alliance: Option<AllianceInfo>,
+    pub role: String,
+    pub is_admin: bool,
+    pub language: String,
+}
+
 // ============================================================================
 // Player Data
 // ============================================================================
@@ -317,6 +503,20 @@ pub struct GalaxySystemInfo {
     pub age_hours: Option<i64>,
 }
 
+#[derive(Serialize)]
+pub struct HubCoverageResponse {
+    pub galaxy: i64,
+    pub systems: Vec<CoverageSystemInfo>,
+}
+
+#[derive(Serialize)]
+pub struct CoverageSystemInfo {
+    pub system: i64,
+    pub scanned: bool,
+    pub last_scan_at: Option<String>,
+    pub age_hours: Option<i64>,
+}
+
 #[derive(Serialize)]
 pub struct HubStatViewResponse {
     pub stat_views: Vec<StatViewInfo>,
@@ -352,6 +552,14 @@ pub struct HubConfigResponse {
     pub galaxy_wrapped: bool,
 }
 
+/// Everything the frontend needs on load
+#[derive(Serialize)]
+pub struct BootstrapResponse {
+    pub config: HubConfigResponse,
+    pub languages: Vec<String>,
+    pub user: MeResponse,
+}
+
 // ============================================================================
 // Hostile Spying
 // ============================================================================
@@ -388,6 +596,23 @@ pub struct HostileSpyingOverviewInfo {
     pub targets: Vec<String>,
 }
 
+#[derive(Serialize)]
+pub struct HostileSpyingTargetsResponse {
+    pub data: Vec<HostileSpyingTargetInfo>,
+    pub page: i64,
+    pub total_pages: i64,
+}
+
+#[derive(Serialize)]
+pub struct HostileSpyingTargetInfo {
+    pub target_coordinates: String,
+    pub target_name: Option<String>,
+    pub target_player_name: Option<String>,
+    pub spy_count: i64,
+    pub last_spy_time: Option<String>,
+    pub attackers: Vec<String>,
+}
+
 // ============================================================================
 // Planets
 // ============================================================================
@@ -399,6 +624,24 @@ pub struct PlanetsNewResponse {
     pub deleted: i64,
 }
 
+/// SSE payload of /api/events/planets: planets and moons a galaxy scan stored for the first time
+#[derive(Serialize, Clone, Debug)]
+pub struct NewPlanetsEvent {
+    pub galaxy: i64,
+    pub system: i64,
+    pub planets: Vec<NewPlanetInfo>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct NewPlanetInfo {
+    pub coordinates: String,
+    pub position: i64,
+    #[serde(rename = "type")]
+    pub planet_type: String,
+    pub player_id: i64,
+    pub name: Option<String>,
+}
+
 // ============================================================================
 // Overview
 // ============================================================================
@@ -561,6 +804,9 @@ pub struct BattleReportHistoryItem {
 #[derive(Serialize)]
 pub struct AdminUsersResponse {
     pub users: Vec<AdminUserInfo>,
+    pub total: i64,
+    pub page: i64,
+    pub total_pages: i64,
 }
 
 #[derive(Serialize)]
@@ -574,6 +820,7 @@ pub struct AdminUserInfo {
     pub role: String,
     pub last_activity_at: Option<String>,
     pub created_at: Option<String>,
+    pub deleted_at: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -583,7 +830,124 @@ pub struct AdminUserCreatedResponse {
     pub api_key: String,
 }
 
+/// Outcome for one name of a bulk user creation
+#[derive(Serialize)]
+pub struct BulkUserResult {
+    pub name: String,
+    /// `created`, `would_create` (dry run), `skipped` (already has a user) or `not_found`
+    pub status: &'static str,
+    pub player_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub api_key: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BulkCreateUsersResponse {
+    pub dry_run: bool,
+    pub results: Vec<BulkUserResult>,
+}
+
 #[derive(Serialize)]
 pub struct AdminCheckResponse {
     pub is_admin: bool,
 }
+
+#[derive(Serialize)]
+pub struct RawConfigResponse {
+    pub entries: Vec<ConfigEntry>,
+}
+
+#[derive(Serialize)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogEntry>,
+}
+
+#[derive(Serialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor_id: i64,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct RankingResponse {
+    pub stat: StatType,
+    pub players: Vec<RankingEntry>,
+}
+
+#[derive(Serialize)]
+pub struct RankingEntry {
+    pub rank: i64,
+    pub id: i64,
+    pub name: String,
+    pub alliance: Option<AllianceInfo>,
+    pub score: i64,
+}
+
+#[derive(Serialize)]
+pub struct AllianceDetailsResponse {
+    pub id: i64,
+    pub name: String,
+    pub tag: String,
+    pub created_at: String,
+    pub member_count: i64,
+    pub planet_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct AllianceRankingResponse {
+    pub alliances: Vec<AllianceRankingEntry>,
+}
+
+#[derive(Serialize)]
+pub struct AllianceRankingEntry {
+    pub rank: i64,
+    pub id: i64,
+    pub name: String,
+    pub tag: String,
+    pub total_score: i64,
+    pub member_count: i64,
+}
+
+#[derive(Serialize)]
+pub struct MaintenanceResponse {
+    pub success: bool,
+    pub vacuum_ms: u64,
+    pub analyze_ms: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coordinates() {
+        assert_eq!(parse_coordinates("1:2:3").unwrap(), (1, 2, 3));
+        assert_eq!(parse_coordinates("  4 : 250 :12 ").unwrap(), (4, 250, 12));
+        assert_eq!(parse_coordinates("[5:6:7]").unwrap(), (5, 6, 7));
+
+        for bad in ["", "1:2", "1:2:3:4", "a:2:3", "1::3"] {
+            assert!(matches!(parse_coordinates(bad), Err(AppError::BadRequest(_))), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_parse_json_map_tolerates_corrupt_json() {
+        let valid = parse_json_map(&Some(r#"{"202": 5}"#.to_string()), "test");
+        assert_eq!(valid.unwrap().get("202"), Some(&5));
+
+        assert_eq!(parse_json_map(&Some("{broken".to_string()), "test"), Some(HashMap::new()));
+        assert_eq!(parse_json_map(&None, "test"), None);
+        assert!(parse_scores(&Some("[]".to_string()), "test").is_none());
+    }
+}
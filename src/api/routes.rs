@@ -3,35 +3,61 @@ use axum::{
     Router,
     middleware,
 };
-use tower_http::services::ServeDir;
-use tower_http::cors::{CorsLayer, Any};
+use axum::extract::Request;
+use axum::response::{IntoResponse, Response};
+use std::path::PathBuf;
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+use tower_http::compression::CompressionLayer;
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer, Any};
 use tower_http::trace::TraceLayer;
 use crate::api::auth::auth_middleware;
-use crate::api::handlers::{admin, players, planets, hub, reports, galaxy, empire, statistics};
+use crate::api::error::AppError;
+use crate::api::state::AppState;
+use crate::Config;
+use crate::api::handlers::{admin, alliances, players, planets, hub, reports, galaxy, empire, events, export, statistics};
 
-pub fn create_router() -> Router {
+pub fn create_router(state: AppState) -> Router {
     let protected = Router::new()
         // Auth
         .route("/login", get(players::login))
+        .route("/me", get(players::get_me))
+        .route("/bootstrap", get(hub::get_bootstrap))
 
         // Users
         .route("/users/language", post(players::update_language))
 
         // Players
+        .route("/players/charts", post(players::get_player_charts))
         .route("/players/{id}", get(players::get_player))
         .route("/players/{id}/planets", get(players::get_player_planets))
+        .route("/players/{id}/planets/insights", get(players::get_planet_insights))
+        .route("/players/{id}/resources", get(players::get_player_resources))
+        .route("/players/{id}/energy", get(players::get_player_energy))
+        .route("/players/{id}/chart", get(players::get_player_chart))
+        .route("/players/{id}/alliance-history", get(players::get_alliance_history))
+        .route("/players/{id}/export.json", get(players::export_player))
         .route("/players/{id}/delete", post(players::delete_player))
         .route("/players", post(players::upsert_player))
 
+        // Alliances
+        .route("/alliances/ranking", get(alliances::get_ranking))
+        .route("/alliances/{id}", get(alliances::get_alliance))
+
         // Planets
         .route("/planets/new", post(planets::create_planets_batch))
 
+        // Events
+        .route("/events/planets", get(events::planet_events))
+
         // Hub
         .route("/hub/planets", get(hub::get_planets))
         .route("/hub/research", get(hub::get_research))
         .route("/hub/playerresearch", get(hub::get_max_research))
         .route("/hub/fleet", get(hub::get_fleet))
         .route("/hub/galaxy", get(hub::get_galaxy_status))
+        .route("/hub/coverage", get(hub::get_coverage))
         .route("/hub/buildings", get(hub::get_buildings))
         .route("/hub/config", get(hub::get_config))
         .route("/hub/stats", get(hub::get_stats))
@@ -39,10 +65,14 @@ pub fn create_router() -> Router {
 
         // Galaxy
         .route("/galaxy/{galaxy}/{system}", get(galaxy::get_system))
+        .route("/galaxy/{galaxy}/{system}/invalidate", post(galaxy::invalidate_system))
 
         // Reports
         .route("/spy-reports/{galaxy}/{system}/{planet}", get(reports::get_spy_reports))
         .route("/spy-reports/{galaxy}/{system}/{planet}/history", get(reports::get_spy_report_history))
+        .route("/spy-reports/{galaxy}/{system}/{planet}/latest", get(reports::get_latest_spy_report))
+        .route("/spy-reports/{galaxy}/{system}/{planet}/delta", get(reports::get_spy_report_delta))
+        .route("/spy-reports/{galaxy}/{system}/{planet}/growth", get(reports::get_spy_report_growth))
         .route("/spy-reports", post(reports::create_spy_report))
         .route("/battle-reports/{galaxy}/{system}/{planet}/history", get(reports::get_battle_report_history))
         .route("/battle-reports", post(reports::create_battle_report))
@@ -50,43 +80,63 @@ pub fn create_router() -> Router {
         .route("/recycle-reports", post(reports::create_recycle_report))
         .route("/hostile-spying", get(reports::get_hostile_spying).post(reports::create_hostile_spying))
         .route("/hostile-spying/overview", get(reports::get_hostile_spying_overview))
+        .route("/hostile-spying/targets", get(reports::get_hostile_spying_targets))
 
         // Empire
         .route("/empire", post(empire::sync_empire))
 
         // Statistics
         .route("/statistics/sync", post(statistics::sync_statistics))
+        .route("/ranking", get(statistics::get_ranking))
 
         // Admin
         .route("/admin/check", get(admin::check_admin))
         .route("/admin/users", get(admin::list_users).post(admin::create_user))
+        .route("/admin/users/bulk", post(admin::bulk_create_users))
         .route("/admin/users/{id}", delete(admin::delete_user))
+        .route("/admin/users/{id}/restore", post(admin::restore_user))
+        .route("/admin/users/{id}/purge", delete(admin::purge_user))
         .route("/admin/users/{id}/role", put(admin::update_user_role))
+        .route("/admin/users/{id}/player", put(admin::update_user_player))
         .route("/admin/users/{id}/apikey", get(admin::get_user_api_key))
         .route("/admin/config", put(admin::update_config))
+        .route("/admin/config/raw", get(admin::get_raw_config).put(admin::update_raw_config))
+        .route("/admin/audit", get(admin::get_audit_log))
+        .route("/admin/maintenance", post(admin::run_maintenance))
+
+        // Export
+        .route("/export", get(export::get_export))
 
-        .layer(middleware::from_fn(auth_middleware));
+        .fallback(not_found)
+        .method_not_allowed_fallback(method_not_allowed)
+        .layer(middleware::from_fn_with_state(state.clone(), auth_middleware))
+        .with_state(state.clone());
 
-    // CORS layer for cross-origin requests from pr0game
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = cors_layer(&state.config);
 
-    // Serve static files from /static folder
-    let static_files = ServeDir::new("static");
+    // Serve static files from STATIC_DIR, with index.html for client-side routes
+    let static_files = static_service(&state.config.static_dir);
+    let index = PathBuf::from(&state.config.static_dir).join("index.html");
+    let api_base_path = state.config.api_base_path.clone();
+
+    // Compress responses (gzip/deflate) based on Accept-Encoding
+    let compression = CompressionLayer::new();
 
     Router::new()
-        .nest("/api", protected)
-        .nest_service("/static", static_files)
+        .nest(&state.config.api_base_path, protected)
+        .nest_service(&state.config.static_base_path, static_files)
+        .fallback(move |request: Request| root_fallback(request, api_base_path.clone(), index.clone()))
+        .layer(compression)
         .layer(cors)
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &axum::http::Request<_>| {
-                    tracing::debug_span!(
+                    // user_id is filled in by auth_middleware once the API key is checked
+                    tracing::info_span!(
                         "http_request",
                         method = %request.method(),
                         uri = %request.uri(),
+                        user_id = tracing::field::Empty,
                     )
                 })
                 .on_request(|request: &axum::http::Request<_>, _span: &tracing::Span| {
@@ -97,11 +147,161 @@ pub fn create_router() -> Router {
                     );
                 })
                 .on_response(|response: &axum::http::Response<_>, latency: std::time::Duration, _span: &tracing::Span| {
-                    tracing::debug!(
-                        status = %response.status(),
-                        latency = ?latency,
-                        "Response sent"
-                    );
+                    // Rejected auth is logged at info so failed attempts show up without debug logging
+                    let status = response.status();
+                    if status == axum::http::StatusCode::UNAUTHORIZED || status == axum::http::StatusCode::FORBIDDEN {
+                        tracing::info!(status = %status, latency = ?latency, "Request rejected");
+                    } else {
+                        tracing::debug!(status = %status, latency = ?latency, "Response sent");
+                    }
                 })
         )
 }
+
+/// Static files with an SPA fallback: unknown paths without a file extension get index.html,
+/// so history-based client routes survive a reload, while missing assets still 404.
+fn static_service(dir: &str) -> ServeDir<Router> {
+    let index = PathBuf::from(dir).join("index.html");
+    let spa_fallback = Router::new().fallback(move |request: Request| spa_index(request, index.clone()));
+    ServeDir::new(dir).fallback(spa_fallback)
+}
+
+async fn spa_index(request: Request, index: PathBuf) -> Response {
+    if is_asset_path(request.uri().path()) {
+        return not_found().await.into_response();
+    }
+    match ServeFile::new(index).oneshot(request).await {
+        Ok(response) => response.into_response(),
+        Err(never) => match never {},
+    }
+}
+
+/// Top-level fallback: GET deep links outside the API (e.g. `/players/42`) get index.html
+/// like client routes below STATIC_BASE_PATH; everything else, or no STATIC_DIR, is a JSON 404.
+async fn root_fallback(request: Request, api_base_path: String, index: PathBuf) -> Response {
+    let path = request.uri().path();
+    let in_api = path.strip_prefix(api_base_path.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+    let has_index = tokio::fs::metadata(&index).await.is_ok_and(|meta| meta.is_file());
+    if request.method() != Method::GET || in_api || !has_index {
+        return not_found().await.into_response();
+    }
+    spa_index(request, index).await
+}
+
+/// Whether the last path segment looks like a file name (`app.js`) rather than a client route
+fn is_asset_path(path: &str) -> bool {
+    path.rsplit('/').next().is_some_and(|name| name.contains('.'))
+}
+
+/// JSON 404 for unknown paths, same body shape as every other API error
+async fn not_found() -> AppError {
+    AppError::NotFound("Route nicht gefunden".into())
+}
+
+/// JSON 405 for known paths called with the wrong method
+async fn method_not_allowed() -> AppError {
+    AppError::MethodNotAllowed
+}
+
+/// CORS layer for cross-origin requests from pr0game
+///
+/// Without CORS_ALLOWED_ORIGINS everything is allowed (dev convenience); otherwise only
+/// the listed origins, the methods the API uses and the headers it reads are allowed.
+fn cors_layer(config: &Config) -> CorsLayer {
+    if config.cors_allowed_origins.is_empty() {
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any);
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| match origin.parse() {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!(origin, "Ignoring invalid CORS origin");
+                None
+            }
+        })
+        .collect();
+    tracing::info!(origins = ?config.cors_allowed_origins, "CORS restricted to allowed origins");
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([
+            HeaderName::from_static("x-api-key"),
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+        ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use crate::api::test_support::{create_admin, create_player, get_json, get_text, test_app_with_config, ADMIN_API_KEY};
+
+    #[tokio::test]
+    async fn test_not_found_is_json() {
+        let response = not_found().await.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["error"], "not_found");
+    }
+
+    #[tokio::test]
+    async fn test_method_not_allowed_is_json() {
+        let response = method_not_allowed().await.into_response();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["error"], "method_not_allowed");
+    }
+
+    #[tokio::test]
+    async fn test_api_base_path() {
+        let config = Config { api_base_path: "/hub/api".to_string(), ..Config::default() };
+        let (app, pool) = test_app_with_config(config).await;
+        create_admin(&pool).await;
+        create_player(&pool, 1, "Bob").await;
+
+        let (status, body) = get_json(&app, "/hub/api/players/1", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "Bob");
+
+        let (status, _) = get_json(&app, "/api/players/1", Some(ADMIN_API_KEY)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_static_dir_with_spa_fallback() {
+        let dir = std::env::temp_dir().join(format!("hub-static-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("index.html"), "<app>").unwrap();
+        std::fs::write(dir.join("app.js"), "js").unwrap();
+
+        let config = Config { static_dir: dir.to_string_lossy().into_owned(), ..Config::default() };
+        let (app, _pool) = test_app_with_config(config).await;
+
+        assert_eq!(get_text(&app, "/static/app.js").await, (StatusCode::OK, "js".to_string()));
+        // Client-side routes get the app shell, missing assets don't
+        assert_eq!(get_text(&app, "/static/players/42").await, (StatusCode::OK, "<app>".to_string()));
+        assert_eq!(get_text(&app, "/static/missing.js").await.0, StatusCode::NOT_FOUND);
+        // Deep links at the root get the app shell too
+        assert_eq!(get_text(&app, "/players/42").await, (StatusCode::OK, "<app>".to_string()));
+        assert_eq!(get_text(&app, "/missing.js").await.0, StatusCode::NOT_FOUND);
+        // API routes keep precedence
+        let (status, body) = get_json(&app, "/api/unknown", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "unauthorized");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
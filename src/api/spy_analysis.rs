@@ -0,0 +1,182 @@
+use chrono::NaiveDateTime;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::db::json_map::parse_stored_json;
+use crate::db::models::SpyReportHistoryRow;
+use crate::db::timestamp::parse_db_timestamp;
+
+/// `{ game_id: amount }` as stored in the report JSON columns
+pub type GameMap = HashMap<String, i64>;
+
+/// Hours from `earlier` to `later` (negative if out of order)
+pub fn hours_between(earlier: &str, later: &str) -> Option<f64> {
+    let earlier = parse_db_timestamp(earlier)?;
+    let later = parse_db_timestamp(later)?;
+    Some((later - earlier).num_seconds() as f64 / 3600.0)
+}
+
+/// Per-ID difference `latest - previous`.
+/// IDs missing on one side count as 0, unchanged IDs are left out.
+pub fn diff_maps(latest: Option<&GameMap>, previous: Option<&GameMap>) -> GameMap {
+    let empty = GameMap::new();
+    let latest = latest.unwrap_or(&empty);
+    let previous = previous.unwrap_or(&empty);
+
+    latest
+        .keys()
+        .chain(previous.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|id| {
+            let diff = latest.get(id).unwrap_or(&0) - previous.get(id).unwrap_or(&0);
+            (diff != 0).then(|| (id.clone(), diff))
+        })
+        .collect()
+}
+
+/// `(time, resources)` samples for [`estimate_growth`]; reports without a
+/// parsable timestamp or resources are skipped
+pub fn resource_samples(reports: &[SpyReportHistoryRow]) -> Vec<(NaiveDateTime, GameMap)> {
+    reports
+        .iter()
+        .filter_map(|r| {
+            let at = parse_db_timestamp(r.created_at.as_deref()?)?;
+            let resources = parse_stored_json(r.resources.as_deref()?, &format!("spy report {}", r.id))?;
+            Some((at, resources))
+        })
+        .collect()
+}
+
+/// Resources that accumulate over time (metal, crystal, deuterium)
+pub const PRODUCED_RESOURCES: [&str; 3] = ["901", "902", "903"];
+
+/// Resource growth derived from a series of spy reports
+#[derive(Debug)]
+pub struct GrowthEstimate {
+    /// Average growth per hour for each produced resource
+    pub rates_per_hour: HashMap<String, f64>,
+    /// Report intervals that contributed to at least one rate
+    pub intervals_used: usize,
+    /// Time of the newest report
+    pub last_at: NaiveDateTime,
+    /// Hours between the newest report and `now`
+    pub hours_since_last: f64,
+    /// Resources in the newest report
+    pub last: GameMap,
+    /// `last` moved forward by the rates to `now`
+    pub projected: GameMap,
+}
+
+/// Estimate resource growth from `(time, resources)` samples and project it to `now`.
+///
+/// Only intervals where a resource grew are used for its rate: a drop means the
+/// planet was raided (or spent resources) in between, which says nothing about
+/// production. Returns `None` without samples.
+pub fn estimate_growth(samples: &[(NaiveDateTime, GameMap)], now: NaiveDateTime) -> Option<GrowthEstimate> {
+    let mut samples: Vec<&(NaiveDateTime, GameMap)> = samples.iter().collect();
+    samples.sort_by_key(|(at, _)| *at);
+    let (last_at, last) = samples.last()?;
+
+    // Per resource: (summed growth, summed hours) over growing intervals
+    let mut totals: HashMap<&str, (i64, f64)> = HashMap::new();
+    let mut intervals_used = 0;
+    for pair in samples.windows(2) {
+        let (from_at, from) = pair[0];
+        let (to_at, to) = pair[1];
+        let hours = (*to_at - *from_at).num_seconds() as f64 / 3600.0;
+        if hours <= 0.0 {
+            continue;
+        }
+
+        let mut used = false;
+        for id in PRODUCED_RESOURCES {
+            let growth = to.get(id).unwrap_or(&0) - from.get(id).unwrap_or(&0);
+            if growth > 0 {
+                let entry = totals.entry(id).or_default();
+                entry.0 += growth;
+                entry.1 += hours;
+                used = true;
+            }
+        }
+        if used {
+            intervals_used += 1;
+        }
+    }
+
+    let rates_per_hour: HashMap<String, f64> = totals
+        .into_iter()
+        .map(|(id, (growth, hours))| (id.to_string(), growth as f64 / hours))
+        .collect();
+
+    let hours_since_last = ((now - *last_at).num_seconds() as f64 / 3600.0).max(0.0);
+    let mut projected = last.clone();
+    for (id, rate) in &rates_per_hour {
+        *projected.entry(id.clone()).or_default() += (rate * hours_since_last).round() as i64;
+    }
+
+    Some(GrowthEstimate {
+        rates_per_hour,
+        intervals_used,
+        last_at: *last_at,
+        hours_since_last,
+        last: last.clone(),
+        projected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, i64)]) -> GameMap {
+        entries.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn test_diff_maps() {
+        let previous = map(&[("401", 10), ("402", 5), ("403", 1)]);
+        let latest = map(&[("401", 25), ("402", 5), ("404", 2)]);
+
+        let diff = diff_maps(Some(&latest), Some(&previous));
+        assert_eq!(diff, map(&[("401", 15), ("403", -1), ("404", 2)]));
+
+        assert_eq!(diff_maps(Some(&latest), None), latest);
+        assert!(diff_maps(None, None).is_empty());
+    }
+
+    #[test]
+    fn test_hours_between() {
+        assert_eq!(hours_between("2025-01-01 10:00:00", "2025-01-01 13:30:00"), Some(3.5));
+        assert_eq!(hours_between("invalid", "2025-01-01 13:30:00"), None);
+    }
+
+    #[test]
+    fn test_estimate_growth_skips_raided_intervals() {
+        let at = |ts: &str| parse_db_timestamp(ts).unwrap();
+        let samples = vec![
+            (at("2025-01-01 00:00:00"), map(&[("901", 1000), ("902", 500)])),
+            (at("2025-01-01 02:00:00"), map(&[("901", 3000), ("902", 900)])),
+            // Raided: resources dropped, interval ignored
+            (at("2025-01-01 03:00:00"), map(&[("901", 100), ("902", 50)])),
+            (at("2025-01-01 05:00:00"), map(&[("901", 2100), ("902", 450)])),
+        ];
+
+        let estimate = estimate_growth(&samples, at("2025-01-01 06:00:00")).unwrap();
+        assert_eq!(estimate.intervals_used, 2);
+        assert_eq!(estimate.rates_per_hour["901"], 1000.0);
+        assert_eq!(estimate.rates_per_hour["902"], 200.0);
+        assert_eq!(estimate.hours_since_last, 1.0);
+        assert_eq!(estimate.projected["901"], 3100);
+        assert_eq!(estimate.projected["902"], 650);
+    }
+
+    #[test]
+    fn test_estimate_growth_single_report() {
+        let at = parse_db_timestamp("2025-01-01 00:00:00").unwrap();
+        let estimate = estimate_growth(&[(at, map(&[("901", 10)]))], at).unwrap();
+        assert!(estimate.rates_per_hour.is_empty());
+        assert_eq!(estimate.projected["901"], 10);
+
+        assert!(estimate_growth(&[], at).is_none());
+    }
+}
@@ -0,0 +1,27 @@
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use crate::api::response::NewPlanetsEvent;
+use crate::Config;
+
+/// Buffered new-planet events per subscriber before slow SSE clients start lagging
+const PLANET_EVENTS_CAPACITY: usize = 64;
+
+/// Shared state handed to handlers via axum's `State`.
+///
+/// Handlers taking the pool from here instead of `get_pool()` can run against any
+/// database, e.g. an in-memory one in tests. Modules are moved over one at a time.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub config: Arc<Config>,
+    /// Fed by galaxy scans that store new planets, read by /api/events/planets
+    pub planet_events: broadcast::Sender<NewPlanetsEvent>,
+}
+
+impl AppState {
+    pub fn new(pool: SqlitePool, config: Config) -> Self {
+        let (planet_events, _) = broadcast::channel(PLANET_EVENTS_CAPACITY);
+        Self { pool, config: Arc::new(config), planet_events }
+    }
+}
@@ -0,0 +1,97 @@
+//! Router-level test harness: the full API against an in-memory database.
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use axum::response::Response;
+use axum::Router;
+use sqlx::SqlitePool;
+use tower::ServiceExt;
+use crate::api::routes::create_router;
+use crate::api::state::AppState;
+use crate::db::connection::connect_memory;
+use crate::Config;
+
+pub const ADMIN_API_KEY: &str = "test-admin-key";
+
+/// Router with default config over a fresh in-memory database with migrations applied
+pub async fn test_app() -> (Router, SqlitePool) {
+    test_app_with_config(Config::default()).await
+}
+
+pub async fn test_app_with_config(config: Config) -> (Router, SqlitePool) {
+    let pool = connect_memory().await;
+    let app = create_router(AppState::new(pool.clone(), config));
+    (app, pool)
+}
+
+/// Admin user authenticating with `ADMIN_API_KEY`
+pub async fn create_admin(pool: &SqlitePool) -> i64 {
+    sqlx::query("INSERT INTO users (api_key, role) VALUES (?, 'admin')")
+        .bind(ADMIN_API_KEY)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+}
+
+/// Player in alliance 1 [TA] with a planet at 1:1:1
+pub async fn create_player(pool: &SqlitePool, id: i64, name: &str) {
+    sqlx::query("INSERT OR IGNORE INTO alliances (id, name, tag) VALUES (1, 'Test Alliance', 'TA')")
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO players (id, name, alliance_id) VALUES (?, ?, 1)")
+        .bind(id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO planets (name, player_id, coordinates, galaxy, system, planet, type)
+         VALUES ('Home', ?, '1:1:1', 1, 1, 1, 'PLANET')",
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+/// Send a GET request, optionally authenticated, and return status and JSON body
+pub async fn get_json(app: &Router, uri: &str, api_key: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let (status, body) = get_raw(app, uri, api_key).await;
+    (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+}
+
+/// Send an unauthenticated GET request and return status and body text
+pub async fn get_text(app: &Router, uri: &str) -> (StatusCode, String) {
+    let (status, body) = get_raw(app, uri, None).await;
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Send a GET request, optionally authenticated, and return the raw response
+pub async fn get(app: &Router, uri: &str, api_key: Option<&str>) -> Response {
+    let mut request = Request::get(uri);
+    if let Some(key) = api_key {
+        request = request.header("X-API-Key", key);
+    }
+    app.clone().oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+/// Send a POST request with a JSON body, optionally authenticated, and return status and JSON body
+pub async fn post_json(app: &Router, uri: &str, body: &str, api_key: Option<&str>) -> (StatusCode, serde_json::Value) {
+    let mut request = Request::post(uri).header("Content-Type", "application/json");
+    if let Some(key) = api_key {
+        request = request.header("X-API-Key", key);
+    }
+    let response = app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null))
+}
+
+async fn get_raw(app: &Router, uri: &str, api_key: Option<&str>) -> (StatusCode, Vec<u8>) {
+    let response = get(app, uri, api_key).await;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, body.to_vec())
+}
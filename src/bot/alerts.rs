@@ -0,0 +1,103 @@
+//! Hostile spying alerts
+//!
+//! Watches incoming hostile spying and warns the bot channel when the alliance
+//! is spied on heavily within a short window.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serenity::all::{ChannelId, CreateMessage};
+use serenity::http::Http;
+use tracing::{error, info};
+
+use crate::bot::format::format_spy_alert;
+use crate::db::queries::hostile_spying;
+use crate::i18n::get_bot_language;
+use crate::CONFIG;
+
+/// How often the recent hostile spying is counted
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Remembers when the last alert went out, so a lasting wave of spies
+/// raises one alert per cooldown instead of one per check
+#[derive(Debug)]
+pub struct AlertDebounce {
+    cooldown: Duration,
+    last_alert: Option<Instant>,
+}
+
+impl AlertDebounce {
+    pub fn new(cooldown: Duration) -> Self {
+        AlertDebounce { cooldown, last_alert: None }
+    }
+
+    /// Whether `count` events should raise an alert at `now`, recording it if so
+    pub fn should_alert(&mut self, count: i64, threshold: u32, now: Instant) -> bool {
+        if threshold == 0 || count < i64::from(threshold) {
+            return false;
+        }
+        if self.last_alert.is_some_and(|last| now.duration_since(last) < self.cooldown) {
+            return false;
+        }
+        self.last_alert = Some(now);
+        true
+    }
+}
+
+/// Periodically count hostile spying of the last `SPY_ALERT_WINDOW_MINUTES` and post
+/// an alert to the bot channel once it reaches `SPY_ALERT_THRESHOLD`
+///
+/// This function runs indefinitely and should be spawned as a tokio task.
+pub async fn run_spy_alert_job(http: Arc<Http>) {
+    let Some(channel_id) = CONFIG.bot_channel_id.map(ChannelId::new) else {
+        return;
+    };
+    let threshold = CONFIG.bot_spy_alert_threshold;
+    let minutes = CONFIG.bot_spy_alert_window_minutes;
+    let mut debounce = AlertDebounce::new(Duration::from_secs(u64::from(minutes) * 60));
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let count = match hostile_spying::count_recent(minutes).await {
+            Ok(count) => count,
+            Err(e) => {
+                error!(error = %e, "Hostile spying alert check failed");
+                continue;
+            }
+        };
+
+        if !debounce.should_alert(count, threshold, Instant::now()) {
+            continue;
+        }
+
+        info!(count, minutes, "Hostile spying threshold reached, posting alert");
+        let message = CreateMessage::new().embed(format_spy_alert(count, minutes, &get_bot_language()));
+        if let Err(e) = channel_id.send_message(&http, message).await {
+            error!(error = %e, "Could not post hostile spying alert");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce_alerts_once_per_cooldown() {
+        let start = Instant::now();
+        let mut debounce = AlertDebounce::new(Duration::from_secs(3600));
+
+        assert!(!debounce.should_alert(4, 5, start));
+        assert!(debounce.should_alert(5, 5, start));
+        assert!(!debounce.should_alert(12, 5, start + Duration::from_secs(60)));
+        assert!(debounce.should_alert(12, 5, start + Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_debounce_disabled_by_zero_threshold() {
+        let mut debounce = AlertDebounce::new(Duration::from_secs(3600));
+        assert!(!debounce.should_alert(100, 0, Instant::now()));
+    }
+}
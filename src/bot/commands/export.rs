@@ -1,14 +1,18 @@
+use std::future::Future;
+
 use serenity::all::{
-    ChannelId, CommandInteraction, Context, CreateAttachment, CreateInteractionResponse,
+    CommandInteraction, Context, CreateAttachment, CreateInteractionResponse,
     CreateInteractionResponseMessage, CreateMessage,
 };
 use tracing::{error, info};
 
-use crate::{tr, i18n, CONFIG};
-use crate::db::queries::bot::build_export_json;
+use crate::{tr, i18n};
+use crate::db::queries::bot::{
+    build_export_json, build_export_json_since, build_stats_export_json, EXPORT_VERSION,
+};
 use super::super::Permission;
 
-use super::respond_error;
+use super::{resolve_channel, respond_error, ChannelKind};
 
 pub async fn handle_export(
     ctx: &Context,
@@ -21,40 +25,78 @@ pub async fn handle_export(
         return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
     }
 
-    let bot_channel_id = match CONFIG.bot_channel_id {
-        Some(id) => id,
+    let since = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "since")
+        .and_then(|o| o.value.as_i64());
+
+    match since {
+        Some(timepoint) => {
+            let label = format!("Galaxy-Export v{} (Delta)", EXPORT_VERSION);
+            run_export(ctx, command, &lang, build_export_json_since(timepoint), "galaxy_export_delta.json", &label).await
+        }
         None => {
-            return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+            let label = format!("Galaxy-Export v{}", EXPORT_VERSION);
+            run_export(ctx, command, &lang, build_export_json(), "galaxy_export.json", &label).await
         }
+    }
+}
+
+pub async fn handle_exportstats(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_manage_users() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.adminOnly")).await;
+    }
+
+    run_export(ctx, command, &lang, build_stats_export_json(), "stats_export.json", "Stats-Export").await
+}
+
+/// Build an export and post it as a JSON attachment to the export channel
+async fn run_export(
+    ctx: &Context,
+    command: &CommandInteraction,
+    lang: &str,
+    build: impl Future<Output = Result<Vec<u8>, sqlx::Error>>,
+    file_name: &str,
+    label: &str,
+) -> Result<(), serenity::Error> {
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Bot) else {
+        return respond_error(ctx, command, &tr!(lang, "bot.errors.channelNotConfigured")).await;
     };
 
     // Send initial "working" response
     let response = CreateInteractionResponse::Message(
         CreateInteractionResponseMessage::new()
-            .content(tr!(&lang, "bot.export.exporting"))
+            .content(tr!(lang, "bot.export.exporting"))
             .ephemeral(true),
     );
     command.create_response(&ctx.http, response).await?;
 
     // Build the JSON export
-    match build_export_json().await {
+    match build.await {
         Ok(json_data) => {
             let size_kb = json_data.len() / 1024;
-            info!(size_kb, "JSON export created");
+            info!(size_kb, file_name, "JSON export created");
 
-            // Create attachment from the JSON string
-            let attachment = CreateAttachment::bytes(json_data.as_bytes(), "galaxy_export.json");
+            // Create attachment from the JSON bytes
+            let attachment = CreateAttachment::bytes(json_data, file_name);
 
             // Send to bot channel with the file
-            let channel_id = ChannelId::new(bot_channel_id);
             let message = CreateMessage::new()
-                .content(format!("Galaxy-Export ({} KB)", size_kb))
+                .content(format!("{} ({} KB)", label, size_kb))
                 .add_file(attachment);
 
             match channel_id.send_message(&ctx.http, message).await {
                 Ok(_) => {
-                    let msg = tr!(&lang, "bot.export.success",
-                        "channel" => &bot_channel_id.to_string(),
+                    let msg = tr!(lang, "bot.export.success",
+                        "channel" => &channel_id.to_string(),
                         "size" => &size_kb.to_string()
                     );
                     command
@@ -71,7 +113,7 @@ pub async fn handle_export(
                         .edit_response(
                             &ctx.http,
                             serenity::all::EditInteractionResponse::new()
-                                .content(tr!(&lang, "bot.errors.sendError")),
+                                .content(tr!(lang, "bot.errors.sendError")),
                         )
                         .await?;
                     Ok(())
@@ -79,12 +121,12 @@ pub async fn handle_export(
             }
         }
         Err(e) => {
-            error!("DB error in /export: {:?}", e);
+            error!("DB error in /{}: {:?}", command.data.name, e);
             command
                 .edit_response(
                     &ctx.http,
                     serenity::all::EditInteractionResponse::new()
-                        .content(tr!(&lang, "bot.export.error")),
+                        .content(tr!(lang, "bot.export.error")),
                 )
                 .await?;
             Ok(())
@@ -0,0 +1,46 @@
+use serenity::all::{
+    Colour, CommandInteraction, Context, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage,
+};
+
+use crate::{tr, i18n};
+use super::super::Permission;
+
+use super::{respond_error, Access, COMMANDS};
+
+pub async fn handle_help(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_use_commands() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
+    }
+
+    let list = |access: Access| -> String {
+        COMMANDS
+            .iter()
+            .filter(|info| info.access == access && info.allowed_for(permission))
+            .map(|info| format!("`/{}` - {}", info.name, info.description(&lang)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(tr!(&lang, "bot.help.title"))
+        .colour(Colour::from_rgb(52, 152, 219))
+        .field(tr!(&lang, "bot.help.userCommands"), list(Access::User), false);
+
+    if permission.can_manage_users() {
+        embed = embed.field(tr!(&lang, "bot.help.adminCommands"), list(Access::Admin), false);
+    }
+
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .embed(embed)
+            .ephemeral(true),
+    );
+    command.create_response(&ctx.http, response).await
+}
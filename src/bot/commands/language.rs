@@ -6,7 +6,7 @@ use tracing::info;
 use crate::{tr, i18n};
 use super::super::Permission;
 
-use super::respond_error;
+use super::{register_commands, respond_error};
 
 pub async fn handle_setlanguage(
     ctx: &Context,
@@ -35,7 +35,19 @@ pub async fn handle_setlanguage(
                         .content(tr!(new_lang, "bot.language.changed", "lang" => new_lang))
                         .ephemeral(true),
                 );
-                command.create_response(&ctx.http, response).await
+                command.create_response(&ctx.http, response).await?;
+
+                // Re-register so Discord shows the command descriptions in the new language
+                let mut guild_ids = ctx.cache.guilds();
+                if let Some(guild_id) = command.guild_id
+                    && !guild_ids.contains(&guild_id)
+                {
+                    guild_ids.push(guild_id);
+                }
+                for guild_id in guild_ids {
+                    register_commands(ctx, guild_id).await;
+                }
+                Ok(())
             } else {
                 let supported = i18n::SUPPORTED_LANGUAGES.join(", ");
                 respond_error(
@@ -1,4 +1,5 @@
 mod export;
+mod help;
 mod language;
 mod planets;
 mod spy;
@@ -6,20 +7,26 @@ mod user;
 mod util;
 
 use serenity::all::{
-    ChannelId, Command, CommandInteraction, CommandOptionType, Context, CreateCommand,
-    CreateCommandOption, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
-    CreateMessage, GuildId,
+    ChannelId, Command, CommandInteraction, CommandOptionType, ComponentInteraction, Context,
+    CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateMessage, GuildId,
 };
+use std::collections::HashMap;
 use tracing::{error, info};
 
 use crate::{tr, i18n, CONFIG};
-use super::get_permission;
+use crate::db::models::StatType;
+use super::{get_permission, limits, Permission};
 
-use export::handle_export;
+use export::{handle_export, handle_exportstats};
+use help::handle_help;
 use language::handle_setlanguage;
 use planets::{handle_markallseen, handle_newplanets};
-use spy::{handle_inactive, handle_spy};
-use user::{handle_adduser, handle_removeuser, handle_sendkey, handle_users};
+use spy::{handle_alliance, handle_defense, handle_estimate, handle_inactive, handle_ranking, handle_spy};
+use user::{
+    handle_adduser, handle_hasuser, handle_removeuser, handle_removeuser_button, handle_sendkey, handle_users,
+    handle_users_page, REMOVE_CANCEL_ID, REMOVE_CONFIRM_ID, USERS_PAGE_ID,
+};
 use util::{handle_info, handle_ping};
 
 /// Clear all global commands (run once to remove duplicates)
@@ -30,80 +37,181 @@ pub async fn clear_global_commands(ctx: &Context) {
     }
 }
 
-/// Register all slash commands with Discord for a specific guild
-pub async fn register_commands(ctx: &Context, guild_id: GuildId) {
-    let commands = vec![
-        // === Utility Commands ===
-        CreateCommand::new("ping").description("Check if bot is responding"),
-        CreateCommand::new("info").description("Show bot information"),
-
-        // === Spy/Stats Commands ===
-        CreateCommand::new("inactive").description("Show top 20 inactive players (farms)"),
-        CreateCommand::new("export").description("Export galaxy data as JSON file"),
-        CreateCommand::new("spy")
-            .description("Show spy report for coordinates")
-            .add_option(
-                CreateCommandOption::new(CommandOptionType::Integer, "galaxy", "Galaxy (1-9)")
-                    .required(true)
-                    .min_int_value(1)
-                    .max_int_value(9),
-            )
+/// Minimum permission needed to run a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    User,
+    Admin,
+}
+
+/// Slash command metadata, shared by `register_commands` and `/help`
+struct CommandInfo {
+    name: &'static str,
+    access: Access,
+}
+
+impl CommandInfo {
+    /// Localized description (`bot.commands.<name>`)
+    fn description(&self, lang: &str) -> String {
+        tr!(lang, &format!("bot.commands.{}", self.name))
+    }
+
+    /// Check if the given permission may run this command
+    fn allowed_for(&self, permission: Permission) -> bool {
+        match self.access {
+            Access::User => permission.can_use_commands(),
+            Access::Admin => permission.can_manage_users(),
+        }
+    }
+}
+
+/// All slash commands, in registration and /help order
+const COMMANDS: &[CommandInfo] = &[
+    // === Utility Commands ===
+    CommandInfo { name: "ping", access: Access::User },
+    CommandInfo { name: "info", access: Access::User },
+    CommandInfo { name: "help", access: Access::User },
+    // === Spy/Stats Commands ===
+    CommandInfo { name: "inactive", access: Access::User },
+    CommandInfo { name: "export", access: Access::User },
+    CommandInfo { name: "exportstats", access: Access::Admin },
+    CommandInfo { name: "spy", access: Access::User },
+    CommandInfo { name: "estimate", access: Access::User },
+    CommandInfo { name: "ranking", access: Access::User },
+    CommandInfo { name: "defense", access: Access::User },
+    CommandInfo { name: "alliance", access: Access::Admin },
+    // === Admin Commands (User Management) ===
+    CommandInfo { name: "adduser", access: Access::Admin },
+    CommandInfo { name: "removeuser", access: Access::Admin },
+    CommandInfo { name: "users", access: Access::Admin },
+    CommandInfo { name: "hasuser", access: Access::Admin },
+    CommandInfo { name: "sendkey", access: Access::Admin },
+    // === Planet Status Commands ===
+    CommandInfo { name: "newplanets", access: Access::Admin },
+    CommandInfo { name: "markallseen", access: Access::Admin },
+    // === Language Command ===
+    CommandInfo { name: "setlanguage", access: Access::Admin },
+];
+
+/// Build a command option with its localized description
+/// (`bot.commandOptions.<command>.<option>`)
+fn option(kind: CommandOptionType, command: &str, name: &str, lang: &str) -> CreateCommandOption {
+    let description = tr!(lang, &format!("bot.commandOptions.{}.{}", command, name));
+    CreateCommandOption::new(kind, name, description)
+}
+
+/// Add the options a command takes
+fn with_options(name: &str, command: CreateCommand, lang: &str) -> CreateCommand {
+    match name {
+        "spy" => with_coordinate_options(name, command, lang, false)
             .add_option(
-                CreateCommandOption::new(CommandOptionType::Integer, "system", "System (1-499)")
-                    .required(true)
-                    .min_int_value(1)
-                    .max_int_value(499),
+                option(CommandOptionType::String, name, "coords_list", lang)
+                    .required(false),
             )
             .add_option(
-                CreateCommandOption::new(CommandOptionType::Integer, "planet", "Planet (1-15)")
-                    .required(true)
-                    .min_int_value(1)
-                    .max_int_value(15),
+                option(CommandOptionType::Boolean, name, "fallback", lang)
+                    .required(false),
             ),
-
-        // === Admin Commands (User Management) ===
-        CreateCommand::new("adduser")
-            .description("Add a new user (admin only)")
+        "estimate" => with_coordinate_options(name, command, lang, true),
+        "ranking" => command
             .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "player", "Player name in game")
-                    .required(true),
+                StatType::ALL.iter().fold(
+                    option(CommandOptionType::String, name, "stat", lang).required(false),
+                    |option, stat| {
+                        let label = tr!(lang, &format!("bot.ranking.stats.{}", stat.as_str()));
+                        option.add_string_choice(label, stat.as_str())
+                    },
+                ),
             )
             .add_option(
-                CreateCommandOption::new(CommandOptionType::User, "discord_user", "Discord user (receives API key via DM)")
+                option(CommandOptionType::Boolean, name, "alliances", lang)
                     .required(false),
             ),
-        CreateCommand::new("removeuser")
-            .description("Remove a user (admin only)")
+        "export" => command.add_option(
+            option(CommandOptionType::Integer, name, "since", lang)
+                .required(false)
+                .min_int_value(0),
+        ),
+        "alliance" => command.add_option(
+            option(CommandOptionType::String, name, "name", lang)
+                .required(true),
+        ),
+        "adduser" => command
             .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "name", "Player name")
+                option(CommandOptionType::String, name, "player", lang)
                     .required(true),
+            )
+            .add_option(
+                option(CommandOptionType::User, name, "discord_user", lang)
+                    .required(false),
             ),
-        CreateCommand::new("users").description("Show all users (admin only)"),
-        CreateCommand::new("sendkey")
-            .description("Resend API key to a user (admin only)")
+        "removeuser" => command.add_option(
+            option(CommandOptionType::String, name, "name", lang)
+                .required(true),
+        ),
+        "hasuser" => command.add_option(
+            option(CommandOptionType::String, name, "player", lang)
+                .required(true),
+        ),
+        "sendkey" => command
             .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "name", "Player name")
+                option(CommandOptionType::String, name, "name", lang)
                     .required(true),
             )
             .add_option(
-                CreateCommandOption::new(CommandOptionType::User, "discord_user", "Discord user")
+                option(CommandOptionType::User, name, "discord_user", lang)
                     .required(true),
             ),
-
-        // === Planet Status Commands ===
-        CreateCommand::new("newplanets")
-            .description("Show all new planets and mark them as seen (admin only)"),
-        CreateCommand::new("markallseen")
-            .description("Mark all new planets as seen without output (admin only)"),
-
-        // === Language Command ===
-        CreateCommand::new("setlanguage")
-            .description("Set or show bot language (admin only)")
+        "newplanets" => command
+            .add_option(
+                option(CommandOptionType::Boolean, name, "include_moons", lang)
+                    .required(false),
+            )
             .add_option(
-                CreateCommandOption::new(CommandOptionType::String, "language", "Language code (en, de)")
+                option(CommandOptionType::Boolean, name, "thread", lang)
                     .required(false),
             ),
-    ];
+        "setlanguage" => command.add_option(
+            option(CommandOptionType::String, name, "language", lang)
+                .required(false),
+        ),
+        _ => command,
+    }
+}
+
+/// Add galaxy/system/planet options (shared by /spy and /estimate)
+fn with_coordinate_options(name: &str, command: CreateCommand, lang: &str, required: bool) -> CreateCommand {
+    command
+        .add_option(
+            option(CommandOptionType::Integer, name, "galaxy", lang)
+                .required(required)
+                .min_int_value(1)
+                .max_int_value(9),
+        )
+        .add_option(
+            option(CommandOptionType::Integer, name, "system", lang)
+                .required(required)
+                .min_int_value(1)
+                .max_int_value(499),
+        )
+        .add_option(
+            option(CommandOptionType::Integer, name, "planet", lang)
+                .required(required)
+                .min_int_value(1)
+                .max_int_value(15),
+        )
+}
+
+/// Register all slash commands with Discord for a specific guild
+pub async fn register_commands(ctx: &Context, guild_id: GuildId) {
+    let lang = i18n::get_bot_language();
+    let commands: Vec<CreateCommand> = COMMANDS
+        .iter()
+        .map(|info| {
+            let command = CreateCommand::new(info.name).description(info.description(&lang));
+            with_options(info.name, command, &lang)
+        })
+        .collect();
 
     match guild_id.set_commands(&ctx.http, commands).await {
         Ok(cmds) => info!("{} slash commands registered for guild {}", cmds.len(), guild_id),
@@ -137,14 +245,21 @@ pub async fn route_command(ctx: &Context, command: &CommandInteraction) {
         // Utility
         "ping" => handle_ping(ctx, command, permission).await,
         "info" => handle_info(ctx, command, permission).await,
+        "help" => handle_help(ctx, command, permission).await,
         // Spy/Stats
         "inactive" => handle_inactive(ctx, command, permission).await,
         "export" => handle_export(ctx, command, permission).await,
+        "exportstats" => handle_exportstats(ctx, command, permission).await,
         "spy" => handle_spy(ctx, command, permission).await,
+        "estimate" => handle_estimate(ctx, command, permission).await,
+        "ranking" => handle_ranking(ctx, command, permission).await,
+        "defense" => handle_defense(ctx, command, permission).await,
+        "alliance" => handle_alliance(ctx, command, permission).await,
         // Admin
         "adduser" => handle_adduser(ctx, command, permission).await,
         "removeuser" => handle_removeuser(ctx, command, permission).await,
         "users" => handle_users(ctx, command, permission).await,
+        "hasuser" => handle_hasuser(ctx, command, permission).await,
         "sendkey" => handle_sendkey(ctx, command, permission).await,
         // Planet Status
         "newplanets" => handle_newplanets(ctx, command, permission).await,
@@ -162,6 +277,31 @@ pub async fn route_command(ctx: &Context, command: &CommandInteraction) {
     }
 }
 
+/// Route button clicks on bot messages by their custom ID
+pub async fn route_component(ctx: &Context, component: &ComponentInteraction) {
+    let role_ids: Vec<u64> = component
+        .member
+        .as_ref()
+        .map(|m| m.roles.iter().map(|r| r.get()).collect())
+        .unwrap_or_default();
+    let permission = get_permission(&role_ids);
+
+    let custom_id = component.data.custom_id.as_str();
+    let result = if let Some(page) = custom_id.strip_prefix(USERS_PAGE_ID).and_then(|p| p.parse().ok()) {
+        handle_users_page(ctx, component, permission, page).await
+    } else if let Some(user_id) = custom_id.strip_prefix(REMOVE_CONFIRM_ID).and_then(|id| id.parse().ok()) {
+        handle_removeuser_button(ctx, component, permission, Some(user_id)).await
+    } else if custom_id == REMOVE_CANCEL_ID {
+        handle_removeuser_button(ctx, component, permission, None).await
+    } else {
+        return;
+    };
+
+    if let Err(e) = result {
+        error!("Error in component '{}': {:?}", custom_id, e);
+    }
+}
+
 /// Send error message as ephemeral response
 pub async fn respond_error(
     ctx: &Context,
@@ -176,58 +316,80 @@ pub async fn respond_error(
     command.create_response(&ctx.http, response).await
 }
 
-/// Post embed to spy channel
-pub async fn post_to_spy_channel(
-    ctx: &Context,
-    command: &CommandInteraction,
-    embeds: Vec<CreateEmbed>,
-) -> Result<(), serenity::Error> {
-    let lang = i18n::get_bot_language();
+/// Default channel of a command when no per-command override is configured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelKind {
+    Spy,
+    Bot,
+}
 
-    let channel_id = match CONFIG.bot_spy_channel_id {
-        Some(id) => ChannelId::new(id),
-        None => {
-            return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
-        }
+/// Channel a command posts to: its `<COMMAND>_CHANNEL_ID` override, else the default channel
+pub fn resolve_channel(command: &str, kind: ChannelKind) -> Option<ChannelId> {
+    let default = match kind {
+        ChannelKind::Spy => CONFIG.bot_spy_channel_id,
+        ChannelKind::Bot => CONFIG.bot_channel_id,
     };
+    pick_channel(&CONFIG.bot_command_channel_ids, command, default).map(ChannelId::new)
+}
 
-    // Post to spy channel
-    let message = CreateMessage::new().embeds(embeds);
-    channel_id.send_message(&ctx.http, message).await?;
-
-    // Confirm to user
-    let response = CreateInteractionResponse::Message(
-        CreateInteractionResponseMessage::new()
-            .content(format!("Spy report posted to <#{}>.", channel_id))
-            .ephemeral(true),
-    );
-    command.create_response(&ctx.http, response).await
+fn pick_channel(overrides: &HashMap<String, u64>, command: &str, default: Option<u64>) -> Option<u64> {
+    overrides.get(command).copied().or(default)
 }
 
-/// Post embed to bot channel
-pub async fn post_to_bot_channel(
+/// Post embeds to `channel_id` and confirm to the user
+pub async fn post_to_channel(
     ctx: &Context,
     command: &CommandInteraction,
-    embed: CreateEmbed,
+    channel_id: ChannelId,
+    embeds: Vec<CreateEmbed>,
 ) -> Result<(), serenity::Error> {
-    let lang = i18n::get_bot_language();
-
-    let channel_id = match CONFIG.bot_channel_id {
-        Some(id) => ChannelId::new(id),
-        None => {
-            return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
-        }
-    };
+    post_messages_to_channel(ctx, command, channel_id, limits::pack_embeds(embeds), format!("Result posted to <#{}>.", channel_id)).await
+}
 
-    // Post to bot channel
-    let message = CreateMessage::new().embed(embed);
-    channel_id.send_message(&ctx.http, message).await?;
+/// Post several messages (each within `limits::EMBEDS_PER_MESSAGE` and `limits::EMBED_TOTAL_MAX`) to `channel_id`,
+/// then confirm to the user with `confirmation`
+pub async fn post_messages_to_channel(
+    ctx: &Context,
+    command: &CommandInteraction,
+    channel_id: ChannelId,
+    messages: Vec<Vec<CreateEmbed>>,
+    confirmation: String,
+) -> Result<(), serenity::Error> {
+    for embeds in messages {
+        let message = CreateMessage::new().embeds(embeds);
+        channel_id.send_message(&ctx.http, message).await?;
+    }
 
     // Confirm to user
     let response = CreateInteractionResponse::Message(
         CreateInteractionResponseMessage::new()
-            .content(format!("Result posted to <#{}>.", channel_id))
+            .content(confirmation)
             .ephemeral(true),
     );
     command.create_response(&ctx.http, response).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_channel_prefers_override() {
+        let overrides = HashMap::from([("inactive".to_string(), 7)]);
+        assert_eq!(pick_channel(&overrides, "inactive", Some(1)), Some(7));
+        assert_eq!(pick_channel(&overrides, "ranking", Some(1)), Some(1));
+        assert_eq!(pick_channel(&overrides, "ranking", None), None);
+    }
+
+    #[test]
+    fn test_command_descriptions_localized() {
+        for lang in i18n::SUPPORTED_LANGUAGES {
+            for info in COMMANDS {
+                let description = info.description(lang);
+                assert_ne!(description, format!("bot.commands.{}", info.name), "{lang}: {}", info.name);
+                // Discord rejects descriptions longer than 100 characters
+                assert!(description.chars().count() <= 100, "{lang}: {}", info.name);
+            }
+        }
+    }
+}
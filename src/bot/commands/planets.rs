@@ -1,18 +1,20 @@
 use serenity::all::{
     ChannelId, CommandInteraction, Context, CreateInteractionResponse,
-    CreateInteractionResponseMessage, CreateMessage,
+    CreateInteractionResponseMessage, CreateMessage, CreateThread,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::{tr, i18n, CONFIG};
+use crate::{tr, tr_plural, i18n, CONFIG};
 use crate::db::queries::bot::{get_new_planets, mark_all_planets_seen, mark_planets_seen_by_ids};
 use super::super::format::format_new_planets;
-use super::super::Permission;
+use super::super::{limits, Permission};
 
-use super::respond_error;
+use super::{resolve_channel, respond_error, ChannelKind};
 
-/// Maximum embeds per Discord message
-const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+/// Whose "seen" state a command works on (None = global planet status)
+fn seen_by(command: &CommandInteraction) -> Option<i64> {
+    (!CONFIG.bot_newplanets_global).then(|| command.user.id.get() as i64)
+}
 
 pub async fn handle_newplanets(
     ctx: &Context,
@@ -25,15 +27,28 @@ pub async fn handle_newplanets(
         return respond_error(ctx, command, &tr!(&lang, "bot.errors.adminOnly")).await;
     }
 
-    let channel_id = match CONFIG.bot_channel_id {
-        Some(id) => ChannelId::new(id),
-        None => {
-            return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
-        }
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Bot) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
     };
 
     // Get new planets
-    let planets = match get_new_planets().await {
+    let include_moons = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "include_moons")
+        .and_then(|o| o.value.as_bool())
+        .unwrap_or(false);
+    let use_thread = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "thread")
+        .and_then(|o| o.value.as_bool())
+        .unwrap_or(false);
+
+    let seen_by = seen_by(command);
+    let planets = match get_new_planets(seen_by, include_moons).await {
         Ok(p) => p,
         Err(e) => {
             error!("DB error in /newplanets: {:?}", e);
@@ -55,11 +70,17 @@ pub async fn handle_newplanets(
     let planet_count = planets.len();
 
     // Format planets
-    let embeds = format_new_planets(&planets, &lang);
+    let embeds = format_new_planets(&planets, &lang, limits::DEFAULT_DESC_BUDGET);
+
+    let channel_id = if use_thread {
+        open_thread(ctx, channel_id, planet_count, &lang).await.unwrap_or(channel_id)
+    } else {
+        channel_id
+    };
 
-    // Send embeds in batches (Discord limit: 10 embeds per message)
-    for chunk in embeds.chunks(MAX_EMBEDS_PER_MESSAGE) {
-        let message = CreateMessage::new().embeds(chunk.to_vec());
+    // Send embeds in batches within Discord's per-message embed and character limits
+    for chunk in limits::pack_embeds(embeds) {
+        let message = CreateMessage::new().embeds(chunk);
         if let Err(e) = channel_id.send_message(&ctx.http, message).await {
             error!("Error sending planet message: {:?}", e);
             return respond_error(ctx, command, &tr!(&lang, "bot.errors.sendError")).await;
@@ -67,7 +88,7 @@ pub async fn handle_newplanets(
     }
 
     // Mark planets as seen
-    match mark_planets_seen_by_ids(&planet_ids).await {
+    match mark_planets_seen_by_ids(&planet_ids, seen_by).await {
         Ok(count) => {
             info!(count, "planets marked as seen");
         }
@@ -77,10 +98,7 @@ pub async fn handle_newplanets(
     }
 
     // Confirm to user
-    let msg = tr!(&lang, "bot.planets.posted",
-        "count" => &planet_count.to_string(),
-        "channel" => &channel_id.to_string()
-    );
+    let msg = tr_plural!(&lang, "bot.planets.posted", planet_count, "channel" => &channel_id.to_string());
     let response = CreateInteractionResponse::Message(
         CreateInteractionResponseMessage::new()
             .content(msg)
@@ -89,6 +107,25 @@ pub async fn handle_newplanets(
     command.create_response(&ctx.http, response).await
 }
 
+/// Post a short header to `channel_id` and open a thread on it for the planet embeds.
+/// Returns None (post to the channel itself) if the thread can't be created.
+async fn open_thread(ctx: &Context, channel_id: ChannelId, count: usize, lang: &str) -> Option<ChannelId> {
+    let header = CreateMessage::new().content(tr!(lang, "bot.planets.newPlanetsCount", "count" => &count.to_string()));
+    let name = format!("{} {}", tr!(lang, "bot.planets.newPlanets"), chrono::Utc::now().format("%Y-%m-%d %H:%M"));
+
+    let result = match channel_id.send_message(&ctx.http, header).await {
+        Ok(message) => channel_id.create_thread_from_message(&ctx.http, message.id, CreateThread::new(name)).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(thread) => Some(thread.id),
+        Err(e) => {
+            warn!("Could not create thread for /newplanets, posting to channel: {:?}", e);
+            None
+        }
+    }
+}
+
 pub async fn handle_markallseen(
     ctx: &Context,
     command: &CommandInteraction,
@@ -100,10 +137,10 @@ pub async fn handle_markallseen(
         return respond_error(ctx, command, &tr!(&lang, "bot.errors.adminOnly")).await;
     }
 
-    match mark_all_planets_seen().await {
+    match mark_all_planets_seen(seen_by(command)).await {
         Ok(count) => {
             info!(count, "planets marked as seen");
-            let msg = tr!(&lang, "bot.planets.markedSeen", "count" => &count.to_string());
+            let msg = tr_plural!(&lang, "bot.planets.markedSeen", count);
             let response = CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new()
                     .content(msg)
@@ -1,12 +1,24 @@
-use serenity::all::{CommandInteraction, Context};
+use serenity::all::{
+    ChannelId, CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
 use tracing::error;
 
-use crate::{tr, i18n};
-use crate::db::queries::bot::{get_spy_report, get_top_inactive};
-use super::super::format::{format_inactive_players, format_spy_report};
-use super::super::Permission;
+use crate::{tr, tr_plural, i18n, CONFIG};
+use crate::api::response::parse_coordinates;
+use crate::api::spy_analysis;
+use crate::db::models::StatType;
+use crate::db::queries::bot::{
+    get_alliance_defense_totals, get_nearest_spy_report, get_ranking, get_spy_report, get_top_defended_planets,
+    get_top_inactive, search_alliances,
+};
+use crate::db::queries::{alliances, spy_reports};
+use super::super::format::{
+    format_alliance, format_alliance_matches, format_alliance_ranking, format_defense_snapshot, format_estimate, format_inactive_players,
+    format_ranking, format_spy_report,
+};
+use super::super::{limits, Permission};
 
-use super::{post_to_bot_channel, post_to_spy_channel, respond_error};
+use super::{post_messages_to_channel, post_to_channel, resolve_channel, respond_error, ChannelKind};
 
 pub async fn handle_inactive(
     ctx: &Context,
@@ -19,10 +31,14 @@ pub async fn handle_inactive(
         return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
     }
 
-    match get_top_inactive().await {
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Bot) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+    };
+
+    match get_top_inactive(&CONFIG.bot_farm_exclude_alliance_ids).await {
         Ok(players) => {
             let embed = format_inactive_players(&players, &lang);
-            post_to_bot_channel(ctx, command, embed).await
+            post_to_channel(ctx, command, channel_id, vec![embed]).await
         }
         Err(e) => {
             error!("DB error in /inactive: {:?}", e);
@@ -31,7 +47,10 @@ pub async fn handle_inactive(
     }
 }
 
-pub async fn handle_spy(
+/// Number of players (or alliances) shown by /ranking
+const RANKING_SIZE: i64 = 10;
+
+pub async fn handle_ranking(
     ctx: &Context,
     command: &CommandInteraction,
     permission: Permission,
@@ -42,44 +61,344 @@ pub async fn handle_spy(
         return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
     }
 
-    let galaxy = command
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Bot) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+    };
+
+    let by_alliance = command
         .data
         .options
         .iter()
-        .find(|o| o.name == "galaxy")
-        .and_then(|o| o.value.as_i64())
-        .unwrap_or(1);
+        .find(|o| o.name == "alliances")
+        .and_then(|o| o.value.as_bool())
+        .unwrap_or(false);
 
-    let system = command
+    // Alliances are ranked by their members' summed total score, `stat` doesn't apply
+    if by_alliance {
+        return match alliances::get_ranking(RANKING_SIZE).await {
+            Ok(alliances) => {
+                let embed = format_alliance_ranking(&alliances, &lang);
+                post_to_channel(ctx, command, channel_id, vec![embed]).await
+            }
+            Err(e) => {
+                error!("DB error in /ranking: {:?}", e);
+                respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await
+            }
+        };
+    }
+
+    // Discord only offers the registered choices, anything else falls back to total
+    let stat = command
         .data
         .options
         .iter()
-        .find(|o| o.name == "system")
-        .and_then(|o| o.value.as_i64())
-        .unwrap_or(1);
+        .find(|o| o.name == "stat")
+        .and_then(|o| o.value.as_str())
+        .and_then(|value| StatType::ALL.into_iter().find(|s| s.as_str() == value))
+        .unwrap_or(StatType::Total);
+
+    match get_ranking(stat, RANKING_SIZE).await {
+        Ok(players) => {
+            let embed = format_ranking(stat, &players, &lang);
+            post_to_channel(ctx, command, channel_id, vec![embed]).await
+        }
+        Err(e) => {
+            error!("DB error in /ranking: {:?}", e);
+            respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await
+        }
+    }
+}
+
+/// Number of planets highlighted by /defense
+const DEFENDED_PLANETS_SIZE: i64 = 10;
+
+/// /defense - Defense units of our alliance in total and its best defended planets
+pub async fn handle_defense(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_use_commands() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
+    }
+
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Bot) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+    };
+
+    let alliance_id = i64::from(CONFIG.bot_ally_id);
+    let result = tokio::try_join!(
+        get_alliance_defense_totals(alliance_id),
+        get_top_defended_planets(alliance_id, DEFENDED_PLANETS_SIZE),
+    );
+
+    match result {
+        Ok((totals, planets)) => {
+            let embed = format_defense_snapshot(&totals, &planets, &lang);
+            post_to_channel(ctx, command, channel_id, vec![embed]).await
+        }
+        Err(e) => {
+            error!("DB error in /defense: {:?}", e);
+            respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await
+        }
+    }
+}
+
+/// Alliances listed when an /alliance search is ambiguous
+const ALLIANCE_SEARCH_LIMIT: i64 = 10;
+
+/// /alliance - Look up an alliance by name or tag prefix
+pub async fn handle_alliance(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_manage_users() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.adminOnly")).await;
+    }
 
-    let planet = command
+    let query = command
         .data
         .options
         .iter()
-        .find(|o| o.name == "planet")
-        .and_then(|o| o.value.as_i64())
-        .unwrap_or(1);
+        .find(|o| o.name == "name")
+        .and_then(|o| o.value.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+
+    let alliances = match search_alliances(&query, ALLIANCE_SEARCH_LIMIT).await {
+        Ok(alliances) => alliances,
+        Err(e) => {
+            error!("DB error in /alliance: {:?}", e);
+            return respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await;
+        }
+    };
+
+    // Exact matches are sorted first, so a typed full tag wins over longer tags sharing its prefix
+    let message = match alliances.as_slice() {
+        [] => CreateInteractionResponseMessage::new()
+            .content(tr!(&lang, "bot.alliance.notFound", "name" => &query)),
+        [first, ..] if alliances.len() == 1
+            || first.tag.eq_ignore_ascii_case(&query)
+            || first.name.eq_ignore_ascii_case(&query) =>
+        {
+            CreateInteractionResponseMessage::new().embed(format_alliance(first, &lang))
+        }
+        _ => CreateInteractionResponseMessage::new().content(format_alliance_matches(&alliances, &lang)),
+    };
+
+    command
+        .create_response(&ctx.http, CreateInteractionResponse::Message(message.ephemeral(true)))
+        .await
+}
+
+pub async fn handle_spy(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_use_commands() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
+    }
+
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Spy) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+    };
 
-    match get_spy_report(galaxy, system, planet).await {
+    if let Some(list) = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "coords_list")
+        .and_then(|o| o.value.as_str())
+    {
+        return handle_spy_batch(ctx, command, channel_id, &lang, list).await;
+    }
+
+    let Some((galaxy, system, planet)) = coordinate_options(command) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.spy.missingCoords")).await;
+    };
+
+    let coords = format!("{}:{}:{}", galaxy, system, planet);
+    match get_spy_report(galaxy, system, planet, "PLANET").await {
         Ok(report) => {
             let embeds = format_spy_report(&report, &lang);
-            post_to_spy_channel(ctx, command, embeds).await
+            return post_to_channel(ctx, command, channel_id, embeds).await;
         }
+        Err(sqlx::Error::RowNotFound) => {}
         Err(e) => {
-            let coords = format!("{}:{}:{}", galaxy, system, planet);
             error!("DB error in /spy {}: {:?}", coords, e);
-            respond_error(
-                ctx,
-                command,
-                &tr!(&lang, "bot.spy.noReport", "coords" => &coords),
-            )
-            .await
+            return respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await;
+        }
+    }
+
+    let fallback = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "fallback")
+        .and_then(|o| o.value.as_bool())
+        .unwrap_or(false);
+    let moon = get_spy_report(galaxy, system, planet, "MOON").await.ok();
+    let channel = channel_id.to_string();
+
+    match (moon, fallback) {
+        (Some(report), true) => {
+            let confirmation = tr!(&lang, "bot.spy.fallbackMoon", "coords" => &coords, "channel" => &channel);
+            post_messages_to_channel(ctx, command, channel_id, vec![format_spy_report(&report, &lang)], confirmation).await
+        }
+        (Some(_), false) => {
+            respond_error(ctx, command, &tr!(&lang, "bot.spy.moonAvailable", "coords" => &coords)).await
         }
+        (None, true) => match get_nearest_spy_report(galaxy, system, planet).await {
+            Ok(report) => {
+                let nearest = format!("{}:{}:{}", report.galaxy, report.system, report.planet);
+                let confirmation = tr!(&lang, "bot.spy.fallbackNearest",
+                    "coords" => &coords,
+                    "nearest" => &nearest,
+                    "channel" => &channel
+                );
+                post_messages_to_channel(ctx, command, channel_id, vec![format_spy_report(&report, &lang)], confirmation).await
+            }
+            Err(_) => respond_error(ctx, command, &tr!(&lang, "bot.spy.noReport", "coords" => &coords)).await,
+        },
+        (None, false) => {
+            respond_error(ctx, command, &tr!(&lang, "bot.spy.noReport", "coords" => &coords)).await
+        }
+    }
+}
+
+/// Max coordinates handled by one /spy coords_list
+const MAX_SPY_BATCH: usize = 10;
+
+/// /spy with coords_list: post the reports for several coordinates at once
+async fn handle_spy_batch(
+    ctx: &Context,
+    command: &CommandInteraction,
+    channel_id: ChannelId,
+    lang: &str,
+    list: &str,
+) -> Result<(), serenity::Error> {
+    let (mut coords, invalid) = parse_coords_list(list);
+    let capped = coords.len() > MAX_SPY_BATCH;
+    coords.truncate(MAX_SPY_BATCH);
+
+    let mut reports = Vec::new();
+    let mut missing = Vec::new();
+    for (galaxy, system, planet) in coords {
+        match get_spy_report(galaxy, system, planet, "PLANET").await {
+            Ok(report) => reports.push(format_spy_report(&report, lang)),
+            Err(_) => missing.push(format!("{}:{}:{}", galaxy, system, planet)),
+        }
+    }
+
+    if reports.is_empty() {
+        let coords = missing.into_iter().chain(invalid).collect::<Vec<_>>().join(", ");
+        return respond_error(ctx, command, &tr!(lang, "bot.spy.noReport", "coords" => &coords)).await;
+    }
+
+    let channel = channel_id.to_string();
+    let mut confirmation = tr_plural!(lang, "bot.spy.batchPosted", reports.len(), "channel" => &channel);
+    if !missing.is_empty() {
+        confirmation.push('\n');
+        confirmation.push_str(&tr!(lang, "bot.spy.batchMissing", "coords" => &missing.join(", ")));
+    }
+    if !invalid.is_empty() {
+        confirmation.push('\n');
+        confirmation.push_str(&tr!(lang, "bot.spy.batchInvalid", "coords" => &invalid.join(", ")));
+    }
+    if capped {
+        confirmation.push('\n');
+        confirmation.push_str(&tr!(lang, "bot.spy.batchCapped", "max" => &MAX_SPY_BATCH.to_string()));
+    }
+
+    let messages = limits::pack_embed_groups(reports);
+    post_messages_to_channel(ctx, command, channel_id, messages, confirmation).await
+}
+
+/// Split "1:2:3 1:2:4,1:2:5" into coordinates (duplicates removed) and the invalid entries
+fn parse_coords_list(list: &str) -> (Vec<(i64, i64, i64)>, Vec<String>) {
+    let mut coords = Vec::new();
+    let mut invalid = Vec::new();
+    for token in list.split(|c: char| c.is_whitespace() || c == ',' || c == ';').filter(|t| !t.is_empty()) {
+        match parse_coordinates(token) {
+            Ok(c) if !coords.contains(&c) => coords.push(c),
+            Ok(_) => {}
+            Err(_) => invalid.push(token.to_string()),
+        }
+    }
+    (coords, invalid)
+}
+
+/// Number of recent reports the estimate is based on
+const ESTIMATE_REPORTS: i64 = 10;
+
+pub async fn handle_estimate(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_use_commands() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.noPermission")).await;
+    }
+
+    let Some(channel_id) = resolve_channel(&command.data.name, ChannelKind::Spy) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.channelNotConfigured")).await;
+    };
+
+    let Some((galaxy, system, planet)) = coordinate_options(command) else {
+        return respond_error(ctx, command, &tr!(&lang, "bot.spy.missingCoords")).await;
+    };
+    let coords = format!("{}:{}:{}", galaxy, system, planet);
+
+    let reports = match spy_reports::get_history_with_reporter(galaxy, system, planet, "PLANET", ESTIMATE_REPORTS).await {
+        Ok(reports) => reports,
+        Err(e) => {
+            error!("DB error in /estimate {}: {:?}", coords, e);
+            return respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await;
+        }
+    };
+
+    let samples = spy_analysis::resource_samples(&reports);
+    match spy_analysis::estimate_growth(&samples, chrono::Utc::now().naive_utc()) {
+        Some(estimate) => {
+            let embed = format_estimate(&coords, &estimate, samples.len(), &lang);
+            post_to_channel(ctx, command, channel_id, vec![embed]).await
+        }
+        None => respond_error(ctx, command, &tr!(&lang, "bot.estimate.noReports", "coords" => &coords)).await,
+    }
+}
+
+/// Read the galaxy/system/planet options shared by /spy and /estimate
+fn coordinate_options(command: &CommandInteraction) -> Option<(i64, i64, i64)> {
+    let get = |name: &str| {
+        command
+            .data
+            .options
+            .iter()
+            .find(|o| o.name == name)
+            .and_then(|o| o.value.as_i64())
+    };
+    Some((get("galaxy")?, get("system")?, get("planet")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_coords_list() {
+        let (coords, invalid) = parse_coords_list("1:2:3 1:2:4,  [1:2:5];1:2:3 foo");
+        assert_eq!(coords, [(1, 2, 3), (1, 2, 4), (1, 2, 5)]);
+        assert_eq!(invalid, ["foo"]);
     }
 }
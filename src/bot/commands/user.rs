@@ -1,12 +1,20 @@
+use chrono::NaiveDateTime;
 use serenity::all::{
-    CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ButtonStyle, CommandInteraction, ComponentInteraction, Context, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
     CreateMessage, UserId,
 };
+use serenity::http::HttpError;
 use tracing::{error, info, warn};
 
-use crate::{tr, i18n, CONFIG};
-use crate::db::queries::bot::{create_user, get_all_users, get_player_by_name, get_user_by_player_name, remove_user};
-use super::super::Permission;
+use crate::{tr, tr_plural, i18n, CONFIG};
+use crate::db::timestamp::parse_db_timestamp;
+use crate::db::models::BotUser;
+use crate::db::queries::bot::{
+    create_user, get_all_users, get_player_by_name, get_user_by_id, get_user_by_player_name, remove_user,
+};
+use super::super::format::format_relative;
+use super::super::{limits, Permission};
 
 use super::respond_error;
 
@@ -78,10 +86,15 @@ pub async fn handle_adduser(
                         let message = CreateMessage::new().content(dm_content);
                         if let Err(e) = dm_channel.send_message(&ctx.http, message).await {
                             warn!("Could not send DM: {:?}", e);
+                            let reason = if dms_disabled(&e) {
+                                tr!(&lang, "bot.user.dmsDisabled", "user" => &user_id.to_string())
+                            } else {
+                                tr!(&lang, "bot.user.dmFailed")
+                            };
                             let content = format!(
                                 "{}\n\n{}\n**{}:** `{}`",
                                 tr!(&lang, "bot.user.created", "name" => &player_name),
-                                tr!(&lang, "bot.user.dmFailed"),
+                                reason,
                                 tr!(&lang, "bot.user.apiKey"),
                                 api_key
                             );
@@ -110,7 +123,7 @@ pub async fn handle_adduser(
                         let content = format!(
                             "{}\n\n{}\n**{}:** `{}`",
                             tr!(&lang, "bot.user.created", "name" => &player_name),
-                            tr!(&lang, "bot.errors.dmError"),
+                            dm_error_message(&e, user_id, &lang),
                             tr!(&lang, "bot.user.apiKey"),
                             api_key
                         );
@@ -177,21 +190,79 @@ pub async fn handle_removeuser(
         }
     };
 
-    match remove_user(user.id).await {
-        Ok(true) => {
-            info!("User for '{}' removed", player_name);
-            let response = CreateInteractionResponse::Message(
-                CreateInteractionResponseMessage::new()
-                    .content(tr!(&lang, "bot.user.removed", "name" => &player_name)),
-            );
-            command.create_response(&ctx.http, response).await
+    // Removing revokes the key, so ask first; the buttons are handled by handle_removeuser_button
+    let name = user.player_name.as_deref().unwrap_or(&player_name);
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(tr!(&lang, "bot.user.removeConfirm", "name" => name, "seconds" => &REMOVE_CONFIRM_SECS.to_string()))
+            .components(vec![CreateActionRow::Buttons(vec![
+                CreateButton::new(format!("{}{}", REMOVE_CONFIRM_ID, user.id))
+                    .label(tr!(&lang, "bot.user.removeConfirmButton"))
+                    .style(ButtonStyle::Danger),
+                CreateButton::new(REMOVE_CANCEL_ID)
+                    .label(tr!(&lang, "bot.user.removeCancelButton"))
+                    .style(ButtonStyle::Secondary),
+            ])])
+            .ephemeral(true),
+    );
+    command.create_response(&ctx.http, response).await
+}
+
+/// Custom ID prefix of the /removeuser confirm button, followed by the user ID
+pub const REMOVE_CONFIRM_ID: &str = "removeuser_confirm:";
+/// Custom ID of the /removeuser cancel button
+pub const REMOVE_CANCEL_ID: &str = "removeuser_cancel";
+/// Seconds the /removeuser buttons stay valid
+const REMOVE_CONFIRM_SECS: i64 = 30;
+
+/// Whether a confirmation prompt sent at `sent_at` (Unix seconds) has expired by `now`
+fn confirmation_expired(sent_at: i64, now: i64) -> bool {
+    now - sent_at > REMOVE_CONFIRM_SECS
+}
+
+/// Confirm (`Some(user_id)`) or cancel (`None`) button of a /removeuser prompt
+pub async fn handle_removeuser_button(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    permission: Permission,
+    user_id: Option<i64>,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    let content = match user_id {
+        _ if !permission.can_manage_users() => tr!(&lang, "bot.errors.adminOnly"),
+        None => tr!(&lang, "bot.user.removeCancelled"),
+        Some(_) if confirmation_expired(component.message.timestamp.unix_timestamp(), chrono::Utc::now().timestamp()) => {
+            tr!(&lang, "bot.user.removeExpired")
         }
-        Ok(false) => {
-            respond_error(ctx, command, &tr!(&lang, "bot.errors.userNotFound", "name" => &player_name)).await
+        Some(user_id) => remove_confirmed(user_id, &lang).await,
+    };
+
+    // Replace the prompt so the buttons can't be clicked twice
+    let response = CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .components(Vec::new()),
+    );
+    component.create_response(&ctx.http, response).await
+}
+
+/// Remove a user after confirmation and describe the outcome
+async fn remove_confirmed(user_id: i64, lang: &str) -> String {
+    let name = match get_user_by_id(user_id).await {
+        Ok(user) => user.player_name.unwrap_or_else(|| user_id.to_string()),
+        Err(_) => return tr!(lang, "bot.errors.userNotFound", "name" => &user_id.to_string()),
+    };
+
+    match remove_user(user_id).await {
+        Ok(true) => {
+            info!("User for '{}' removed", name);
+            tr!(lang, "bot.user.removed", "name" => &name)
         }
+        Ok(false) => tr!(lang, "bot.errors.userNotFound", "name" => &name),
         Err(e) => {
-            error!("Error removing user for '{}': {:?}", player_name, e);
-            respond_error(ctx, command, &tr!(&lang, "bot.user.removeError")).await
+            error!("Error removing user for '{}': {:?}", name, e);
+            tr!(lang, "bot.user.removeError")
         }
     }
 }
@@ -218,42 +289,19 @@ pub async fn handle_users(
                 return command.create_response(&ctx.http, response).await;
             }
 
-            let mut content = format!("**{}**\n```\n", tr!(&lang, "bot.user.listTitle", "count" => &users.len().to_string()));
-            content.push_str(&tr!(&lang, "bot.user.tableHeader"));
-            content.push('\n');
-            content.push_str(&"-".repeat(50));
-            content.push('\n');
-
-            for user in &users {
-                let activity = user
-                    .last_activity_at
-                    .as_deref()
-                    .map(|s| {
-                        s.split(' ').next().unwrap_or("-").to_string()
-                    })
-                    .unwrap_or_else(|| "-".to_string());
-
-                let player_name = user
-                    .player_name
-                    .as_deref()
-                    .unwrap_or("-");
-
-                content.push_str(&format!(
-                    "{:<4} {:<20} {:<10} {:<10}\n",
-                    user.id,
-                    truncate(player_name, 18),
-                    &user.role,
-                    activity
-                ));
-            }
-            content.push_str("```");
-
+            let mut messages = format_users_page(&users, 0, &lang).into_iter();
             let response = CreateInteractionResponse::Message(
                 CreateInteractionResponseMessage::new()
-                    .content(content)
+                    .content(messages.next().unwrap_or_default())
+                    .components(users_page_buttons(0, users_page_count(users.len())))
                     .ephemeral(true),
             );
-            command.create_response(&ctx.http, response).await
+            command.create_response(&ctx.http, response).await?;
+            for content in messages {
+                let followup = CreateInteractionResponseFollowup::new().content(content).ephemeral(true);
+                command.create_followup(&ctx.http, followup).await?;
+            }
+            Ok(())
         }
         Err(e) => {
             error!("Error loading users: {:?}", e);
@@ -262,9 +310,176 @@ pub async fn handle_users(
     }
 }
 
+/// /hasuser: tell an admin whether a player already has an API key
+pub async fn handle_hasuser(
+    ctx: &Context,
+    command: &CommandInteraction,
+    permission: Permission,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    if !permission.can_manage_users() {
+        return respond_error(ctx, command, &tr!(&lang, "bot.errors.adminOnly")).await;
+    }
+
+    let player_name = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "player")
+        .and_then(|o| o.value.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+
+    let content = match get_user_by_player_name(&player_name).await {
+        Ok(user) => tr!(&lang, "bot.user.hasUserYes",
+            "name" => user.player_name.as_deref().unwrap_or(&player_name),
+            "id" => &user.id.to_string(),
+            "role" => &user.role,
+            "activity" => user.last_activity_at.as_deref().unwrap_or("-")
+        ),
+        Err(sqlx::Error::RowNotFound) => tr!(&lang, "bot.user.hasUserNo", "name" => &player_name),
+        Err(e) => {
+            error!("Error looking up user for '{}': {:?}", player_name, e);
+            return respond_error(ctx, command, &tr!(&lang, "bot.errors.dbError")).await;
+        }
+    };
+
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(content)
+            .ephemeral(true),
+    );
+    command.create_response(&ctx.http, response).await
+}
+
+/// Users shown per /users page, keeps each page well below `limits::MESSAGE_CONTENT_MAX`
+const USERS_PAGE_SIZE: usize = 20;
+
+/// Custom ID prefix of the /users page buttons, followed by the target page
+pub const USERS_PAGE_ID: &str = "users_page:";
+
+fn users_page_count(user_count: usize) -> usize {
+    user_count.div_ceil(USERS_PAGE_SIZE).max(1)
+}
+
+/// Render one page of the /users table as code-block messages. A page normally fits into
+/// one message; the rest (if any) is sent as follow-ups so nothing hits the length limit.
+fn format_users_page(users: &[BotUser], page: usize, lang: &str) -> Vec<String> {
+    let pages = users_page_count(users.len());
+    let page = page.min(pages - 1);
+
+    let mut title = format!("**{}**", tr_plural!(lang, "bot.user.listTitle", users.len()));
+    if pages > 1 {
+        title.push_str(&format!(
+            " - {}",
+            tr!(lang, "bot.user.page", "page" => &(page + 1).to_string(), "pages" => &pages.to_string())
+        ));
+    }
+    let header = format!("{}\n{}", tr!(lang, "bot.user.tableHeader"), "-".repeat(50));
+
+    let now = chrono::Utc::now().naive_utc();
+    let rows: Vec<String> = users
+        .iter()
+        .skip(page * USERS_PAGE_SIZE)
+        .take(USERS_PAGE_SIZE)
+        .map(|user| format_user_row(user, now, lang))
+        .collect();
+    limits::pack_code_blocks(&title, &header, &rows)
+}
+
+/// Users without API activity for this many days get a ⚠️ in /users
+const INACTIVE_USER_DAYS: i64 = 14;
+
+fn format_user_row(user: &BotUser, now: NaiveDateTime, lang: &str) -> String {
+    let last_activity = user.last_activity_at.as_deref();
+    let activity = last_activity
+        .and_then(|ts| format_relative(ts, now, lang))
+        .unwrap_or_else(|| "-".to_string());
+    // Warning goes at the end of the row so the emoji width can't shift the columns
+    let dormant = last_activity
+        .and_then(parse_db_timestamp)
+        .is_none_or(|ts| (now - ts).num_days() > INACTIVE_USER_DAYS);
+
+    let player_name = user
+        .player_name
+        .as_deref()
+        .unwrap_or("-");
+
+    let row = format!(
+        "{:<4} {:<20} {:<10} {:<10}",
+        user.id,
+        truncate(player_name, 18),
+        &user.role,
+        activity
+    );
+    if dormant { format!("{} ⚠️", row) } else { row }
+}
+
+/// ◀️/▶️ buttons for the /users pages (none if everything fits on one page)
+fn users_page_buttons(page: usize, pages: usize) -> Vec<CreateActionRow> {
+    if pages <= 1 {
+        return Vec::new();
+    }
+    let page = page.min(pages - 1);
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}{}", USERS_PAGE_ID, page.saturating_sub(1)))
+            .emoji('◀')
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(format!("{}{}", USERS_PAGE_ID, page + 1))
+            .emoji('▶')
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages),
+    ])]
+}
+
+/// Page button of a /users message: re-render the list on the requested page
+pub async fn handle_users_page(
+    ctx: &Context,
+    component: &ComponentInteraction,
+    permission: Permission,
+    page: usize,
+) -> Result<(), serenity::Error> {
+    let lang = i18n::get_bot_language();
+
+    let content = if !permission.can_manage_users() {
+        tr!(&lang, "bot.errors.adminOnly")
+    } else {
+        match get_all_users().await {
+            Ok(users) => {
+                let mut messages = format_users_page(&users, page, &lang).into_iter();
+                let response = CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .content(messages.next().unwrap_or_default())
+                        .components(users_page_buttons(page, users_page_count(users.len()))),
+                );
+                component.create_response(&ctx.http, response).await?;
+                for content in messages {
+                    let followup = CreateInteractionResponseFollowup::new().content(content).ephemeral(true);
+                    component.create_followup(&ctx.http, followup).await?;
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                error!("Error loading users: {:?}", e);
+                tr!(&lang, "bot.errors.dbError")
+            }
+        }
+    };
+
+    let response = CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .content(format!("Error: {}", content))
+            .ephemeral(true),
+    );
+    component.create_response(&ctx.http, response).await
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
+    if s.chars().count() > max_len {
+        format!("{}...", s.chars().take(max_len - 3).collect::<String>())
     } else {
         s.to_string()
     }
@@ -333,7 +548,7 @@ pub async fn handle_sendkey(
             let message = CreateMessage::new().content(dm_content);
             if let Err(e) = dm_channel.send_message(&ctx.http, message).await {
                 warn!("Could not send DM: {:?}", e);
-                return respond_error(ctx, command, &tr!(&lang, "bot.errors.dmError")).await;
+                return respond_error(ctx, command, &dm_error_message(&e, user_id, &lang)).await;
             }
 
             info!("API key for '{}' sent to <@{}>", player_name, user_id);
@@ -346,7 +561,108 @@ pub async fn handle_sendkey(
         }
         Err(e) => {
             warn!("Could not create DM channel: {:?}", e);
-            respond_error(ctx, command, &tr!(&lang, "bot.errors.dmError")).await
+            respond_error(ctx, command, &dm_error_message(&e, user_id, &lang)).await
+        }
+    }
+}
+
+/// Discord error code "Cannot send messages to this user" (DMs disabled or bot blocked)
+const CANNOT_DM_USER: isize = 50007;
+
+/// Whether a DM failed because the user doesn't accept DMs (as opposed to a transient error)
+fn dms_disabled(error: &serenity::Error) -> bool {
+    matches!(
+        error,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.error.code == CANNOT_DM_USER
+    )
+}
+
+fn dm_error_message(error: &serenity::Error, user_id: UserId, lang: &str) -> String {
+    if dms_disabled(error) {
+        tr!(lang, "bot.user.dmsDisabled", "user" => &user_id.to_string())
+    } else {
+        tr!(lang, "bot.errors.dmError")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users(count: usize) -> Vec<BotUser> {
+        (1..=count as i64)
+            .map(|id| BotUser {
+                id,
+                api_key: String::new(),
+                player_id: Some(id),
+                player_name: Some(format!("Player{id}")),
+                alliance_id: None,
+                role: "user".to_string(),
+                last_activity_at: Some("2026-01-01 12:00:00".to_string()),
+                updated_at: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_users_pages() {
+        let users = users(45);
+        assert_eq!(users_page_count(users.len()), 3);
+        assert_eq!(users_page_count(0), 1);
+
+        let last = format_users_page(&users, 2, "en").concat();
+        assert!(last.contains("Player41") && last.contains("Player45"));
+        assert!(!last.contains("Player40"));
+        // Out of range pages clamp to the last one
+        assert_eq!(format_users_page(&users, 9, "en").concat(), last);
+
+        assert!(users_page_buttons(0, 1).is_empty());
+        assert_eq!(users_page_buttons(1, 3).len(), 1);
+    }
+
+    #[test]
+    fn test_users_table_stays_under_message_limit() {
+        let mut users = users(200);
+        for user in &mut users {
+            user.player_name = Some(format!("Spieler-Ümläüt-{}-with-a-long-name", user.id));
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let rows: Vec<String> = users.iter().map(|user| format_user_row(user, now, "en")).collect();
+        let messages = limits::pack_code_blocks("**Users (200)**", "ID   Player", &rows);
+        assert!(messages.len() > 1);
+        assert!(messages.iter().all(|m| m.chars().count() <= limits::MESSAGE_CONTENT_MAX));
+
+        for page in 0..users_page_count(users.len()) {
+            let messages = format_users_page(&users, page, "en");
+            assert!(messages.iter().all(|m| m.chars().count() <= limits::MESSAGE_CONTENT_MAX));
         }
     }
+
+    #[test]
+    fn test_user_row_flags_dormant_users() {
+        let now = parse_db_timestamp("2026-01-20 12:00:00").unwrap();
+        let mut user = users(1).remove(0);
+
+        user.last_activity_at = Some("2026-01-17 12:00:00".to_string());
+        let active = format_user_row(&user, now, "en");
+        assert!(active.contains("3d ago") && !active.contains('⚠'));
+
+        user.last_activity_at = Some("2025-12-01 12:00:00".to_string());
+        let dormant = format_user_row(&user, now, "en");
+        assert!(dormant.ends_with("⚠️"));
+        // Columns stay aligned: the warning only follows the padded row
+        assert_eq!(dormant.find("50d ago"), active.find("3d ago"));
+
+        user.last_activity_at = None;
+        assert!(format_user_row(&user, now, "en").ends_with("⚠️"));
+    }
+
+    #[test]
+    fn test_confirmation_expired() {
+        assert!(!confirmation_expired(1_000, 1_000));
+        assert!(!confirmation_expired(1_000, 1_030));
+        assert!(confirmation_expired(1_000, 1_031));
+    }
 }
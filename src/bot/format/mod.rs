@@ -1,12 +1,26 @@
+use std::collections::HashMap;
+use chrono::NaiveDateTime;
 use serenity::all::{CreateEmbed, Colour};
-use crate::db::models::{BotSpyReport, InactivePlayer, NewPlanet};
-use crate::tr;
+use crate::api::spy_analysis::{GrowthEstimate, PRODUCED_RESOURCES};
+use crate::db::timestamp::parse_db_timestamp;
+use crate::db::models::{
+    AllianceRankingRow, AllianceSearchRow, BotSpyReport, DefendedPlanet, DefenseTotal, InactivePlayer, NewPlanet, RankingRow, StatType,
+};
+use crate::bot::limits;
+use crate::{tr, tr_plural, CONFIG};
 
 /// Format a spy report as Discord embeds
 pub fn format_spy_report(report: &BotSpyReport, lang: &str) -> Vec<CreateEmbed> {
-    let coords = format!("{}:{}:{}", report.galaxy, report.system, report.planet);
+    let marker = if report.planet_type.as_deref() == Some("MOON") { "🌙 " } else { "" };
+    let coords = format!("{}{}:{}:{}", marker, report.galaxy, report.system, report.planet);
     let unknown = tr!(lang, "bot.spy.unknown");
-    let timestamp = report.created_at.as_deref().unwrap_or(&unknown);
+    let timestamp = match report.created_at.as_deref() {
+        Some(ts) => match format_relative(ts, chrono::Utc::now().naive_utc(), lang) {
+            Some(ago) => format!("{} ({})", ts, ago),
+            None => ts.to_string(),
+        },
+        None => unknown.clone(),
+    };
     let reporter = report.reporter_name.as_deref().unwrap_or(&unknown);
     let player = report.player_name.as_deref().unwrap_or(&unknown);
     let alliance = report.alliance_name.as_deref().unwrap_or("-");
@@ -53,6 +67,57 @@ pub fn format_spy_report(report: &BotSpyReport, lang: &str) -> Vec<CreateEmbed>
     ]
 }
 
+/// Compact relative age of a DB timestamp, e.g. "5m ago", "3h ago", "2d ago"
+pub fn format_relative(ts: &str, now: NaiveDateTime, lang: &str) -> Option<String> {
+    let minutes = (now - parse_db_timestamp(ts)?).num_minutes().max(0);
+    let text = match minutes {
+        0 => tr!(lang, "bot.time.justNow"),
+        1..60 => tr!(lang, "bot.time.minutesAgo", "count" => &minutes.to_string()),
+        60..1440 => tr!(lang, "bot.time.hoursAgo", "count" => &(minutes / 60).to_string()),
+        _ => tr!(lang, "bot.time.daysAgo", "count" => &(minutes / 1440).to_string()),
+    };
+    Some(text)
+}
+
+/// Format the alert posted when the alliance is spied on heavily
+pub fn format_spy_alert(count: i64, minutes: u32, lang: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(tr!(lang, "bot.spyAlert.title"))
+        .description(tr_plural!(lang, "bot.spyAlert.description", count, "minutes" => &minutes.to_string()))
+        .colour(Colour::from_rgb(235, 33, 50))
+}
+
+/// Format a projected resource estimate as Discord embed
+pub fn format_estimate(coords: &str, estimate: &GrowthEstimate, reports_used: usize, lang: &str) -> CreateEmbed {
+    let mut desc = String::new();
+    for id in PRODUCED_RESOURCES {
+        let projected = estimate.projected.get(id).copied().unwrap_or(0);
+        let rate = estimate.rates_per_hour.get(id).copied().unwrap_or(0.0);
+        desc.push_str(&format!(
+            "{}: **{}** ({})\n",
+            tr!(lang, &format!("gameIds.resources.{}", id)),
+            format_number(projected),
+            tr!(lang, "bot.estimate.rate", "rate" => &format_number(rate.round() as i64))
+        ));
+    }
+
+    let confidence = match estimate.intervals_used {
+        0 => tr!(lang, "bot.estimate.confidenceLow"),
+        1 => tr!(lang, "bot.estimate.confidenceMedium"),
+        n => tr!(lang, "bot.estimate.confidenceHigh", "count" => &n.to_string()),
+    };
+    desc.push_str(&format!("\n{}\n*{}*", confidence, tr!(lang, "bot.estimate.caveat")));
+
+    CreateEmbed::new()
+        .author(serenity::all::CreateEmbedAuthor::new(tr!(lang, "bot.estimate.title")))
+        .title(coords)
+        .colour(Colour::from_rgb(235, 225, 52))
+        .description(desc)
+        .footer(serenity::all::CreateEmbedFooter::new(tr_plural!(lang, "bot.estimate.basedOn", reports_used,
+            "hours" => &format!("{:.1}", estimate.hours_since_last)
+        )))
+}
+
 /// Format top inactive players as Discord embed
 pub fn format_inactive_players(players: &[InactivePlayer], lang: &str) -> CreateEmbed {
     let mut desc = String::new();
@@ -62,7 +127,10 @@ pub fn format_inactive_players(players: &[InactivePlayer], lang: &str) -> Create
     let since_label = tr!(lang, "bot.inactive.since");
 
     for (i, player) in players.iter().enumerate() {
-        let name = player.name.as_deref().unwrap_or("?");
+        let name = match player.alliance_tag.as_deref().filter(|tag| !tag.is_empty()) {
+            Some(tag) => format!("{} [{}]", player.name.as_deref().unwrap_or("?"), tag),
+            None => player.name.as_deref().unwrap_or("?").to_string(),
+        };
         let score = player.score_total.unwrap_or(0);
         let fleet = player.score_fleet.unwrap_or(0);
         let inactive_date = player.inactive_since.as_deref().unwrap_or("?");
@@ -81,11 +149,111 @@ pub fn format_inactive_players(players: &[InactivePlayer], lang: &str) -> Create
     }
 
     CreateEmbed::new()
-        .title(tr!(lang, "bot.inactive.title"))
+        .title(tr_plural!(lang, "bot.inactive.title", players.len()))
         .colour(Colour::from_rgb(241, 196, 15))
         .description(desc)
 }
 
+/// Format the universe leaderboard for /ranking
+pub fn format_ranking(stat: StatType, players: &[RankingRow], lang: &str) -> CreateEmbed {
+    let mut desc = String::new();
+
+    for (i, player) in players.iter().enumerate() {
+        let alliance = player
+            .alliance_tag
+            .as_deref()
+            .map(|tag| format!(" [{}]", tag))
+            .unwrap_or_default();
+        desc.push_str(&format!(
+            "**{}. {}**{} - {}\n",
+            i + 1, player.name, alliance, format_number(player.score)
+        ));
+    }
+
+    if desc.is_empty() {
+        desc = tr!(lang, "bot.ranking.noPlayers");
+    }
+
+    let stat_name = tr!(lang, &format!("bot.ranking.stats.{}", stat.as_str()));
+    CreateEmbed::new()
+        .title(tr!(lang, "bot.ranking.title", "stat" => &stat_name))
+        .colour(Colour::from_rgb(52, 152, 219))
+        .description(desc)
+}
+
+/// Format the alliance leaderboard for /ranking alliances:true
+pub fn format_alliance_ranking(alliances: &[AllianceRankingRow], lang: &str) -> CreateEmbed {
+    let mut desc = String::new();
+
+    for (i, alliance) in alliances.iter().enumerate() {
+        desc.push_str(&format!(
+            "**{}. {} [{}]** - {} ({})\n",
+            i + 1,
+            alliance.name,
+            alliance.tag,
+            format_number(alliance.total_score),
+            tr_plural!(lang, "bot.ranking.members", alliance.member_count)
+        ));
+    }
+
+    if desc.is_empty() {
+        desc = tr!(lang, "bot.ranking.noAlliances");
+    }
+
+    CreateEmbed::new()
+        .title(tr!(lang, "bot.ranking.allianceTitle"))
+        .colour(Colour::from_rgb(52, 152, 219))
+        .description(desc)
+}
+
+/// Format the alliance defense snapshot for /defense
+pub fn format_defense_snapshot(totals: &[DefenseTotal], planets: &[DefendedPlanet], lang: &str) -> CreateEmbed {
+    let embed = CreateEmbed::new()
+        .title(tr!(lang, "bot.defense.title"))
+        .colour(Colour::from_rgb(227, 26, 237));
+
+    if totals.is_empty() {
+        return embed.description(tr!(lang, "bot.defense.noDefense"));
+    }
+
+    let mut desc = String::new();
+    for total in totals {
+        let label = tr!(lang, &format!("gameIds.defense.{}", total.defense_id));
+        append_value(&mut desc, &label, Some(&total.amount));
+    }
+
+    let mut top = String::new();
+    for (i, planet) in planets.iter().enumerate() {
+        let marker = if planet.planet_type.as_deref() == Some("MOON") { "🌙 " } else { "" };
+        top.push_str(&format!(
+            "**{}. {}{}** ({}) - {}\n",
+            i + 1, marker, planet.coordinates, planet.player_name, format_number(planet.total_units)
+        ));
+    }
+
+    embed
+        .description(desc)
+        .field(tr!(lang, "bot.defense.topPlanets"), top, false)
+}
+
+/// Format a single alliance found by /alliance
+pub fn format_alliance(alliance: &AllianceSearchRow, lang: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(format!("[{}] {}", alliance.tag, alliance.name))
+        .colour(Colour::from_rgb(52, 152, 219))
+        .field(tr!(lang, "bot.alliance.members"), alliance.member_count.to_string(), true)
+        .field(tr!(lang, "bot.alliance.totalScore"), format_number(alliance.total_score), true)
+}
+
+/// List alliances matching an ambiguous /alliance search
+pub fn format_alliance_matches(alliances: &[AllianceSearchRow], lang: &str) -> String {
+    let mut text = tr!(lang, "bot.alliance.multiple");
+    for alliance in alliances {
+        text.push_str(&format!("\n• **[{}]** {}", alliance.tag, alliance.name));
+    }
+    text
+}
+
 // === Private helper functions ===
 
 fn format_resources(r: &BotSpyReport, lang: &str) -> String {
@@ -94,7 +262,35 @@ fn format_resources(r: &BotSpyReport, lang: &str) -> String {
     append_value(&mut s, &tr!(lang, "gameIds.resources.902"), r.resources.get("902"));
     append_value(&mut s, &tr!(lang, "gameIds.resources.903"), r.resources.get("903"));
     append_value(&mut s, &tr!(lang, "gameIds.resources.904"), r.resources.get("904"));
-    if s.is_empty() { tr!(lang, "bot.spy.noData") } else { s }
+    if s.is_empty() {
+        return tr!(lang, "bot.spy.noData");
+    }
+
+    let percent = CONFIG.bot_raid_loot_percent;
+    if let Some((loot, cargos)) = estimate_loot(&r.resources, percent, CONFIG.bot_large_cargo_capacity) {
+        s.push_str("\n\n");
+        s.push_str(&tr_plural!(lang, "bot.spy.estimatedLoot", cargos,
+            "percent" => &percent.to_string(),
+            "loot" => &format_number(loot)));
+    }
+    s
+}
+
+/// Loot a raid takes (`percent` of metal, crystal and deuterium) and the Large Cargos needed for it.
+/// None if the report doesn't show any of these resources.
+fn estimate_loot(resources: &HashMap<String, i64>, percent: u32, cargo_capacity: i64) -> Option<(i64, i64)> {
+    let amounts: Vec<i64> = PRODUCED_RESOURCES
+        .iter()
+        .filter_map(|id| resources.get(*id).copied())
+        .collect();
+    if amounts.is_empty() {
+        return None;
+    }
+
+    let loot = amounts.iter().map(|&a| a.max(0)).sum::<i64>() * i64::from(percent) / 100;
+    let capacity = cargo_capacity.max(1);
+    let cargos = (loot + capacity - 1) / capacity;
+    Some((loot, cargos))
 }
 
 fn format_buildings(r: &BotSpyReport, lang: &str) -> String {
@@ -143,13 +339,13 @@ fn format_fleet(r: &BotSpyReport, lang: &str) -> String {
 }
 
 fn append_value(s: &mut String, label: &str, value: Option<&i64>) {
-    if let Some(&v) = value {
-        if v > 0 {
-            if !s.is_empty() {
-                s.push('\n');
-            }
-            s.push_str(&format!("{}: **{}**", label, format_number(v)));
+    if let Some(&v) = value
+        && v > 0
+    {
+        if !s.is_empty() {
+            s.push('\n');
         }
+        s.push_str(&format!("{}: **{}**", label, format_number(v)));
     }
 }
 
@@ -165,12 +361,10 @@ fn format_number(n: i64) -> String {
     }
 }
 
-/// Discord limits: 6000 chars per embed, 25 fields, 10 embeds per message
-const MAX_EMBED_DESC_LEN: usize = 4000; // Leave some buffer
-
 /// Format new planets as Discord embeds
-/// Splits into multiple embeds if content exceeds Discord limits
-pub fn format_new_planets(planets: &[NewPlanet], lang: &str) -> Vec<CreateEmbed> {
+/// Splits into multiple embeds once a description exceeds `desc_budget`
+/// (clamped to Discord's limits, see [`limits::desc_budget`])
+pub fn format_new_planets(planets: &[NewPlanet], lang: &str, desc_budget: usize) -> Vec<CreateEmbed> {
     if planets.is_empty() {
         return vec![
             CreateEmbed::new()
@@ -185,16 +379,19 @@ pub fn format_new_planets(planets: &[NewPlanet], lang: &str) -> Vec<CreateEmbed>
     let total_count = planets.len();
     let unknown = tr!(lang, "bot.spy.unknown");
     let new_planets_title = tr!(lang, "bot.planets.newPlanets");
+    let desc_budget = limits::desc_budget(desc_budget);
 
     for planet in planets {
         let coords = format!("{}:{}:{}", planet.galaxy, planet.system, planet.planet);
         let player = planet.player_name.as_deref().unwrap_or(&unknown);
         let alliance = planet.alliance_tag.as_deref().map(|t| format!(" [{}]", t)).unwrap_or_default();
 
-        let line = format!("**{}** - {}{}\n", coords, player, alliance);
+        let marker = if planet.planet_type.as_deref() == Some("MOON") { "🌙 " } else { "" };
+
+        let line = format!("{}**{}** - {}{}\n", marker, coords, player, alliance);
 
         // Check if adding this line would exceed the limit
-        if current_desc.len() + line.len() > MAX_EMBED_DESC_LEN && !current_desc.is_empty() {
+        if current_desc.len() + line.len() > desc_budget && !current_desc.is_empty() {
             // Save current embed and start new one
             embeds.push(
                 CreateEmbed::new()
@@ -222,10 +419,79 @@ pub fn format_new_planets(planets: &[NewPlanet], lang: &str) -> Vec<CreateEmbed>
                 .colour(Colour::from_rgb(52, 152, 219))
                 .description(current_desc)
                 .footer(serenity::all::CreateEmbedFooter::new(
-                    tr!(lang, "bot.planets.total", "count" => &total_count.to_string())
+                    tr_plural!(lang, "bot.planets.total", total_count)
                 ))
         );
     }
 
     embeds
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_relative() {
+        let now = parse_db_timestamp("2026-03-10 12:00:00").unwrap();
+        let ago = |ts: &str| format_relative(ts, now, "en");
+        assert_eq!(ago("2026-03-10 12:00:00").as_deref(), Some("just now"));
+        assert_eq!(ago("2026-03-10 11:55:00").as_deref(), Some("5m ago"));
+        assert_eq!(ago("2026-03-10 09:00:00").as_deref(), Some("3h ago"));
+        assert_eq!(ago("2026-03-07 12:00:00").as_deref(), Some("3d ago"));
+        // Clock skew doesn't produce negative ages
+        assert_eq!(ago("2026-03-10 12:05:00").as_deref(), Some("just now"));
+        assert_eq!(ago("not a date"), None);
+    }
+
+    #[test]
+    fn test_estimate_loot() {
+        let resources = |pairs: &[(&str, i64)]| -> HashMap<String, i64> {
+            pairs.iter().map(|(id, n)| (id.to_string(), *n)).collect()
+        };
+
+        let full = resources(&[("901", 100_000), ("902", 50_000), ("903", 10_000), ("904", 5_000)]);
+        assert_eq!(estimate_loot(&full, 50, 25_000), Some((80_000, 4)));
+        assert_eq!(estimate_loot(&full, 100, 25_000), Some((160_000, 7)));
+        assert_eq!(estimate_loot(&resources(&[("901", 0)]), 50, 25_000), Some((0, 0)));
+        // Energy alone says nothing about the loot
+        assert_eq!(estimate_loot(&resources(&[("904", 5_000)]), 50, 25_000), None);
+    }
+
+    #[test]
+    fn test_inactive_players_show_alliance_tag() {
+        let player = |name: &str, tag: Option<&str>| InactivePlayer {
+            name: Some(name.to_string()),
+            score_total: Some(1000),
+            score_fleet: Some(0),
+            score_buildings: Some(1000),
+            inactive_since: Some("2026-01-01".to_string()),
+            alliance_tag: tag.map(str::to_string),
+        };
+        let embed = format_inactive_players(&[player("Farm", Some("ALLY")), player("Solo", None)], "en");
+        let desc = serde_json::to_value(&embed).unwrap()["description"].as_str().unwrap().to_string();
+        assert!(desc.contains("**1. Farm [ALLY]**"));
+        assert!(desc.contains("**2. Solo**"));
+    }
+
+    #[test]
+    fn test_alliance_ranking() {
+        let alliance = |name: &str, tag: &str, total_score: i64, member_count: i64| AllianceRankingRow {
+            id: 1,
+            name: name.to_string(),
+            tag: tag.to_string(),
+            total_score,
+            member_count,
+        };
+        let description = |embed: CreateEmbed| {
+            serde_json::to_value(&embed).unwrap()["description"].as_str().unwrap().to_string()
+        };
+
+        let desc = description(format_alliance_ranking(&[alliance("Big", "BIG", 150, 3), alliance("Solo", "S", 100, 1)], "en"));
+        assert!(desc.contains("**1. Big [BIG]**"), "{desc}");
+        assert!(desc.contains("3 members"), "{desc}");
+        assert!(desc.contains("**2. Solo [S]**") && desc.contains("1 member)"), "{desc}");
+
+        assert_eq!(description(format_alliance_ranking(&[], "en")), tr!("en", "bot.ranking.noAlliances"));
+    }
+}
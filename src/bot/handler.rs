@@ -4,7 +4,7 @@ use serenity::client::{Context, EventHandler};
 use serenity::model::gateway::Ready;
 use tracing::info;
 
-use super::commands::{clear_global_commands, register_commands, route_command};
+use super::commands::{clear_global_commands, register_commands, route_command, route_component};
 
 pub struct Handler;
 
@@ -23,8 +23,10 @@ impl EventHandler for Handler {
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::Command(command) = interaction {
-            route_command(&ctx, &command).await;
+        match interaction {
+            Interaction::Command(command) => route_command(&ctx, &command).await,
+            Interaction::Component(component) => route_component(&ctx, &component).await,
+            _ => {}
         }
     }
 }
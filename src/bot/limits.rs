@@ -0,0 +1,161 @@
+//! Discord API limits
+//!
+//! Central place for the hard limits Discord enforces on messages and embeds,
+//! plus the (tunable) budgets we chunk content against.
+
+use serenity::all::CreateEmbed;
+
+/// Max characters in a message's `content`
+pub const MESSAGE_CONTENT_MAX: usize = 2000;
+
+/// Max embeds per message
+pub const EMBEDS_PER_MESSAGE: usize = 10;
+
+/// Max characters in an embed description
+pub const EMBED_DESCRIPTION_MAX: usize = 4096;
+
+/// Max fields per embed
+pub const EMBED_FIELDS_MAX: usize = 25;
+
+/// Max characters across title, description, field names and values, footer text and
+/// author name of all embeds in one message combined
+pub const EMBED_TOTAL_MAX: usize = 6000;
+
+/// Room kept free for title and footer when chunking descriptions
+const EMBED_CHROME_RESERVE: usize = 512;
+
+/// Default description budget for chunked embeds (leaves some buffer)
+pub const DEFAULT_DESC_BUDGET: usize = 4000;
+
+/// Clamp a requested description budget so the embed stays within Discord's limits
+pub fn desc_budget(requested: usize) -> usize {
+    requested
+        .min(EMBED_DESCRIPTION_MAX)
+        .min(EMBED_TOTAL_MAX - EMBED_CHROME_RESERVE)
+}
+
+/// Characters of an embed that count towards `EMBED_TOTAL_MAX`
+pub fn embed_chars(embed: &CreateEmbed) -> usize {
+    let json = serde_json::to_value(embed).unwrap_or_default();
+    let text = |v: &serde_json::Value| v.as_str().map_or(0, |s| s.chars().count());
+    let fields: usize = json["fields"]
+        .as_array()
+        .map_or(0, |fields| fields.iter().map(|f| text(&f["name"]) + text(&f["value"])).sum());
+    text(&json["title"]) + text(&json["description"]) + fields + text(&json["footer"]["text"]) + text(&json["author"]["name"])
+}
+
+/// Pack embeds into messages of at most `EMBEDS_PER_MESSAGE` embeds and `EMBED_TOTAL_MAX`
+/// characters
+pub fn pack_embeds(embeds: Vec<CreateEmbed>) -> Vec<Vec<CreateEmbed>> {
+    pack_embed_groups(embeds.into_iter().map(|e| vec![e]).collect())
+}
+
+/// Pack groups of embeds (e.g. one spy report each) into messages of at most
+/// `EMBEDS_PER_MESSAGE` embeds and `EMBED_TOTAL_MAX` characters without splitting a
+/// group across messages. Groups over either limit get a message of their own.
+pub fn pack_embed_groups(groups: Vec<Vec<CreateEmbed>>) -> Vec<Vec<CreateEmbed>> {
+    pack_groups(groups, embed_chars)
+}
+
+fn pack_groups<T>(groups: Vec<Vec<T>>, chars: impl Fn(&T) -> usize) -> Vec<Vec<T>> {
+    let mut messages: Vec<(Vec<T>, usize)> = Vec::new();
+    for group in groups {
+        let group_chars: usize = group.iter().map(&chars).sum();
+        match messages.last_mut() {
+            Some((last, last_chars))
+                if last.len() + group.len() <= EMBEDS_PER_MESSAGE
+                    && *last_chars + group_chars <= EMBED_TOTAL_MAX =>
+            {
+                last.extend(group);
+                *last_chars += group_chars;
+            }
+            _ => messages.push((group, group_chars)),
+        }
+    }
+    messages.into_iter().map(|(message, _)| message).collect()
+}
+
+/// Pack table rows into code-block messages of at most `MESSAGE_CONTENT_MAX` characters.
+/// `title` starts the first message, `header` is repeated at the top of every block.
+/// Rows that don't fit into an empty block on their own are cut.
+pub fn pack_code_blocks(title: &str, header: &str, rows: &[String]) -> Vec<String> {
+    const FENCE: &str = "```";
+    let open = |first: bool| {
+        let title = if first { format!("{}\n", title) } else { String::new() };
+        format!("{}{}\n{}\n", title, FENCE, header)
+    };
+
+    let mut messages = Vec::new();
+    let mut current = open(true);
+    let mut rows_in_current = 0;
+    for row in rows {
+        let line = format!("{}\n", row);
+        if rows_in_current > 0
+            && current.chars().count() + line.chars().count() + FENCE.len() > MESSAGE_CONTENT_MAX
+        {
+            current.push_str(FENCE);
+            messages.push(std::mem::replace(&mut current, open(false)));
+            rows_in_current = 0;
+        }
+        let room = MESSAGE_CONTENT_MAX.saturating_sub(current.chars().count() + FENCE.len() + 1);
+        current.extend(row.chars().take(room));
+        current.push('\n');
+        rows_in_current += 1;
+    }
+    current.push_str(FENCE);
+    messages.push(current);
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_embed_groups() {
+        // Spy reports are 5 embeds each: two per message
+        let groups: Vec<Vec<u8>> = (0..5).map(|i| vec![i; 5]).collect();
+        let messages = pack_groups(groups, |_| 1);
+        assert_eq!(messages.iter().map(Vec::len).collect::<Vec<_>>(), [10, 10, 5]);
+        assert_eq!(messages[1], [2, 2, 2, 2, 2, 3, 3, 3, 3, 3]);
+    }
+
+    #[test]
+    fn test_pack_embeds_by_total_characters() {
+        let embed = CreateEmbed::new()
+            .title("Report")
+            .description("x".repeat(2000))
+            .field("Metal", "1.000", true)
+            .footer(serenity::all::CreateEmbedFooter::new("1:2:3"));
+        assert_eq!(embed_chars(&embed), 6 + 2000 + 5 + 5 + 5);
+
+        // Three of these fit under the embed count limit but not under the character limit
+        let messages = pack_embeds(vec![embed; 5]);
+        assert_eq!(messages.iter().map(Vec::len).collect::<Vec<_>>(), [2, 2, 1]);
+        for message in &messages {
+            assert!(message.iter().map(embed_chars).sum::<usize>() <= EMBED_TOTAL_MAX);
+        }
+
+        let small = vec![CreateEmbed::new().description("x".repeat(1000)); 3];
+        let big = vec![CreateEmbed::new().description("y".repeat(4000))];
+        let messages = pack_embed_groups(vec![small, big]);
+        assert_eq!(messages.iter().map(Vec::len).collect::<Vec<_>>(), [3, 1]);
+    }
+
+    #[test]
+    fn test_pack_code_blocks() {
+        let rows: Vec<String> = (0..300).map(|i| format!("{i:<4} {}", "x".repeat(40))).collect();
+        let messages = pack_code_blocks("**Title**", "ID   Name", &rows);
+        assert!(messages.len() > 1);
+        assert!(messages.iter().all(|m| m.chars().count() <= MESSAGE_CONTENT_MAX));
+        assert!(messages.iter().all(|m| m.ends_with("```") && m.contains("ID   Name")));
+        assert!(messages[0].starts_with("**Title**") && !messages[1].contains("**Title**"));
+        let packed: usize = messages.iter().map(|m| m.lines().count() - 3).sum::<usize>() - 1;
+        assert_eq!(packed, rows.len());
+
+        // A single oversized row is cut instead of overflowing
+        let messages = pack_code_blocks("", "", &["y".repeat(5000)]);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].chars().count() <= MESSAGE_CONTENT_MAX);
+    }
+}
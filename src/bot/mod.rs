@@ -2,9 +2,11 @@
 //!
 //! Integrated Discord bot that runs as a tokio task within the main hg_hub process.
 
+pub mod alerts;
 pub mod commands;
 pub mod format;
 pub mod handler;
+pub mod limits;
 
 use serenity::prelude::GatewayIntents;
 use serenity::Client;
@@ -82,6 +84,14 @@ pub async fn run_bot() {
 
     match client {
         Ok(mut client) => {
+            if CONFIG.bot_spy_alert_threshold > 0 {
+                info!(
+                    threshold = CONFIG.bot_spy_alert_threshold,
+                    window_minutes = CONFIG.bot_spy_alert_window_minutes,
+                    "Hostile spying alerts enabled"
+                );
+                tokio::spawn(alerts::run_spy_alert_job(client.http.clone()));
+            }
             if let Err(e) = client.start().await {
                 error!("Discord bot error: {:?}", e);
             }
@@ -15,3 +15,23 @@ pub async fn connect(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
 
     Ok(pool)
 }
+
+/// Fresh in-memory database with all migrations applied (for tests).
+/// Uses a single connection, as every SQLite memory connection is its own database.
+#[cfg(test)]
+pub async fn connect_memory() -> SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+
+    sqlx::migrate!()
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    pool
+}
@@ -0,0 +1,123 @@
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Serializer};
+use sqlx::error::BoxDynError;
+use sqlx::sqlite::{Sqlite, SqliteTypeInfo, SqliteValueRef};
+use sqlx::{Decode, Type};
+use std::collections::HashMap;
+use std::ops::Deref;
+use tracing::warn;
+
+/// `{ game_id: amount }` JSON stored in a TEXT column, parsed once when the row is read.
+///
+/// Malformed JSON decodes to an empty map, so one corrupt row doesn't fail a whole list.
+/// The parse error is kept and logged by [`JsonMap::into_map`] together with the caller's
+/// context, since the decoder doesn't know which row it is reading.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonMap {
+    map: HashMap<String, i64>,
+    error: Option<String>,
+}
+
+impl JsonMap {
+    /// Take the map, logging `context` (player, coordinates) if the stored JSON was malformed
+    pub fn into_map(self, context: &str) -> HashMap<String, i64> {
+        if let Some(error) = &self.error {
+            warn!(context, error = %error, "Malformed JSON column, using empty map");
+        }
+        self.map
+    }
+
+    pub fn is_malformed(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+impl Deref for JsonMap {
+    type Target = HashMap<String, i64>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl Type<Sqlite> for JsonMap {
+    fn type_info() -> SqliteTypeInfo {
+        <String as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <String as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for JsonMap {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let text = <&str as Decode<Sqlite>>::decode(value)?;
+        match serde_json::from_str(text) {
+            Ok(map) => Ok(Self { map, error: None }),
+            Err(e) => Ok(Self {
+                map: HashMap::new(),
+                error: Some(format!("{} (len {})", e, text.len())),
+            }),
+        }
+    }
+}
+
+/// Parse JSON read from the database. Corrupt data is logged together with `context`
+/// (player, coordinates, report ID) so it can be tracked down, and yields `None`.
+pub fn parse_stored_json<T: DeserializeOwned>(json: &str, context: &str) -> Option<T> {
+    match serde_json::from_str(json) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!(context, error = %e, "Malformed stored JSON");
+            None
+        }
+    }
+}
+
+impl Serialize for JsonMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.map.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_malformed_map_logged_with_context() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        let malformed = JsonMap { map: HashMap::new(), error: Some("expected value".into()) };
+        let valid = JsonMap { map: HashMap::from([("202".to_string(), 10)]), error: None };
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(malformed.into_map("fleet of player 10").is_empty());
+            assert_eq!(valid.into_map("fleet of player 11").get("202"), Some(&10));
+        });
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("fleet of player 10") && logs.contains("expected value"), "{logs}");
+        assert!(!logs.contains("player 11"), "{logs}");
+    }
+}
@@ -1,3 +1,6 @@
 pub mod models;
 pub mod connection;
-pub mod queries;
\ No newline at end of file
+pub mod json_map;
+pub mod queries;
+pub mod retention;
+pub mod timestamp;
@@ -1,6 +1,7 @@
 use sqlx::FromRow;
 use serde::Serialize;
 use std::collections::HashMap;
+use crate::db::json_map::{parse_stored_json, JsonMap};
 
 // ============================================================================
 // Enums
@@ -14,26 +15,65 @@ pub enum Status {
     Deleted,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Deserialize)]
 #[sqlx(type_name = "TEXT", rename_all = "UPPERCASE")]
+#[serde(rename_all = "UPPERCASE")]
 pub enum PlanetType {
     Planet,
     Moon,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
+impl PlanetType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlanetType::Planet => "PLANET",
+            PlanetType::Moon => "MOON",
+        }
+    }
+}
+
+/// Statistics category (score dimension); unknown values are rejected at parse time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatType {
+    Total,
+    Fleet,
+    Research,
+    Buildings,
+    Defense,
+    Honor,
+}
+
+impl StatType {
+    pub const ALL: [StatType; 6] = [
+        StatType::Total,
+        StatType::Fleet,
+        StatType::Research,
+        StatType::Buildings,
+        StatType::Defense,
+        StatType::Honor,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatType::Total => "total",
+            StatType::Fleet => "fleet",
+            StatType::Research => "research",
+            StatType::Buildings => "buildings",
+            StatType::Defense => "defense",
+            StatType::Honor => "honor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::Type, serde::Serialize, serde::Deserialize)]
 #[sqlx(type_name = "TEXT", rename_all = "lowercase")]
 pub enum UserRole {
     Admin,
+    #[default]
     User,
 }
 
-impl Default for UserRole {
-    fn default() -> Self {
-        UserRole::User
-    }
-}
-
 impl UserRole {
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -54,6 +94,7 @@ pub struct UserListRow {
     pub last_activity_at: Option<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    pub deleted_at: Option<String>,
     pub player_name: Option<String>,
     pub alliance_name: Option<String>,
 }
@@ -84,6 +125,28 @@ pub struct AllianceRow {
     pub updated_at: String,
 }
 
+#[derive(Debug, FromRow)]
+pub struct AuditLogRow {
+    pub id: i64,
+    pub actor_id: i64,
+    pub actor_name: Option<String>,
+    pub action: String,
+    pub target: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct AllianceHistoryRow {
+    pub id: i64,
+    pub player_id: i64,
+    pub old_alliance_id: Option<i64>,
+    pub old_alliance_tag: Option<String>,
+    pub new_alliance_id: Option<i64>,
+    pub new_alliance_tag: Option<String>,
+    pub changed_at: Option<String>,
+}
+
 #[derive(Debug, FromRow)]
 pub struct PlayerRow {
     pub id: i64,
@@ -120,7 +183,7 @@ pub struct HubPlanetRow {
 pub struct HubResearchRow {
     pub id: Option<i64>,
     pub name: Option<String>,
-    pub research: Option<String>,
+    pub research: Option<JsonMap>,
 }
 
 #[derive(Debug, FromRow)]
@@ -128,7 +191,7 @@ pub struct HubFleetRow {
     pub player_id: Option<i64>,
     pub player_name: Option<String>,
     pub score_fleet: Option<i64>,
-    pub fleet: Option<String>,
+    pub fleet: Option<JsonMap>,
 }
 
 #[derive(Debug, FromRow)]
@@ -202,16 +265,36 @@ pub struct PlanetRow {
     pub planet: i64,
     pub r#type: Option<String>,           // 'PLANET' or 'MOON' (DEFAULT but nullable in SQLite)
     pub planet_id: Option<i64>,           // pr0game internal planet ID
-    pub buildings: Option<String>,        // JSON
-    pub fleet: Option<String>,            // JSON
-    pub defense: Option<String>,          // JSON
-    pub resources: Option<String>,        // JSON
+    pub buildings: Option<JsonMap>,
+    pub fleet: Option<JsonMap>,
+    pub defense: Option<JsonMap>,
+    pub resources: Option<JsonMap>,
     pub prod_h: Option<i64>,
     pub status: Option<String>,           // DEFAULT but nullable in SQLite
     pub created_at: Option<String>,       // DEFAULT but nullable in SQLite
     pub updated_at: Option<String>,       // DEFAULT but nullable in SQLite
 }
 
+/// Energy columns of a planet, stored by the empire sync (`None` for planets never synced)
+#[derive(Debug, FromRow)]
+pub struct PlanetEnergyRow {
+    pub coordinates: String,
+    pub r#type: Option<String>,
+    pub name: Option<String>,
+    pub energy_used: Option<i64>,
+    pub energy_max: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
+/// Temperature of a planet, stored by the empire sync (`None` for planets never synced)
+#[derive(Debug, FromRow)]
+pub struct PlanetTemperatureRow {
+    pub coordinates: String,
+    pub name: Option<String>,
+    pub temperature: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
 // ============================================================================
 // Report Tables
 // ============================================================================
@@ -333,6 +416,16 @@ pub struct HostileSpyingOverviewRow {
     pub targets: Option<String>,
 }
 
+#[derive(Debug, FromRow)]
+pub struct HostileSpyingTargetRow {
+    pub target_coordinates: String,
+    pub target_name: Option<String>,
+    pub target_player_name: Option<String>,
+    pub spy_count: i64,
+    pub last_spy_time: Option<String>,
+    pub attackers: Option<String>,
+}
+
 // ============================================================================
 // Tracking Tables
 // ============================================================================
@@ -429,6 +522,7 @@ pub struct NewPlanet {
     pub planet: i64,
     pub player_name: Option<String>,
     pub alliance_tag: Option<String>,
+    pub planet_type: Option<String>,
     pub created_at: Option<String>,
 }
 
@@ -444,6 +538,7 @@ pub struct InactivePlayer {
     pub score_fleet: Option<i64>,
     pub score_buildings: Option<i64>,
     pub inactive_since: Option<String>,
+    pub alliance_tag: Option<String>,
 }
 
 // Spy report row from bot query (different from SpyReportRow)
@@ -453,6 +548,7 @@ pub struct BotSpyReportRow {
     pub galaxy: i64,
     pub system: i64,
     pub planet: i64,
+    pub planet_type: Option<String>,
     pub player_name: Option<String>,
     pub alliance_name: Option<String>,
     pub reporter_name: Option<String>,
@@ -469,6 +565,7 @@ pub struct BotSpyReport {
     pub galaxy: i64,
     pub system: i64,
     pub planet: i64,
+    pub planet_type: Option<String>,
     pub player_name: Option<String>,
     pub alliance_name: Option<String>,
     pub reporter_name: Option<String>,
@@ -480,8 +577,9 @@ pub struct BotSpyReport {
 
 impl From<BotSpyReportRow> for BotSpyReport {
     fn from(row: BotSpyReportRow) -> Self {
+        let context = format!("spy report {}:{}:{}", row.galaxy, row.system, row.planet);
         let parse_json = |s: Option<String>| -> HashMap<String, i64> {
-            s.and_then(|json| serde_json::from_str(&json).ok())
+            s.and_then(|json| parse_stored_json(&json, &context))
                 .unwrap_or_default()
         };
 
@@ -490,6 +588,7 @@ impl From<BotSpyReportRow> for BotSpyReport {
             galaxy: row.galaxy,
             system: row.system,
             planet: row.planet,
+            planet_type: row.planet_type,
             player_name: row.player_name,
             alliance_name: row.alliance_name,
             reporter_name: row.reporter_name,
@@ -501,6 +600,25 @@ impl From<BotSpyReportRow> for BotSpyReport {
     }
 }
 
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AllianceRankingRow {
+    pub id: i64,
+    pub name: String,
+    pub tag: String,
+    pub total_score: i64,
+    pub member_count: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RankingRow {
+    pub id: i64,
+    pub name: String,
+    pub alliance_id: Option<i64>,
+    pub alliance_name: Option<String>,
+    pub alliance_tag: Option<String>,
+    pub score: i64,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct PlayerName {
     pub name: Option<String>,
@@ -523,6 +641,32 @@ pub struct AllianceId {
     pub id: i64,
 }
 
+/// Summed amount of one defense type across the alliance (/defense)
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DefenseTotal {
+    pub defense_id: String,
+    pub amount: i64,
+}
+
+/// Planet or moon ranked by its number of defense units (/defense)
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DefendedPlanet {
+    pub coordinates: String,
+    pub planet_type: Option<String>,
+    pub player_name: String,
+    pub total_units: i64,
+}
+
+/// Alliance found by /alliance, with its non-deleted members summed up
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AllianceSearchRow {
+    pub id: i64,
+    pub name: String,
+    pub tag: String,
+    pub member_count: i64,
+    pub total_score: i64,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct BotUser {
     pub id: i64,
@@ -563,6 +707,39 @@ pub struct ExportAlliance {
     pub timepoint: i64,
 }
 
+/// Combat/honor/destruction stats of one player for /exportstats
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ExportPlayerStats {
+    pub id: i64,
+    pub name: String,
+    pub alliance_id: Option<i64>,
+    pub alliance_tag: Option<String>,
+    pub combats_total: Option<i64>,
+    pub combats_won: Option<i64>,
+    pub combats_draw: Option<i64>,
+    pub combats_lost: Option<i64>,
+    pub units_shot: Option<i64>,
+    pub units_lost: Option<i64>,
+    // Honorpoints
+    pub honorpoints: Option<i64>,
+    pub honorpoints_rank: Option<i64>,
+    // Honorfights
+    pub fights_honorable: Option<i64>,
+    pub fights_dishonorable: Option<i64>,
+    pub fights_neutral: Option<i64>,
+    // Destruction stats (involved in)
+    pub destruction_units_killed: Option<i64>,
+    pub destruction_units_lost: Option<i64>,
+    pub destruction_recycled_metal: Option<i64>,
+    pub destruction_recycled_crystal: Option<i64>,
+    // Destruction stats (actually destroyed)
+    pub real_destruction_units_killed: Option<i64>,
+    pub real_destruction_units_lost: Option<i64>,
+    pub real_destruction_recycled_metal: Option<i64>,
+    pub real_destruction_recycled_crystal: Option<i64>,
+    pub updated_at: Option<String>,
+}
+
 // JSON serialization types for export
 #[derive(Debug, Clone, Serialize)]
 pub struct PlanetSlotData {
@@ -1,12 +1,13 @@
-use crate::db::models::{PlanetRow, PlayerScoreRow};
+use crate::db::models::{AllianceRankingRow, AllianceRow, PlanetRow, PlayerScoreRow};
 use crate::get_pool;
+use sqlx::SqlitePool;
 use tracing::debug;
-use super::sql;
 
 /// Ensure alliance exists (creates if not exists, updates tag if exists)
-pub async fn ensure_exists(id: i64, tag: &str) -> Result<(), sqlx::Error> {
+///
+/// Until [`update_name`] sets the full name, the tag doubles as the name.
+pub async fn ensure_exists(pool: &SqlitePool, id: i64, tag: &str) -> Result<(), sqlx::Error> {
     debug!(id, tag, "DB: alliances::ensure_exists");
-    let pool = get_pool().await;
     sqlx::query(sql!(alliances, ensure_exists))
         .bind(id)
         .bind(tag)  // Use tag as name
@@ -16,6 +17,38 @@ pub async fn ensure_exists(id: i64, tag: &str) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
+/// Set the full alliance name once it is known; a real name is never replaced by the tag
+pub async fn update_name(pool: &SqlitePool, id: i64, name: &str) -> Result<(), sqlx::Error> {
+    debug!(id, name, "DB: alliances::update_name");
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(());
+    }
+    sqlx::query(sql!(alliances, update_name))
+        .bind(name)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn get_by_id(pool: &SqlitePool, alliance_id: i64) -> Result<Option<AllianceRow>, sqlx::Error> {
+    debug!(alliance_id, "DB: alliances::get_by_id");
+    sqlx::query_as::<_, AllianceRow>(sql!(alliances, get_by_id))
+        .bind(alliance_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Number of members and their planets/moons, deleted ones excluded
+pub async fn get_counts(pool: &SqlitePool, alliance_id: i64) -> Result<(i64, i64), sqlx::Error> {
+    debug!(alliance_id, "DB: alliances::get_counts");
+    sqlx::query_as(sql!(alliances, get_counts))
+        .bind(alliance_id)
+        .fetch_one(pool)
+        .await
+}
+
 pub async fn get_planets(alliance_id: i64) -> Result<Vec<PlanetRow>, sqlx::Error> {
     debug!(alliance_id, "DB: alliances::get_planets");
     let pool = get_pool().await;
@@ -33,3 +66,74 @@ pub async fn get_chart(alliance_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::Er
         .fetch_all(pool)
         .await
 }
+
+/// Alliances ranked by the summed total score of their members
+pub async fn get_ranking(limit: i64) -> Result<Vec<AllianceRankingRow>, sqlx::Error> {
+    debug!(limit, "DB: alliances::get_ranking");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, AllianceRankingRow>(sql!(alliances, get_ranking))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_ranking_sums_members() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO alliances (id, name, tag) VALUES (1, 'Small', 'S'), (2, 'Big', 'B');
+             INSERT INTO players (id, name, alliance_id, score_total, is_deleted) VALUES
+             (1, 'A', 1, 100, 0), (2, 'B', 2, 80, 0), (3, 'C', 2, 70, 0), (4, 'D', 1, 500, 1), (5, 'E', 2, NULL, 0)"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let ranking = sqlx::query_as::<_, AllianceRankingRow>(sql!(alliances, get_ranking))
+            .bind(10)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(ranking.len(), 2);
+        assert_eq!((ranking[0].id, ranking[0].total_score, ranking[0].member_count), (2, 150, 3));
+        assert_eq!((ranking[1].id, ranking[1].total_score, ranking[1].member_count), (1, 100, 1));
+    }
+
+    async fn name_and_tag(pool: &SqlitePool, id: i64) -> (String, String) {
+        sqlx::query_as("SELECT name, tag FROM alliances WHERE id = ?")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_full_name_survives_tag_upserts() {
+        let pool = connect_memory().await;
+
+        ensure_exists(&pool, 1, "TA").await.unwrap();
+        assert_eq!(name_and_tag(&pool, 1).await, ("TA".into(), "TA".into()));
+
+        // Placeholder name follows a tag change
+        ensure_exists(&pool, 1, "TB").await.unwrap();
+        assert_eq!(name_and_tag(&pool, 1).await, ("TB".into(), "TB".into()));
+
+        update_name(&pool, 1, "Test Bund").await.unwrap();
+        ensure_exists(&pool, 1, "TB").await.unwrap();
+        ensure_exists(&pool, 1, "TC").await.unwrap();
+        assert_eq!(name_and_tag(&pool, 1).await, ("Test Bund".into(), "TC".into()));
+
+        // The tag doesn't overwrite a real name, another full name does
+        update_name(&pool, 1, "TC").await.unwrap();
+        update_name(&pool, 1, " ").await.unwrap();
+        assert_eq!(name_and_tag(&pool, 1).await.0, "Test Bund");
+        update_name(&pool, 1, "Test Bund Neu").await.unwrap();
+        assert_eq!(name_and_tag(&pool, 1).await.0, "Test Bund Neu");
+    }
+}
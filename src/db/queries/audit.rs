@@ -0,0 +1,56 @@
+use crate::db::models::AuditLogRow;
+use crate::get_pool;
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+/// Record an admin action in the audit log.
+///
+/// Best effort: a failed insert is logged but never fails the admin action itself.
+pub async fn record(pool: &SqlitePool, actor_id: i64, action: &str, target: Option<&str>, detail: Option<&str>) {
+    debug!(actor_id, action, ?target, "DB: audit::record");
+    let result = sqlx::query(sql!(audit, insert))
+        .bind(actor_id)
+        .bind(action)
+        .bind(target)
+        .bind(detail)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = result {
+        warn!(actor_id, action, error = %e, "Failed to write audit log entry");
+    }
+}
+
+pub async fn get_recent(limit: i64) -> Result<Vec<AuditLogRow>, sqlx::Error> {
+    debug!(limit, "DB: audit::get_recent");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, AuditLogRow>(sql!(audit, get_recent))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_recent_entries_newest_first() {
+        let pool = connect_memory().await;
+
+        for action in ["create_user", "update_role"] {
+            record(&pool, 1, action, Some("user:2"), None).await;
+        }
+
+        let rows = sqlx::query_as::<_, AuditLogRow>(sql!(audit, get_recent))
+            .bind(10)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        let actions: Vec<&str> = rows.iter().map(|r| r.action.as_str()).collect();
+        assert_eq!(actions, ["update_role", "create_user"]);
+        assert_eq!(rows[0].target.as_deref(), Some("user:2"));
+    }
+}
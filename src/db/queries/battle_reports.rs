@@ -1,16 +1,16 @@
 use crate::db::models::BattleReportHistoryRow;
 use crate::get_pool;
+use sqlx::SqlitePool;
 use tracing::debug;
-use super::sql;
 
 pub async fn get_history_with_reporter(
+    pool: &SqlitePool,
     galaxy: i64,
     system: i64,
     planet: i64,
     limit: i64,
 ) -> Result<Vec<BattleReportHistoryRow>, sqlx::Error> {
     debug!(galaxy, system, planet, limit, "DB: battle_reports::get_history_with_reporter");
-    let pool = get_pool().await;
     sqlx::query_as::<_, BattleReportHistoryRow>(sql!(battle_reports, get_history_with_reporter))
         .bind(galaxy)
         .bind(system)
@@ -20,6 +20,7 @@ pub async fn get_history_with_reporter(
         .await
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert(
     external_id: i64,
     galaxy: i64,
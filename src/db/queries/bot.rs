@@ -1,18 +1,18 @@
 //! Bot-specific database queries for Discord bot commands
 
-use std::collections::HashMap;
 use serde_json::{json, Map, Value};
 use sqlx::query_as;
 use tracing::info;
 use uuid::Uuid;
 
 use crate::get_pool;
+use crate::db::queries::spy_reports;
 use crate::db::models::{
-    AllianceExportData, AllianceId, BotSpyReport, BotSpyReportRow, BotUser,
-    CountResult, ExportAlliance, ExportPlanet, ExportPlayer, InactivePlayer,
-    NewPlanet, PlayerExportData, PlayerId, PlayerInfo, PlayerName, PlanetSlotData,
+    AllianceExportData, AllianceId, AllianceSearchRow, BotSpyReport, BotSpyReportRow, BotUser,
+    CountResult, DefendedPlanet, DefenseTotal, ExportAlliance, ExportPlanet, ExportPlayer, ExportPlayerStats, InactivePlayer,
+    NewPlanet, PlayerExportData, PlayerId, PlayerInfo, PlayerName, PlanetSlotData, RankingRow,
+    StatType,
 };
-use super::sql;
 
 // ============================================================================
 // Player Queries
@@ -67,6 +67,24 @@ pub async fn get_ally_id_by_name(name: &str) -> Result<AllianceId, sqlx::Error>
     Ok(ally)
 }
 
+/// Alliances whose name or tag starts with `prefix`, exact matches first
+pub async fn search_alliances(prefix: &str, limit: i64) -> Result<Vec<AllianceSearchRow>, sqlx::Error> {
+    let pool = get_pool().await;
+    let alliances = query_as::<_, AllianceSearchRow>(sql!(bot, search_alliances))
+        .bind(escape_like(prefix))
+        .bind(prefix)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    info!(prefix, found = alliances.len(), "alliances searched");
+    Ok(alliances)
+}
+
+/// Escape LIKE wildcards so user input only matches literally (`ESCAPE '\'` in the SQL)
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
 // ============================================================================
 // User Queries
 // ============================================================================
@@ -136,30 +154,53 @@ pub async fn get_user_by_id(id: i64) -> Result<BotUser, sqlx::Error> {
 // Planet Queries
 // ============================================================================
 
-pub async fn get_new_planets() -> Result<Vec<NewPlanet>, sqlx::Error> {
+/// New planets (and moons if requested); with `seen_by` set, only those this
+/// Discord user hasn't seen yet
+pub async fn get_new_planets(seen_by: Option<i64>, include_moons: bool) -> Result<Vec<NewPlanet>, sqlx::Error> {
     let pool = get_pool().await;
     let planets = query_as::<_, NewPlanet>(sql!(bot, get_new_planets))
+        .bind(seen_by)
+        .bind(include_moons)
         .fetch_all(pool)
         .await?;
-    info!(count = planets.len(), "new planets found");
+    info!(count = planets.len(), ?seen_by, "new planets found");
     Ok(planets)
 }
 
-pub async fn mark_planets_seen_by_ids(ids: &[i64]) -> Result<u64, sqlx::Error> {
+/// Mark planets seen for one Discord user, or globally when `seen_by` is None
+pub async fn mark_planets_seen_by_ids(ids: &[i64], seen_by: Option<i64>) -> Result<u64, sqlx::Error> {
     let pool = get_pool().await;
     let json_ids = serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string());
-    let result = sqlx::query(sql!(bot, mark_planets_seen_by_ids))
-        .bind(&json_ids)
-        .execute(pool)
-        .await?;
+    let result = match seen_by {
+        Some(user_id) => {
+            sqlx::query(sql!(bot, mark_planets_seen_by_ids_for_user))
+                .bind(user_id)
+                .bind(&json_ids)
+                .execute(pool)
+                .await?
+        }
+        None => {
+            sqlx::query(sql!(bot, mark_planets_seen_by_ids))
+                .bind(&json_ids)
+                .execute(pool)
+                .await?
+        }
+    };
     Ok(result.rows_affected())
 }
 
-pub async fn mark_all_planets_seen() -> Result<u64, sqlx::Error> {
+/// Mark all new planets seen for one Discord user, or globally when `seen_by` is None
+pub async fn mark_all_planets_seen(seen_by: Option<i64>) -> Result<u64, sqlx::Error> {
     let pool = get_pool().await;
-    let result = sqlx::query(sql!(bot, mark_all_planets_seen))
-        .execute(pool)
-        .await?;
+    let result = match seen_by {
+        Some(user_id) => {
+            sqlx::query(sql!(bot, mark_all_planets_seen_for_user))
+                .bind(user_id)
+                .execute(pool)
+                .await?
+        }
+        None => sqlx::query(sql!(bot, mark_all_planets_seen)).execute(pool).await?,
+    };
     Ok(result.rows_affected())
 }
 
@@ -175,9 +216,20 @@ pub async fn count_new_planets() -> Result<i64, sqlx::Error> {
 // Spy Queries
 // ============================================================================
 
-pub async fn get_spy_report(galaxy: i64, system: i64, planet: i64) -> Result<BotSpyReport, sqlx::Error> {
+pub async fn get_spy_report(galaxy: i64, system: i64, planet: i64, planet_type: &str) -> Result<BotSpyReport, sqlx::Error> {
+    let report = spy_reports::get_latest(galaxy, system, planet, planet_type)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let player = &report.player_name;
+    info!(?player, "report found");
+    Ok(report)
+}
+
+/// Newest planet report in the same system, closest planet position first
+pub async fn get_nearest_spy_report(galaxy: i64, system: i64, planet: i64) -> Result<BotSpyReport, sqlx::Error> {
     let pool = get_pool().await;
-    let row = query_as::<_, BotSpyReportRow>(sql!(bot, get_spy_report))
+    let row = query_as::<_, BotSpyReportRow>(sql!(bot, get_nearest_spy_report))
         .bind(galaxy)
         .bind(system)
         .bind(planet)
@@ -185,14 +237,51 @@ pub async fn get_spy_report(galaxy: i64, system: i64, planet: i64) -> Result<Bot
         .await?;
 
     let report: BotSpyReport = row.into();
-    let player = &report.player_name;
-    info!(?player, "report found");
+    info!(planet = report.planet, "nearest report found");
     Ok(report)
 }
 
-pub async fn get_top_inactive() -> Result<Vec<InactivePlayer>, sqlx::Error> {
+/// Universe leaderboard for /ranking (same query as GET /api/ranking)
+pub async fn get_ranking(stat: StatType, limit: i64) -> Result<Vec<RankingRow>, sqlx::Error> {
+    let pool = get_pool().await;
+    let players = query_as::<_, RankingRow>(sql!(players, get_ranking))
+        .bind(stat.as_str())
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    info!(stat = stat.as_str(), players = players.len(), "ranking fetched");
+    Ok(players)
+}
+
+/// Defense units of all members of `alliance_id`, summed per defense type
+pub async fn get_alliance_defense_totals(alliance_id: i64) -> Result<Vec<DefenseTotal>, sqlx::Error> {
+    let pool = get_pool().await;
+    let totals = query_as::<_, DefenseTotal>(sql!(bot, get_alliance_defense_totals))
+        .bind(alliance_id)
+        .fetch_all(pool)
+        .await?;
+    info!(alliance_id, defense_types = totals.len(), "alliance defense fetched");
+    Ok(totals)
+}
+
+/// The `limit` planets of `alliance_id` with the most defense units
+pub async fn get_top_defended_planets(alliance_id: i64, limit: i64) -> Result<Vec<DefendedPlanet>, sqlx::Error> {
+    let pool = get_pool().await;
+    let planets = query_as::<_, DefendedPlanet>(sql!(bot, get_top_defended_planets))
+        .bind(alliance_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    info!(alliance_id, planets = planets.len(), "top defended planets fetched");
+    Ok(planets)
+}
+
+/// Top inactive players, leaving out members of `exclude_alliance_ids`
+pub async fn get_top_inactive(exclude_alliance_ids: &[i64]) -> Result<Vec<InactivePlayer>, sqlx::Error> {
     let pool = get_pool().await;
+    let exclude = serde_json::to_string(exclude_alliance_ids).unwrap_or_else(|_| "[]".to_string());
     let farms = query_as::<_, InactivePlayer>(sql!(bot, get_top_inactive))
+        .bind(exclude)
         .fetch_all(pool)
         .await?;
     info!(farms = farms.len(), "farms found");
@@ -203,9 +292,10 @@ pub async fn get_top_inactive() -> Result<Vec<InactivePlayer>, sqlx::Error> {
 // Export Queries
 // ============================================================================
 
-pub async fn get_all_planets_for_export() -> Result<Vec<ExportPlanet>, sqlx::Error> {
+pub async fn get_all_planets_for_export(since: Option<i64>) -> Result<Vec<ExportPlanet>, sqlx::Error> {
     let pool = get_pool().await;
     let planets = query_as::<_, ExportPlanet>(sql!(bot, get_all_planets_for_export))
+        .bind(since)
         .fetch_all(pool)
         .await?;
 
@@ -213,9 +303,10 @@ pub async fn get_all_planets_for_export() -> Result<Vec<ExportPlanet>, sqlx::Err
     Ok(planets)
 }
 
-pub async fn get_all_players_for_export() -> Result<Vec<ExportPlayer>, sqlx::Error> {
+pub async fn get_all_players_for_export(since: Option<i64>) -> Result<Vec<ExportPlayer>, sqlx::Error> {
     let pool = get_pool().await;
     let players = query_as::<_, ExportPlayer>(sql!(bot, get_all_players_for_export))
+        .bind(since)
         .fetch_all(pool)
         .await?;
 
@@ -223,9 +314,10 @@ pub async fn get_all_players_for_export() -> Result<Vec<ExportPlayer>, sqlx::Err
     Ok(players)
 }
 
-pub async fn get_all_alliances_for_export() -> Result<Vec<ExportAlliance>, sqlx::Error> {
+pub async fn get_all_alliances_for_export(since: Option<i64>) -> Result<Vec<ExportAlliance>, sqlx::Error> {
     let pool = get_pool().await;
     let alliances = query_as::<_, ExportAlliance>(sql!(bot, get_all_alliances_for_export))
+        .bind(since)
         .fetch_all(pool)
         .await?;
 
@@ -233,108 +325,329 @@ pub async fn get_all_alliances_for_export() -> Result<Vec<ExportAlliance>, sqlx:
     Ok(alliances)
 }
 
+/// Version of the export format `[coords, players, alliances]`.
+/// Bump this whenever the shape of the export changes.
+pub const EXPORT_VERSION: u32 = 1;
+
 /// Builds the complete export JSON in the required format
-pub async fn build_export_json() -> Result<String, sqlx::Error> {
+pub async fn build_export_json() -> Result<Vec<u8>, sqlx::Error> {
+    build_export(None).await
+}
+
+/// Builds a delta export: only systems, players and alliances whose timepoint
+/// is newer than `timepoint_ms`. Systems are always exported with all slots so
+/// the client can replace them when merging.
+pub async fn build_export_json_since(timepoint_ms: i64) -> Result<Vec<u8>, sqlx::Error> {
+    build_export(Some(timepoint_ms)).await
+}
+
+async fn build_export(since: Option<i64>) -> Result<Vec<u8>, sqlx::Error> {
     // Run all queries in parallel for better performance
     let (planets_result, players_result, alliances_result) = tokio::join!(
-        get_all_planets_for_export(),
-        get_all_players_for_export(),
-        get_all_alliances_for_export()
+        get_all_planets_for_export(since),
+        get_all_players_for_export(since),
+        get_all_alliances_for_export(since)
     );
 
     let planets = planets_result?;
     let players = players_result?;
     let alliances = alliances_result?;
 
-    // Build coordinates map: "galaxy:system" -> { "1": data, ..., "15": data, "timepoint": ts }
-    let mut coords_map: HashMap<String, Map<String, Value>> = HashMap::new();
+    let mut out = Vec::with_capacity(planets.len() * 128);
+    write_export(&mut out, &planets, &players, &alliances).map_err(|e| {
+        sqlx::Error::Protocol(format!("JSON serialization error: {}", e))
+    })?;
+
+    info!(
+        size_bytes = out.len(),
+        ?since,
+        "export JSON built"
+    );
+
+    Ok(out)
+}
 
-    for planet in &planets {
-        let key = format!("{}:{}", planet.galaxy, planet.system);
-        let slot_key = planet.planet.to_string();
+/// Writes the export array `[coords, players, alliances]` into `out` piece by
+/// piece instead of building the whole document as one `Value` first
+fn write_export(
+    out: &mut Vec<u8>,
+    planets: &[ExportPlanet],
+    players: &[ExportPlayer],
+    alliances: &[ExportAlliance],
+) -> serde_json::Result<()> {
+    out.push(b'[');
+
+    // Coordinates map: "galaxy:system" -> { "1": data, ..., "15": data, "timepoint": ts }
+    // Planets are ordered by galaxy/system, so each system is one contiguous run
+    let systems = planets.chunk_by(|a, b| a.galaxy == b.galaxy && a.system == b.system);
+    write_object(out, systems.map(|system| {
+        let first = &system[0];
+        let mut entry = Map::new();
+        // Initialize all 15 slots as null
+        for i in 1..=15 {
+            entry.insert(i.to_string(), Value::Null);
+        }
 
-        let entry = coords_map.entry(key).or_insert_with(|| {
-            let mut map = Map::new();
-            // Initialize all 15 slots as null
-            for i in 1..=15 {
-                map.insert(i.to_string(), Value::Null);
-            }
-            map.insert("timepoint".to_string(), json!(planet.timepoint));
-            map
-        });
-
-        // Update timepoint to the latest
-        if let Some(Value::Number(current_tp)) = entry.get("timepoint") {
-            if let Some(current) = current_tp.as_i64() {
-                if planet.timepoint > current {
-                    entry.insert("timepoint".to_string(), json!(planet.timepoint));
-                }
+        for planet in system {
+            // Only add planet data if there's a player
+            if let (Some(player_id), Some(player_name)) = (&planet.player_id, &planet.player_name) {
+                let slot_data = PlanetSlotData {
+                    planetname: String::new(),
+                    hasmoon: planet.has_moon != 0,
+                    playerid: *player_id,
+                    name: player_name.clone(),
+                    allianceid: planet.alliance_id,
+                    alliancename: planet.alliance_name.clone(),
+                    special: String::new(),
+                };
+                entry.insert(planet.planet.to_string(), json!(slot_data));
             }
         }
 
-        // Only add planet data if there's a player
-        if let (Some(player_id), Some(player_name)) = (&planet.player_id, &planet.player_name) {
-            let slot_data = PlanetSlotData {
-                planetname: String::new(),
-                hasmoon: planet.has_moon != 0,
-                playerid: *player_id,
-                name: player_name.clone(),
-                allianceid: planet.alliance_id,
-                alliancename: planet.alliance_name.clone(),
-                special: String::new(),
-            };
-            entry.insert(slot_key, json!(slot_data));
-        }
-    }
+        // Latest timepoint of the system
+        let timepoint = system.iter().map(|p| p.timepoint).max().unwrap_or(0);
+        entry.insert("timepoint".to_string(), json!(timepoint));
+
+        (format!("{}:{}", first.galaxy, first.system), entry)
+    }))?;
+    out.push(b',');
 
-    // Build players map: "playerid" -> { "name": ..., "timepoint": ... }
-    let mut players_map: Map<String, Value> = Map::new();
-    for player in &players {
-        let player_data = PlayerExportData {
+    // Players map: "playerid" -> { "name": ..., "timepoint": ... }
+    write_object(out, players.iter().map(|player| {
+        (player.id.to_string(), PlayerExportData {
             name: player.name.clone(),
             timepoint: player.timepoint,
-        };
-        players_map.insert(player.id.to_string(), json!(player_data));
-    }
+        })
+    }))?;
+    out.push(b',');
 
-    // Build alliances map: "allianceid" -> { "name": ..., "timepoint": ... }
-    let mut alliances_map: Map<String, Value> = Map::new();
-    for alliance in &alliances {
-        let alliance_data = AllianceExportData {
-            name: alliance.name.clone(),
-            timepoint: alliance.timepoint,
-        };
-        alliances_map.insert(alliance.id.to_string(), json!(alliance_data));
+    // Alliances map: "allianceid" -> { "name": ..., "timepoint": ... },
+    // plus an entry for "no alliance" (-1)
+    let max_timepoint = alliances.iter().map(|a| a.timepoint).max().unwrap_or(0);
+    let no_alliance = AllianceExportData {
+        name: "-".to_string(),
+        timepoint: max_timepoint,
+    };
+    write_object(
+        out,
+        alliances
+            .iter()
+            .map(|alliance| {
+                (alliance.id.to_string(), AllianceExportData {
+                    name: alliance.name.clone(),
+                    timepoint: alliance.timepoint,
+                })
+            })
+            .chain(std::iter::once(("-1".to_string(), no_alliance))),
+    )?;
+
+    out.push(b']');
+    Ok(())
+}
+
+/// Writes `{"key": value, ...}` into `out` one entry at a time
+fn write_object<V: serde::Serialize>(
+    out: &mut Vec<u8>,
+    entries: impl Iterator<Item = (String, V)>,
+) -> serde_json::Result<()> {
+    out.push(b'{');
+    for (i, (key, value)) in entries.enumerate() {
+        if i > 0 {
+            out.push(b',');
+        }
+        serde_json::to_writer(&mut *out, &key)?;
+        out.push(b':');
+        serde_json::to_writer(&mut *out, &value)?;
     }
+    out.push(b'}');
+    Ok(())
+}
 
-    // Add entry for "no alliance" (-1)
-    let max_timepoint = alliances.iter().map(|a| a.timepoint).max().unwrap_or(0);
-    alliances_map.insert(
-        "-1".to_string(),
-        json!(AllianceExportData {
-            name: "-".to_string(),
-            timepoint: max_timepoint,
-        }),
-    );
+pub async fn get_player_stats_for_export() -> Result<Vec<ExportPlayerStats>, sqlx::Error> {
+    let pool = get_pool().await;
+    let stats = query_as::<_, ExportPlayerStats>(sql!(bot, get_player_stats_for_export))
+        .fetch_all(pool)
+        .await?;
 
-    // Convert coords_map to Value
-    let coords_value: Value = coords_map
-        .into_iter()
-        .map(|(k, v)| (k, Value::Object(v)))
-        .collect::<Map<String, Value>>()
-        .into();
+    info!(count = stats.len(), "export player stats fetched");
+    Ok(stats)
+}
 
-    // Build final array: [coords, players, alliances]
-    let result = json!([coords_value, players_map, alliances_map]);
+/// Builds the combat/honor/destruction stats export: an array with one object per player
+pub async fn build_stats_export_json() -> Result<Vec<u8>, sqlx::Error> {
+    let stats = get_player_stats_for_export().await?;
 
-    let json_string = serde_json::to_string(&result).map_err(|e| {
+    let json_data = serde_json::to_vec(&stats).map_err(|e| {
         sqlx::Error::Protocol(format!("JSON serialization error: {}", e))
     })?;
 
     info!(
-        size_bytes = json_string.len(),
-        "export JSON built"
+        size_bytes = json_data.len(),
+        "stats export JSON built"
     );
 
-    Ok(json_string)
+    Ok(json_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_export_shape() {
+        let planet = |planet, player_id: Option<i64>, timepoint| ExportPlanet {
+            galaxy: 1,
+            system: 2,
+            planet,
+            player_id,
+            player_name: player_id.map(|_| "Bob".to_string()),
+            alliance_id: -1,
+            alliance_name: "-".to_string(),
+            has_moon: 1,
+            timepoint,
+        };
+        let planets = vec![planet(3, Some(5), 1000), planet(7, None, 2000)];
+        let players = vec![ExportPlayer { id: 5, name: "Bob".to_string(), timepoint: 1000 }];
+        let alliances = vec![ExportAlliance { id: 9, name: "Ally".to_string(), timepoint: 3000 }];
+
+        let mut out = Vec::new();
+        write_export(&mut out, &planets, &players, &alliances).unwrap();
+        let value: Value = serde_json::from_slice(&out).unwrap();
+
+        let system = &value[0]["1:2"];
+        assert_eq!(system["3"]["playerid"], 5);
+        assert_eq!(system["3"]["hasmoon"], true);
+        assert!(system["7"].is_null());
+        assert!(system["15"].is_null());
+        assert_eq!(system["timepoint"], 2000);
+        assert_eq!(value[1]["5"]["name"], "Bob");
+        assert_eq!(value[2]["9"]["name"], "Ally");
+        assert_eq!(value[2]["-1"]["timepoint"], 3000);
+
+        let mut empty = Vec::new();
+        write_export(&mut empty, &[], &[], &[]).unwrap();
+        assert_eq!(String::from_utf8(empty).unwrap(), r#"[{},{},{"-1":{"name":"-","timepoint":0}}]"#);
+    }
+
+    #[tokio::test]
+    async fn test_nearest_spy_report() {
+        let pool = crate::db::connection::connect_memory().await;
+        for (planet, kind) in [(2, "PLANET"), (9, "PLANET"), (5, "MOON")] {
+            sqlx::query("INSERT INTO spy_reports (coordinates, galaxy, system, planet, type) VALUES ('', 1, 2, ?, ?)")
+                .bind(planet)
+                .bind(kind)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let nearest = |planet: i64| {
+            query_as::<_, BotSpyReportRow>(sql!(bot, get_nearest_spy_report))
+                .bind(1)
+                .bind(2)
+                .bind(planet)
+                .fetch_optional(&pool)
+        };
+
+        // The moon at 5 is skipped, 2 is closer to 4 than 9
+        assert_eq!(nearest(4).await.unwrap().unwrap().planet, 2);
+        assert_eq!(nearest(7).await.unwrap().unwrap().planet, 9);
+    }
+
+    #[tokio::test]
+    async fn test_top_inactive_excludes_alliances() {
+        let pool = crate::db::connection::connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO alliances (id, name, tag) VALUES (1, 'Friends', 'FR'), (2, 'Others', 'OT');
+               INSERT INTO players (id, name, alliance_id, score_total, inactive_since) VALUES
+                   (10, 'Ally', 1, 300, '2026-01-01'),
+                   (11, 'Target', 2, 200, '2026-01-01'),
+                   (12, 'Loner', NULL, 100, '2026-01-01')"#,
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for (exclude, expected) in [("[]", vec!["Ally", "Target", "Loner"]), ("[1]", vec!["Target", "Loner"])] {
+            let farms = query_as::<_, InactivePlayer>(sql!(bot, get_top_inactive))
+                .bind(exclude)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            let names: Vec<_> = farms.iter().filter_map(|p| p.name.as_deref()).collect();
+            assert_eq!(names, expected, "{exclude}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_alliances() {
+        let pool = crate::db::connection::connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO alliances (id, name, tag) VALUES
+                   (1, 'Hydra Group', 'HG'), (2, 'Hypernova', 'HYP'), (3, 'Others', 'H_X');
+               INSERT INTO players (id, name, alliance_id, score_total, is_deleted) VALUES
+                   (10, 'A', 1, 300, 0), (11, 'B', 1, 200, 0), (12, 'Gone', 1, 999, 1),
+                   (13, 'C', 2, 1000, 0)"#,
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let search = |prefix: &'static str| {
+            query_as::<_, AllianceSearchRow>(sql!(bot, search_alliances))
+                .bind(escape_like(prefix))
+                .bind(prefix)
+                .bind(10)
+                .fetch_all(&pool)
+        };
+
+        let hy = search("hy").await.unwrap();
+        let tags: Vec<_> = hy.iter().map(|a| a.tag.as_str()).collect();
+        assert_eq!(tags, vec!["HYP", "HG"]);
+
+        // Exact tag match wins over score, deleted members don't count
+        let hg = search("hg").await.unwrap();
+        assert_eq!(hg.len(), 1);
+        assert_eq!((hg[0].member_count, hg[0].total_score), (2, 500));
+
+        // Wildcards in the input are literal
+        let tags: Vec<_> = search("H_").await.unwrap().into_iter().map(|a| a.tag).collect();
+        assert_eq!(tags, vec!["H_X"]);
+        assert!(search("%").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alliance_defense_aggregates() {
+        let pool = crate::db::connection::connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO alliances (id, name, tag) VALUES (1, 'Friends', 'FR'), (2, 'Others', 'OT');
+               INSERT INTO players (id, name, alliance_id) VALUES (10, 'Alice', 1), (11, 'Bob', 1), (12, 'Eve', 2);
+               INSERT INTO planets (player_id, coordinates, galaxy, system, planet, type, defense) VALUES
+                   (10, '1:2:3', 1, 2, 3, 'PLANET', '{"401": 100, "402": 20}'),
+                   (10, '1:2:3', 1, 2, 3, 'MOON', '{"401": 5}'),
+                   (11, '2:3:4', 2, 3, 4, 'PLANET', '{"401": 300, "502": 0}'),
+                   (11, '2:3:5', 2, 3, 5, 'PLANET', NULL),
+                   (12, '3:4:5', 3, 4, 5, 'PLANET', '{"401": 9999}')"#,
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let totals = query_as::<_, DefenseTotal>(sql!(bot, get_alliance_defense_totals))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let totals: Vec<_> = totals.iter().map(|t| (t.defense_id.as_str(), t.amount)).collect();
+        assert_eq!(totals, vec![("401", 405), ("402", 20)]);
+
+        let planets = query_as::<_, DefendedPlanet>(sql!(bot, get_top_defended_planets))
+            .bind(1)
+            .bind(2)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let planets: Vec<_> = planets.iter().map(|p| (p.coordinates.as_str(), p.player_name.as_str(), p.total_units)).collect();
+        assert_eq!(planets, vec![("2:3:4", "Bob", 300), ("1:2:3", "Alice", 120)]);
+    }
 }
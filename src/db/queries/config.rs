@@ -1,5 +1,4 @@
 use crate::get_pool;
-use super::sql;
 use tracing::debug;
 
 #[derive(sqlx::FromRow)]
@@ -16,6 +15,14 @@ pub async fn get_universe_config() -> Result<Vec<ConfigRow>, sqlx::Error> {
         .await
 }
 
+pub async fn get_all() -> Result<Vec<ConfigRow>, sqlx::Error> {
+    debug!("DB: config::get_all");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, ConfigRow>(sql!(config, get_all))
+        .fetch_all(pool)
+        .await
+}
+
 pub async fn set_config(key: &str, value: &str) -> Result<(), sqlx::Error> {
     debug!(key, value, "DB: config::set_config");
     let pool = get_pool().await;
@@ -1,5 +1,4 @@
 use crate::get_pool;
-use super::sql;
 use tracing::debug;
 
 pub async fn upsert(
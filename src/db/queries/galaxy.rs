@@ -1,7 +1,6 @@
 use crate::db::models::PlanetRow;
 use crate::get_pool;
 use tracing::debug;
-use super::sql;
 
 pub async fn get_system(galaxy: i64, system: i64) -> Result<Vec<PlanetRow>, sqlx::Error> {
     debug!(galaxy, system, "DB: get_system");
@@ -12,3 +11,96 @@ pub async fn get_system(galaxy: i64, system: i64) -> Result<Vec<PlanetRow>, sqlx
         .fetch_all(pool)
         .await
 }
+
+/// Clear the scan timestamp of a system marker. Returns false if the system was never scanned.
+pub async fn invalidate(galaxy: i64, system: i64) -> Result<bool, sqlx::Error> {
+    debug!(galaxy, system, "DB: invalidate system");
+    let pool = get_pool().await;
+    let result = sqlx::query(sql!(galaxy, invalidate))
+        .bind(galaxy)
+        .bind(system)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_planet_rows_decode_json_columns() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO alliances (id, name, tag) VALUES (1, 'Ally', 'A');
+               INSERT INTO players (id, name, alliance_id) VALUES (10, 'Bob', 1);
+               INSERT INTO planets (player_id, coordinates, galaxy, system, planet, buildings, fleet)
+               VALUES (10, '1:2:3', 1, 2, 3, '{"1": 20}', 'not json')"#,
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Player 10 and alliance 1 own the same single planet
+        let queries = [
+            (sql!(players, get_player_planets), 10),
+            (sql!(alliances, get_alliance_planets), 1),
+        ];
+        for (query, id) in queries {
+            let rows = sqlx::query_as::<_, PlanetRow>(query)
+                .bind(id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(rows.len(), 1, "{query}");
+        }
+
+        let rows = sqlx::query_as::<_, PlanetRow>(sql!(galaxy, get_system))
+            .bind(1)
+            .bind(2)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let planet = &rows[0];
+        assert_eq!(planet.buildings.as_ref().unwrap().get("1"), Some(&20));
+        // Corrupt JSON becomes an empty map, NULL stays None
+        let fleet = planet.fleet.as_ref().unwrap();
+        assert!(fleet.is_empty() && fleet.is_malformed());
+        assert!(!planet.buildings.as_ref().unwrap().is_malformed());
+        assert_eq!(planet.defense, None);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_clears_system_marker() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO players (id, name) VALUES (0, 'System');
+               INSERT INTO planets (player_id, coordinates, galaxy, system, planet)
+               VALUES (0, '1:2:0', 1, 2, 0), (0, '1:3:0', 1, 3, 0)"#,
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for (system, expected) in [(2, 1), (4, 0)] {
+            let result = sqlx::query(sql!(galaxy, invalidate))
+                .bind(1)
+                .bind(system)
+                .execute(&pool)
+                .await
+                .unwrap();
+            assert_eq!(result.rows_affected(), expected);
+        }
+
+        let scans: Vec<(i64, Option<String>)> = sqlx::query_as(sql!(hub, get_galaxy_status))
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(_, system, last_scan_at): (i64, i64, Option<String>)| (system, last_scan_at))
+            .collect();
+        assert!(scans[0].1.is_none());
+        assert!(scans[1].1.is_some());
+    }
+}
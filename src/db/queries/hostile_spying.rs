@@ -1,6 +1,5 @@
-use crate::db::models::{HostileSpyingRow, HostileSpyingOverviewRow};
+use crate::db::models::{HostileSpyingRow, HostileSpyingOverviewRow, HostileSpyingTargetRow};
 use crate::get_pool;
-use super::sql;
 use tracing::debug;
 
 pub async fn upsert(
@@ -23,23 +22,33 @@ pub async fn upsert(
 
 pub async fn get(
     search: Option<&str>,
+    time_from: Option<&str>,
+    time_to: Option<&str>,
     limit: i64,
     offset: i64,
 ) -> Result<Vec<HostileSpyingRow>, sqlx::Error> {
-    debug!(?search, limit, offset, "DB: hostile_spying::get");
+    debug!(?search, ?time_from, ?time_to, limit, offset, "DB: hostile_spying::get");
     let pool = get_pool().await;
     sqlx::query_as::<_, HostileSpyingRow>(sql!(hostile_spying, get))
         .bind(search)
         .bind(search)
         .bind(search)
+        .bind(time_from)
+        .bind(time_from)
+        .bind(time_to)
+        .bind(time_to)
         .bind(limit)
         .bind(offset)
         .fetch_all(pool)
         .await
 }
 
-pub async fn count(search: Option<&str>) -> Result<i64, sqlx::Error> {
-    debug!(?search, "DB: hostile_spying::count");
+pub async fn count(
+    search: Option<&str>,
+    time_from: Option<&str>,
+    time_to: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    debug!(?search, ?time_from, ?time_to, "DB: hostile_spying::count");
     let pool = get_pool().await;
 
     #[derive(sqlx::FromRow)]
@@ -51,6 +60,10 @@ pub async fn count(search: Option<&str>) -> Result<i64, sqlx::Error> {
         .bind(search)
         .bind(search)
         .bind(search)
+        .bind(time_from)
+        .bind(time_from)
+        .bind(time_to)
+        .bind(time_to)
         .fetch_one(pool)
         .await?;
 
@@ -120,3 +133,201 @@ pub async fn count_overview(
 
     Ok(result.total)
 }
+
+/// Count hostile spying events received within the last `minutes`
+pub async fn count_recent(minutes: u32) -> Result<i64, sqlx::Error> {
+    debug!(minutes, "DB: hostile_spying::count_recent");
+    let pool = get_pool().await;
+
+    #[derive(sqlx::FromRow)]
+    struct CountResult {
+        total: i64,
+    }
+
+    let result = sqlx::query_as::<_, CountResult>(sql!(hostile_spying, count_recent))
+        .bind(format!("-{} minutes", minutes))
+        .fetch_one(pool)
+        .await?;
+
+    Ok(result.total)
+}
+
+/// Get hostile spying grouped by target (our planets) with filters
+pub async fn get_target_overview(
+    target_filter: Option<&str>,
+    time_from: Option<&str>,
+    time_to: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<HostileSpyingTargetRow>, sqlx::Error> {
+    debug!(?target_filter, ?time_from, ?time_to, limit, offset, "DB: hostile_spying::get_target_overview");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, HostileSpyingTargetRow>(sql!(hostile_spying, get_target_overview))
+        .bind(target_filter)
+        .bind(target_filter)
+        .bind(target_filter)
+        .bind(time_from)
+        .bind(time_from)
+        .bind(time_to)
+        .bind(time_to)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+}
+
+/// Count unique targets for pagination (with filters)
+pub async fn count_target_overview(
+    target_filter: Option<&str>,
+    time_from: Option<&str>,
+    time_to: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    debug!(?target_filter, ?time_from, ?time_to, "DB: hostile_spying::count_target_overview");
+    let pool = get_pool().await;
+
+    #[derive(sqlx::FromRow)]
+    struct CountResult {
+        total: i64,
+    }
+
+    let result = sqlx::query_as::<_, CountResult>(sql!(hostile_spying, count_target_overview))
+        .bind(target_filter)
+        .bind(target_filter)
+        .bind(target_filter)
+        .bind(time_from)
+        .bind(time_from)
+        .bind(time_to)
+        .bind(time_to)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(result.total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_get_filters_by_time_range() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO hostile_spying (external_id, attacker_coordinates, target_coordinates, report_time) VALUES
+             (1, '1:2:3', '4:5:6', '2026-10-01 10:00:00'),
+             (2, '1:2:3', '4:5:7', '2026-10-10 10:00:00'),
+             (3, '7:8:9', '4:5:7', '2026-10-11 10:00:00')"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let fetch = |search: Option<&'static str>, from: Option<&'static str>, to: Option<&'static str>| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, HostileSpyingRow>(sql!(hostile_spying, get))
+                    .bind(search).bind(search).bind(search)
+                    .bind(from).bind(from)
+                    .bind(to).bind(to)
+                    .bind(20)
+                    .bind(0)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .filter_map(|r| r.external_id)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(fetch(None, None, None).await.len(), 3);
+        assert_eq!(fetch(None, Some("2026-10-05"), None).await.len(), 2);
+        assert_eq!(fetch(Some("1:2:3"), Some("2026-10-05"), None).await, vec![2]);
+        assert_eq!(fetch(None, None, Some("2026-10-10 23:59:59")).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_overviews_group_by_attacker_and_target() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO players (id, name) VALUES (1, 'Bob');
+             INSERT INTO planets (name, player_id, coordinates, galaxy, system, planet, type)
+             VALUES ('Home', 1, '4:5:7', 4, 5, 7, 'PLANET');
+             INSERT INTO hostile_spying (external_id, attacker_coordinates, target_coordinates, report_time) VALUES
+             (1, '1:2:3', '4:5:6', '2026-10-01 10:00:00'),
+             (2, '1:2:3', '4:5:7', '2026-10-10 10:00:00'),
+             (3, '7:8:9', '4:5:7', '2026-10-11 10:00:00')"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let none: Option<&str> = None;
+        let attackers = sqlx::query_as::<_, HostileSpyingOverviewRow>(sql!(hostile_spying, get_overview))
+            .bind(none).bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(20)
+            .bind(0)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(attackers.len(), 2);
+
+        let targets = sqlx::query_as::<_, HostileSpyingTargetRow>(sql!(hostile_spying, get_target_overview))
+            .bind(none).bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(20)
+            .bind(0)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].target_coordinates, "4:5:7");
+        assert_eq!(targets[0].target_player_name.as_deref(), Some("Bob"));
+        assert_eq!(targets[0].spy_count, 2);
+        assert_eq!(targets[0].last_spy_time.as_deref(), Some("2026-10-11 10:00:00"));
+        assert_eq!(targets[1].target_coordinates, "4:5:6");
+
+        let (total,): (i64,) = sqlx::query_as(sql!(hostile_spying, count_target_overview))
+            .bind("Bob").bind("Bob").bind("Bob")
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_count_recent_uses_receive_time() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO hostile_spying (external_id, attacker_coordinates, target_coordinates, created_at) VALUES
+             (1, '1:2:3', '4:5:6', datetime('now', '-10 minutes')),
+             (2, '1:2:3', '4:5:7', datetime('now', '-30 minutes')),
+             (3, '7:8:9', '4:5:7', datetime('now', '-2 hours'))"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let count = |window: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let (total,): (i64,) = sqlx::query_as(sql!(hostile_spying, count_recent))
+                    .bind(window)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+                total
+            }
+        };
+
+        assert_eq!(count("-60 minutes").await, 2);
+        assert_eq!(count("-5 minutes").await, 0);
+        assert_eq!(count("-180 minutes").await, 3);
+    }
+}
@@ -4,8 +4,7 @@ use crate::db::models::{
 };
 use crate::get_pool;
 use tracing::debug;
-use sqlx::FromRow;
-use super::sql;
+use sqlx::{FromRow, SqlitePool};
 
 /// Row structure for galaxy status queries
 #[derive(Debug, FromRow)]
@@ -15,6 +14,18 @@ pub struct GalaxyStatusRow {
     pub last_scan_at: Option<String>,
 }
 
+/// Change markers used to build the overview ETag
+#[derive(Debug, FromRow)]
+pub struct OverviewVersionRow {
+    pub planet_count: i64,
+    pub planets_updated_at: Option<String>,
+    pub players_updated_at: Option<String>,
+    pub last_score_id: Option<i64>,
+    pub last_spy_report_id: Option<i64>,
+    pub last_battle_report_id: Option<i64>,
+    pub current_hour: String,
+}
+
 pub async fn get_planets(alliance_id: i64) -> Result<Vec<HubPlanetRow>, sqlx::Error> {
     debug!(alliance_id, "DB: hub::get_planets");
     let pool = get_pool().await;
@@ -59,6 +70,17 @@ pub async fn get_galaxy_status() -> Result<Vec<GalaxyStatusRow>, sqlx::Error> {
         .await
 }
 
+/// Scan markers for a contiguous system range within one galaxy
+pub async fn get_coverage(pool: &SqlitePool, galaxy: i64, from: i64, to: i64) -> Result<Vec<GalaxyStatusRow>, sqlx::Error> {
+    debug!(galaxy, from, to, "DB: hub::get_coverage");
+    sqlx::query_as::<_, GalaxyStatusRow>(sql!(hub, get_coverage))
+        .bind(galaxy)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+}
+
 pub async fn get_stat_view() -> Result<Vec<StatViewRow>, sqlx::Error> {
     debug!("DB: hub::get_stat_view");
     let pool = get_pool().await;
@@ -75,3 +97,80 @@ pub async fn get_scores(alliance_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::E
         .fetch_all(pool)
         .await
 }
+
+pub async fn get_overview_version() -> Result<OverviewVersionRow, sqlx::Error> {
+    debug!("DB: hub::get_overview_version");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, OverviewVersionRow>(sql!(hub, get_overview_version))
+        .fetch_one(pool)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    /// Alliance 1 with an active player (10), a deleted player (11)
+    /// and a deleted planet of the active player
+    async fn seed(pool: &sqlx::SqlitePool) {
+        sqlx::raw_sql(
+            r#"INSERT INTO alliances (id, name, tag) VALUES (1, 'Ally', 'ALY');
+               INSERT INTO players (id, name, alliance_id, is_deleted, research) VALUES
+                   (10, 'Active', 1, 0, '{"106": 10}'),
+                   (11, 'Gone', 1, 1, '{"106": 20}');
+               INSERT INTO planets (player_id, coordinates, galaxy, system, planet, type, status, buildings, fleet) VALUES
+                   (10, '1:1:1', 1, 1, 1, 'PLANET', 'seen', '{"1": 5}', '{"202": 10}'),
+                   (10, '1:1:2', 1, 1, 2, 'PLANET', 'deleted', '{"1": 30}', '{"202": 99}'),
+                   (11, '1:1:3', 1, 1, 3, 'PLANET', 'seen', '{"1": 40}', '{"202": 500}');
+               INSERT INTO player_scores (player_id, score_total) VALUES (10, 100), (11, 999);"#,
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_aggregates_exclude_deleted() {
+        let pool = connect_memory().await;
+        seed(&pool).await;
+
+        let planets = sqlx::query_as::<_, HubPlanetRow>(sql!(hub, get_planets))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(planets.len(), 1);
+        assert_eq!(planets[0].coordinates.as_deref(), Some("1:1:1"));
+
+        let research = sqlx::query_as::<_, HubResearchRow>(sql!(hub, get_research))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(research.iter().map(|r| r.id).collect::<Vec<_>>(), vec![Some(10)]);
+
+        let fleet = sqlx::query_as::<_, HubFleetRow>(sql!(hub, get_fleet))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(fleet.len(), 1);
+        assert_eq!(fleet[0].fleet.as_ref().unwrap().get("202"), Some(&10));
+
+        let buildings = sqlx::query_as::<_, HubBuildingsRow>(sql!(hub, get_buildings))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(buildings.len(), 1);
+        assert_eq!(buildings[0].buildings.as_deref(), Some(r#"{"1": 5}"#));
+
+        let scores = sqlx::query_as::<_, PlayerScoreRow>(sql!(hub, get_scores))
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(scores.iter().map(|s| s.player_id).collect::<Vec<_>>(), vec![10]);
+    }
+}
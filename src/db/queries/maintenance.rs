@@ -0,0 +1,141 @@
+use crate::get_pool;
+use sqlx::SqlitePool;
+use tracing::debug;
+
+/// Rebuild the database file, releasing free pages
+pub async fn vacuum(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    debug!("DB: maintenance::vacuum");
+    sqlx::query(sql!(maintenance, vacuum))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Refresh the query planner statistics
+pub async fn analyze(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    debug!("DB: maintenance::analyze");
+    sqlx::query(sql!(maintenance, analyze))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Rows removed by a retention run, per table
+#[derive(Debug, Default)]
+pub struct RetentionCounts {
+    pub spy_reports: u64,
+    pub battle_reports: u64,
+    pub expedition_reports: u64,
+    pub recycle_reports: u64,
+    pub hostile_spying: u64,
+    pub player_scores: u64,
+}
+
+impl RetentionCounts {
+    pub fn total(&self) -> u64 {
+        self.spy_reports
+            + self.battle_reports
+            + self.expedition_reports
+            + self.recycle_reports
+            + self.hostile_spying
+            + self.player_scores
+    }
+}
+
+/// Delete reports and score history older than `days`
+pub async fn delete_older_than(days: u32) -> Result<RetentionCounts, sqlx::Error> {
+    debug!(days, "DB: maintenance::delete_older_than");
+    let pool = get_pool().await;
+    let modifier = format!("-{} days", days);
+
+    Ok(RetentionCounts {
+        spy_reports: delete_old(pool, &modifier, sql!(maintenance, delete_old_spy_reports)).await?,
+        battle_reports: delete_old(pool, &modifier, sql!(maintenance, delete_old_battle_reports)).await?,
+        expedition_reports: delete_old(pool, &modifier, sql!(maintenance, delete_old_expedition_reports)).await?,
+        recycle_reports: delete_old(pool, &modifier, sql!(maintenance, delete_old_recycle_reports)).await?,
+        hostile_spying: delete_old(pool, &modifier, sql!(maintenance, delete_old_hostile_spying)).await?,
+        player_scores: delete_old(pool, &modifier, sql!(maintenance, delete_old_player_scores)).await?,
+    })
+}
+
+/// Reduce score history older than `days` to the last point per player and day
+pub async fn downsample_player_scores(days: u32) -> Result<u64, sqlx::Error> {
+    debug!(days, "DB: maintenance::downsample_player_scores");
+    let pool = get_pool().await;
+    delete_old(pool, &format!("-{} days", days), sql!(maintenance, downsample_player_scores)).await
+}
+
+async fn delete_old(pool: &SqlitePool, modifier: &str, query: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(query)
+        .bind(modifier)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_retention_deletes_only_old_rows() {
+        let pool = connect_memory().await;
+
+        sqlx::query(
+            "INSERT INTO hostile_spying (attacker_coordinates, target_coordinates, created_at) VALUES
+                ('1:1:1', '1:1:2', datetime('now', '-100 days')),
+                ('1:1:1', '1:1:2', datetime('now', '-10 days'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let deleted = sqlx::query(sql!(maintenance, delete_old_hostile_spying))
+            .bind("-90 days")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .rows_affected();
+        assert_eq!(deleted, 1);
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM hostile_spying")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+
+    #[tokio::test]
+    async fn test_downsample_keeps_last_score_per_day() {
+        let pool = connect_memory().await;
+
+        sqlx::query(
+            "INSERT INTO players (id, name) VALUES (1, 'Alice'), (2, 'Bob');
+             INSERT INTO player_scores (player_id, score_total, recorded_at) VALUES
+                (1, 10, datetime(date('now', '-20 days'), '+1 hours')),
+                (1, 11, datetime(date('now', '-20 days'), '+13 hours')),
+                (1, 12, datetime(date('now', '-20 days'), '+23 hours')),
+                (2, 20, datetime(date('now', '-20 days'), '+2 hours')),
+                (1, 13, datetime(date('now', '-19 days'), '+5 hours')),
+                (1, 14, datetime('now', '-2 days', '-1 hours')),
+                (1, 15, datetime('now', '-2 days'))",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let deleted = sqlx::query(sql!(maintenance, downsample_player_scores))
+            .bind("-7 days")
+            .execute(&pool)
+            .await
+            .unwrap()
+            .rows_affected();
+        assert_eq!(deleted, 2);
+
+        let scores: Vec<i64> = sqlx::query_scalar("SELECT score_total FROM player_scores ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(scores, vec![12, 20, 13, 14, 15]);
+    }
+}
@@ -6,8 +6,6 @@ macro_rules! sql {
     };
 }
 
-pub(crate) use sql;
-
 pub mod players;
 pub mod planets;
 pub mod galaxy;
@@ -20,5 +18,164 @@ pub mod recycle_reports;
 pub mod hostile_spying;
 pub mod messages;
 pub mod users;
+pub mod audit;
 pub mod config;
-pub mod bot;
\ No newline at end of file
+pub mod bot;
+pub mod maintenance;
+
+#[cfg(test)]
+mod tests {
+    use crate::db::connection::connect_memory;
+    use sqlx::Executor;
+    use std::path::Path;
+
+    /// Every file in queries/ must be valid SQL against the migrated schema
+    #[tokio::test]
+    async fn test_query_files_prepare() {
+        let pool = connect_memory().await;
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("queries");
+
+        let mut checked = 0;
+        let mut failed = Vec::new();
+        for folder in std::fs::read_dir(&root).unwrap().flatten() {
+            for file in std::fs::read_dir(folder.path()).unwrap().flatten() {
+                let path = file.path();
+                if path.extension().is_none_or(|ext| ext != "sql") {
+                    continue;
+                }
+
+                let sql = std::fs::read_to_string(&path).unwrap();
+                assert!(!sql.trim().is_empty(), "{} is empty", path.display());
+                if let Err(e) = pool.prepare(sql.as_str()).await {
+                    failed.push(format!("{}: {}", path.display(), e));
+                }
+                checked += 1;
+            }
+        }
+
+        assert!(checked > 0);
+        assert!(failed.is_empty(), "{}", failed.join("\n"));
+    }
+
+    /// sqlx's `AS "col!"` override only works with the compile-time macros; at runtime
+    /// the alias becomes the column name and `FromRow` fails with `ColumnNotFound`.
+    /// Every query that carried such an alias must map onto its row type.
+    #[tokio::test]
+    async fn test_formerly_aliased_queries_map_to_rows() {
+        use crate::db::models::{HostileSpyingOverviewRow, HostileSpyingRow, PlayerScoreRow, SpyReportRow, UserListRow, UserRow};
+
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO alliances (id, name, tag) VALUES (1, 'Alpha', 'A');
+             INSERT INTO players (id, name, alliance_id) VALUES (1, 'Bob', 1);
+             INSERT INTO users (id, api_key, player_id, alliance_id) VALUES (1, 'key', 1, 1);
+             INSERT INTO player_scores (player_id, score_total, recorded_at) VALUES (1, 100, datetime('now'));
+             INSERT INTO spy_reports (external_id, coordinates, galaxy, system, planet, type, reported_by)
+             VALUES (1, '1:2:3', 1, 2, 3, 'PLANET', 1);
+             INSERT INTO hostile_spying (external_id, attacker_coordinates, target_coordinates, report_time)
+             VALUES (1, '1:2:3', '4:5:6', '2026-10-01 10:00:00')"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let reports = sqlx::query_as::<_, SpyReportRow>(sql!(spy_reports, get_by_system))
+            .bind(1).bind(2)
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(reports.len(), 1);
+        let reports = sqlx::query_as::<_, SpyReportRow>(sql!(spy_reports, get_by_coordinates))
+            .bind(1).bind(2).bind(3).bind("PLANET").bind(10)
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(reports.len(), 1);
+
+        for sql in [sql!(alliances, get_chart), sql!(players, get_chart), sql!(players, get_chart_7days), sql!(hub, get_scores)] {
+            let scores = sqlx::query_as::<_, PlayerScoreRow>(sql).bind(1).fetch_all(&pool).await.unwrap();
+            assert_eq!(scores.len(), 1, "{sql}");
+        }
+
+        let user = sqlx::query_as::<_, UserRow>(sql!(users, get_by_api_key))
+            .bind("key")
+            .fetch_optional(&pool).await.unwrap();
+        assert!(user.is_some());
+        let user = sqlx::query_as::<_, UserRow>(sql!(users, get_by_player_id))
+            .bind(1)
+            .fetch_optional(&pool).await.unwrap();
+        assert!(user.is_some());
+        let users = sqlx::query_as::<_, UserListRow>(sql!(users, get_all)).fetch_all(&pool).await.unwrap();
+        assert_eq!(users.len(), 1);
+
+        let none: Option<&str> = None;
+        let spying = sqlx::query_as::<_, HostileSpyingRow>(sql!(hostile_spying, get))
+            .bind(none).bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(20).bind(0)
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(spying.len(), 1);
+        let overview = sqlx::query_as::<_, HostileSpyingOverviewRow>(sql!(hostile_spying, get_overview))
+            .bind(none).bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(20).bind(0)
+            .fetch_all(&pool).await.unwrap();
+        assert_eq!(overview.len(), 1);
+
+        #[derive(sqlx::FromRow)]
+        struct CountResult {
+            total: i64,
+        }
+        let count = sqlx::query_as::<_, CountResult>(sql!(hostile_spying, count_overview))
+            .bind(none).bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .bind(none).bind(none)
+            .fetch_one(&pool).await.unwrap();
+        assert_eq!(count.total, 1);
+    }
+
+    /// The extension retries report uploads, so posting the same report twice must
+    /// update the existing row (`ON CONFLICT(external_id)`) instead of adding one
+    #[tokio::test]
+    async fn test_report_upserts_are_idempotent() {
+        let pool = connect_memory().await;
+        sqlx::query("INSERT INTO players (id, name) VALUES (1, 'Bob'); INSERT INTO users (id, api_key) VALUES (1, 'key')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let none = None::<&str>;
+        for _ in 0..2 {
+            sqlx::query(sql!(spy_reports, upsert))
+                .bind(42).bind("1:2:3").bind(1).bind(2).bind(3).bind("PLANET")
+                .bind(r#"{"901": 1000}"#).bind(none).bind(none).bind(none).bind(none)
+                .bind(1).bind("2026-01-01 12:00:00").bind("hash")
+                .execute(&pool).await.unwrap();
+            sqlx::query(sql!(battle_reports, upsert))
+                .bind(42).bind("1:2:3").bind(1).bind(2).bind(3).bind("PLANET")
+                .bind(0).bind(100).bind(1).bind(2).bind(3).bind(4).bind(5)
+                .bind("2026-01-01 12:00:00").bind(1)
+                .execute(&pool).await.unwrap();
+            sqlx::query(sql!(expedition_reports, upsert))
+                .bind(42).bind("Nichts gefunden").bind("nothing").bind(none).bind(none)
+                .bind("2026-01-01 12:00:00").bind(1)
+                .execute(&pool).await.unwrap();
+            sqlx::query(sql!(recycle_reports, upsert))
+                .bind(42).bind("1:2:3").bind(1).bind(2).bind(3)
+                .bind(10).bind(20).bind(100).bind(200)
+                .bind("2026-01-01 12:00:00").bind(1)
+                .execute(&pool).await.unwrap();
+            sqlx::query(sql!(hostile_spying, upsert))
+                .bind(42).bind("1:2:4").bind("1:2:3").bind("2026-01-01 12:00:00")
+                .execute(&pool).await.unwrap();
+        }
+
+        for table in ["spy_reports", "battle_reports", "expedition_reports", "recycle_reports", "hostile_spying"] {
+            let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 1, "{table}");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::get_pool;
-use super::sql;
 use tracing::debug;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert(
     player_id: i64,
     coordinates: &str,
@@ -11,9 +11,16 @@ pub async fn upsert(
     planet_type: &str,
     name: Option<&str>,
     pr0_planet_id: Option<i64>,
-) -> Result<(), sqlx::Error> {
+) -> Result<bool, sqlx::Error> {
     debug!(player_id, coordinates, ?name, ?pr0_planet_id, "DB: upsert planet");
     let pool = get_pool().await;
+
+    let existing: i64 = sqlx::query_scalar(sql!(planets, exists))
+        .bind(coordinates)
+        .bind(planet_type)
+        .fetch_one(pool)
+        .await?;
+
     sqlx::query(sql!(planets, upsert_galaxy))
         .bind(name)
         .bind(player_id)
@@ -25,7 +32,7 @@ pub async fn upsert(
         .bind(pr0_planet_id)
         .execute(pool)
         .await?;
-    Ok(())
+    Ok(existing == 0)
 }
 
 pub async fn update_buildings(coordinates: &str, planet_type: &str, buildings_json: &str) -> Result<(), sqlx::Error> {
@@ -87,7 +94,8 @@ pub async fn mark_deleted(coordinates: &str, planet_type: &str) -> Result<(), sq
     Ok(())
 }
 
-/// Full upsert from Empire page with all data
+/// Upsert a planet from the empire page. Returns true if the planet was new.
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert_empire(
     player_id: i64,
     pr0_planet_id: i64,
@@ -105,10 +113,16 @@ pub async fn upsert_empire(
     buildings: &std::collections::HashMap<String, i64>,
     fleet: &std::collections::HashMap<String, i64>,
     defense: &std::collections::HashMap<String, i64>,
-) -> Result<(), sqlx::Error> {
+) -> Result<bool, sqlx::Error> {
     debug!(player_id, coordinates, name, "DB: upsert_empire");
     let pool = get_pool().await;
 
+    let existing: i64 = sqlx::query_scalar(sql!(planets, exists))
+        .bind(coordinates)
+        .bind("PLANET")
+        .fetch_one(pool)
+        .await?;
+
     let resources_json = serde_json::to_string(resources).unwrap_or_default();
     let buildings_json = serde_json::to_string(buildings).unwrap_or_default();
     let fleet_json = serde_json::to_string(fleet).unwrap_or_default();
@@ -137,5 +151,5 @@ pub async fn upsert_empire(
         .bind(&defense_json)
         .execute(pool)
         .await?;
-    Ok(())
+    Ok(existing == 0)
 }
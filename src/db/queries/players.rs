@@ -1,47 +1,99 @@
-use crate::db::models::{PlanetRow, PlayerRow, PlayerScoreRow, PlayerWithAlliance};
-use crate::get_pool;
-use super::sql;
+use crate::db::models::{AllianceHistoryRow, PlanetEnergyRow, PlanetRow, PlanetTemperatureRow, PlayerRow, PlayerScoreRow, PlayerWithAlliance, RankingRow, StatType};
+use sqlx::SqlitePool;
 use tracing::debug;
 
-pub async fn get_by_id(player_id: i64) -> Result<Option<PlayerWithAlliance>, sqlx::Error> {
+pub async fn get_by_id(pool: &SqlitePool, player_id: i64) -> Result<Option<PlayerWithAlliance>, sqlx::Error> {
     debug!(player_id, "DB: get_by_id");
-    let pool = get_pool().await;
     sqlx::query_as::<_, PlayerWithAlliance>(sql!(players, get_by_id))
         .bind(player_id)
         .fetch_optional(pool)
         .await
 }
 
-pub async fn get_by_name(name: &str) -> Result<Option<PlayerWithAlliance>, sqlx::Error> {
+pub async fn get_by_name(pool: &SqlitePool, name: &str) -> Result<Option<PlayerWithAlliance>, sqlx::Error> {
     debug!(name, "DB: get_by_name");
-    let pool = get_pool().await;
     sqlx::query_as::<_, PlayerWithAlliance>(sql!(players, get_by_name))
         .bind(name)
         .fetch_optional(pool)
         .await
 }
 
-pub async fn get_planets(player_id: i64) -> Result<Vec<PlanetRow>, sqlx::Error> {
+pub async fn get_planets(pool: &SqlitePool, player_id: i64) -> Result<Vec<PlanetRow>, sqlx::Error> {
     debug!(player_id, "DB: get_planets");
-    let pool = get_pool().await;
     sqlx::query_as::<_, PlanetRow>(sql!(players, get_player_planets))
         .bind(player_id)
         .fetch_all(pool)
         .await
 }
 
-pub async fn get_chart(player_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
+pub async fn get_planet_energy(pool: &SqlitePool, player_id: i64) -> Result<Vec<PlanetEnergyRow>, sqlx::Error> {
+    debug!(player_id, "DB: get_planet_energy");
+    sqlx::query_as::<_, PlanetEnergyRow>(sql!(players, get_planet_energy))
+        .bind(player_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_planet_temperatures(pool: &SqlitePool, player_id: i64) -> Result<Vec<PlanetTemperatureRow>, sqlx::Error> {
+    debug!(player_id, "DB: get_planet_temperatures");
+    sqlx::query_as::<_, PlanetTemperatureRow>(sql!(players, get_planet_temperatures))
+        .bind(player_id)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_chart(pool: &SqlitePool, player_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
     debug!(player_id, "DB: get_chart");
-    let pool = get_pool().await;
     sqlx::query_as::<_, PlayerScoreRow>(sql!(players, get_chart))
         .bind(player_id)
         .fetch_all(pool)
         .await
 }
 
-pub async fn get_chart_7days(player_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
+pub async fn get_charts(pool: &SqlitePool, player_ids: &[i64]) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
+    debug!(count = player_ids.len(), "DB: get_charts");
+    let ids = serde_json::to_string(player_ids).unwrap_or_else(|_| "[]".to_string());
+    sqlx::query_as::<_, PlayerScoreRow>(sql!(players, get_charts))
+        .bind(ids)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_chart_range(
+    pool: &SqlitePool,
+    player_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
+    debug!(player_id, ?from, ?to, "DB: get_chart_range");
+    sqlx::query_as::<_, PlayerScoreRow>(sql!(players, get_chart_range))
+        .bind(player_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+}
+
+/// Last score per time bucket; `bucket_format` is an SQLite strftime format
+pub async fn get_chart_bucketed(
+    pool: &SqlitePool,
+    player_id: i64,
+    bucket_format: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
+    debug!(player_id, bucket_format, ?from, ?to, "DB: get_chart_bucketed");
+    sqlx::query_as::<_, PlayerScoreRow>(sql!(players, get_chart_bucketed))
+        .bind(player_id)
+        .bind(bucket_format)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_chart_7days(pool: &SqlitePool, player_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx::Error> {
     debug!(player_id, "DB: get_chart_7days");
-    let pool = get_pool().await;
     sqlx::query_as::<_, PlayerScoreRow>(sql!(players, get_chart_7days))
         .bind(player_id)
         .fetch_all(pool)
@@ -49,6 +101,7 @@ pub async fn get_chart_7days(player_id: i64) -> Result<Vec<PlayerScoreRow>, sqlx
 }
 
 pub async fn upsert(
+    pool: &SqlitePool,
     id: i64,
     name: &str,
     alliance_id: Option<i64>,
@@ -56,7 +109,6 @@ pub async fn upsert(
     notice: Option<&str>,
 ) -> Result<(), sqlx::Error> {
     debug!(id, name, ?alliance_id, "DB: upsert player");
-    let pool = get_pool().await;
     sqlx::query(sql!(players, upsert))
         .bind(id)
         .bind(name)
@@ -69,9 +121,8 @@ pub async fn upsert(
 }
 
 /// Full upsert from PlayerCard with all stats
-pub async fn upsert_full(req: &crate::api::handlers::players::UpsertPlayerRequest) -> Result<(), sqlx::Error> {
+pub async fn upsert_full(pool: &SqlitePool, req: &crate::api::handlers::players::UpsertPlayerRequest) -> Result<(), sqlx::Error> {
     debug!(req.id, req.name, "DB: upsert_full player");
-    let pool = get_pool().await;
     sqlx::query(sql!(players, upsert_full))
         .bind(req.id)
         .bind(&req.name)
@@ -111,9 +162,8 @@ pub async fn upsert_full(req: &crate::api::handlers::players::UpsertPlayerReques
 }
 
 /// Ensure player exists (minimal insert from galaxy scan, does nothing if player exists)
-pub async fn ensure_exists(id: i64, name: &str) -> Result<(), sqlx::Error> {
+pub async fn ensure_exists(pool: &SqlitePool, id: i64, name: &str) -> Result<(), sqlx::Error> {
     debug!(id, name, "DB: ensure_exists player");
-    let pool = get_pool().await;
     sqlx::query(sql!(players, ensure_exists))
         .bind(id)
         .bind(name)
@@ -122,32 +172,70 @@ pub async fn ensure_exists(id: i64, name: &str) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-pub async fn update_research(player_id: i64, research_json: &str) -> Result<(), sqlx::Error> {
+/// Store a player's research. Returns true if it differs from what was stored before.
+pub async fn update_research(pool: &SqlitePool, player_id: i64, research_json: &str) -> Result<bool, sqlx::Error> {
     debug!(player_id, "DB: update_research");
-    let pool = get_pool().await;
+    let previous: Option<String> = sqlx::query_scalar(sql!(players, get_research))
+        .bind(player_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+
     sqlx::query(sql!(players, update_research))
         .bind(research_json)
         .bind(player_id)
         .execute(pool)
         .await?;
-    Ok(())
+
+    // Compare parsed JSON, key order of the stored text is arbitrary
+    let parse = |json: &str| serde_json::from_str::<serde_json::Value>(json).ok();
+    Ok(previous.as_deref().and_then(parse) != parse(research_json))
 }
 
-pub async fn update_alliance(player_id: i64, alliance_id: i64) -> Result<(), sqlx::Error> {
+/// Set a player's alliance and record the transition in alliance_history
+/// if the value actually changed
+pub async fn update_alliance(pool: &SqlitePool, player_id: i64, alliance_id: i64) -> Result<(), sqlx::Error> {
     debug!(player_id, alliance_id, "DB: update_alliance");
-    let pool = get_pool().await;
-    sqlx::query(sql!(players, update_alliance))
+    let mut tx = pool.begin().await?;
+
+    let old_alliance_id: Option<Option<i64>> = sqlx::query_scalar(sql!(players, get_alliance_id))
+        .bind(player_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let result = sqlx::query(sql!(players, update_alliance))
         .bind(alliance_id)
         .bind(player_id)
         .bind(alliance_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
-    Ok(())
+
+    if result.rows_affected() > 0
+        && let Some(old_alliance_id) = old_alliance_id
+        && old_alliance_id != Some(alliance_id)
+    {
+        debug!(player_id, ?old_alliance_id, alliance_id, "DB: alliance change recorded");
+        sqlx::query(sql!(players, insert_alliance_history))
+            .bind(player_id)
+            .bind(old_alliance_id)
+            .bind(alliance_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await
+}
+
+pub async fn get_alliance_history(pool: &SqlitePool, player_id: i64) -> Result<Vec<AllianceHistoryRow>, sqlx::Error> {
+    debug!(player_id, "DB: get_alliance_history");
+    sqlx::query_as::<_, AllianceHistoryRow>(sql!(players, get_alliance_history))
+        .bind(player_id)
+        .fetch_all(pool)
+        .await
 }
 
-pub async fn mark_deleted(player_id: i64) -> Result<(), sqlx::Error> {
+pub async fn mark_deleted(pool: &SqlitePool, player_id: i64) -> Result<(), sqlx::Error> {
     debug!(player_id, "DB: mark_deleted");
-    let pool = get_pool().await;
     sqlx::query(sql!(players, mark_deleted))
         .bind(player_id)
         .execute(pool)
@@ -155,14 +243,12 @@ pub async fn mark_deleted(player_id: i64) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-pub async fn get_by_ids(ids: &[i64]) -> Result<Vec<PlayerRow>, sqlx::Error> {
+pub async fn get_by_ids(pool: &SqlitePool, ids: &[i64]) -> Result<Vec<PlayerRow>, sqlx::Error> {
     debug!(count = ids.len(), "DB: get_by_ids");
     if ids.is_empty() {
         return Ok(vec![]);
     }
 
-    let pool = get_pool().await;
-
     // Placeholders: ?, ?, ?
     let placeholders: String = ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
     let query = format!("SELECT * FROM players WHERE id IN ({})", placeholders);
@@ -175,6 +261,16 @@ pub async fn get_by_ids(ids: &[i64]) -> Result<Vec<PlayerRow>, sqlx::Error> {
     q.fetch_all(pool).await
 }
 
+/// Top players by the given score category, across all alliances
+pub async fn get_ranking(pool: &SqlitePool, stat: StatType, limit: i64) -> Result<Vec<RankingRow>, sqlx::Error> {
+    debug!(stat = stat.as_str(), limit, "DB: get_ranking");
+    sqlx::query_as::<_, RankingRow>(sql!(players, get_ranking))
+        .bind(stat.as_str())
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+}
+
 pub struct PlayerStats {
     pub id: i64,
     pub name: String,
@@ -187,9 +283,8 @@ pub struct PlayerStats {
     pub rank: Option<i64>,
 }
 
-pub async fn upsert_stats(stats: &[PlayerStats]) -> Result<u64, sqlx::Error> {
+pub async fn upsert_stats(pool: &SqlitePool, stats: &[PlayerStats]) -> Result<u64, sqlx::Error> {
     debug!(count = stats.len(), "DB: upsert_stats");
-    let pool = get_pool().await;
     let mut count = 0u64;
 
     for s in stats {
@@ -224,3 +319,36 @@ pub async fn upsert_stats(stats: &[PlayerStats]) -> Result<u64, sqlx::Error> {
 
     Ok(count)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::connect_memory;
+
+    #[tokio::test]
+    async fn test_ranking_excludes_deleted_and_markers() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            "INSERT INTO players (id, name, score_total, score_fleet, is_deleted) VALUES
+             (0, 'System', 999, 999, 0), (1, 'A', 100, 5, 0), (2, 'B', 300, NULL, 0),
+             (3, 'C', 500, 50, 1), (4, 'D', 200, 20, 0)"
+        )
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let ranking = |stat: StatType| {
+            sqlx::query_as::<_, RankingRow>(sql!(players, get_ranking))
+                .bind(stat.as_str())
+                .bind(10)
+                .fetch_all(&pool)
+        };
+
+        let total: Vec<i64> = ranking(StatType::Total).await.unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(total, vec![2, 4, 1]);
+
+        // Players without a fleet score are left out
+        let fleet: Vec<i64> = ranking(StatType::Fleet).await.unwrap().iter().map(|r| r.id).collect();
+        assert_eq!(fleet, vec![4, 1]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::get_pool;
-use super::sql;
 use tracing::debug;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert(
     external_id: i64,
     galaxy: i64,
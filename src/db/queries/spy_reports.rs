@@ -1,9 +1,11 @@
-use crate::db::models::{SpyReportRow, SpyReportHistoryRow};
+use crate::db::models::{BotSpyReport, BotSpyReportRow, SpyReportRow, SpyReportHistoryRow};
 use crate::get_pool;
+use sqlx::SqlitePool;
+use sha2::{Digest, Sha256};
 use tracing::debug;
-use super::sql;
 
 pub async fn get_by_coordinates(
+    pool: &SqlitePool,
     galaxy: i64,
     system: i64,
     planet: i64,
@@ -11,7 +13,6 @@ pub async fn get_by_coordinates(
     limit: i64,
 ) -> Result<Vec<SpyReportRow>, sqlx::Error> {
     debug!(galaxy, system, planet, planet_type, limit, "DB: spy_reports::get_by_coordinates");
-    let pool = get_pool().await;
     sqlx::query_as::<_, SpyReportRow>(sql!(spy_reports, get_by_coordinates))
         .bind(galaxy)
         .bind(system)
@@ -35,6 +36,7 @@ pub async fn get_by_system(
         .await
 }
 
+/// Newest reports of a position with their reporter's name, newest first
 pub async fn get_history_with_reporter(
     galaxy: i64,
     system: i64,
@@ -44,7 +46,7 @@ pub async fn get_history_with_reporter(
 ) -> Result<Vec<SpyReportHistoryRow>, sqlx::Error> {
     debug!(galaxy, system, planet, planet_type, limit, "DB: spy_reports::get_history_with_reporter");
     let pool = get_pool().await;
-    sqlx::query_as::<_, SpyReportHistoryRow>(sql!(spy_reports, get_history_with_reporter))
+    sqlx::query_as::<_, SpyReportHistoryRow>(sql!(spy_reports, get_with_names))
         .bind(galaxy)
         .bind(system)
         .bind(planet)
@@ -54,6 +56,27 @@ pub async fn get_history_with_reporter(
         .await
 }
 
+/// Newest report of a position with player, alliance and reporter names (/spy and the web UI)
+pub async fn get_latest(
+    galaxy: i64,
+    system: i64,
+    planet: i64,
+    planet_type: &str,
+) -> Result<Option<BotSpyReport>, sqlx::Error> {
+    debug!(galaxy, system, planet, planet_type, "DB: spy_reports::get_latest");
+    let pool = get_pool().await;
+    let row = sqlx::query_as::<_, BotSpyReportRow>(sql!(spy_reports, get_with_names))
+        .bind(galaxy)
+        .bind(system)
+        .bind(planet)
+        .bind(planet_type)
+        .bind(1)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(BotSpyReport::from))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn upsert(
     external_id: i64,
     galaxy: i64,
@@ -67,6 +90,7 @@ pub async fn upsert(
     defense: Option<&str>,
     reported_by: Option<i64>,
     report_time: Option<&str>,
+    content_hash: &str,
 ) -> Result<(), sqlx::Error> {
     debug!(external_id, galaxy, system, planet, "DB: spy_reports::upsert");
     let pool = get_pool().await;
@@ -85,7 +109,172 @@ pub async fn upsert(
         .bind(defense)
         .bind(reported_by)
         .bind(report_time)
+        .bind(content_hash)
         .execute(pool)
         .await?;
     Ok(())
 }
+
+/// Whether a report with the same content hash but another external ID was stored
+/// within the last `window_minutes`
+pub async fn has_recent_duplicate(content_hash: &str, external_id: i64, window_minutes: u32) -> Result<bool, sqlx::Error> {
+    debug!(external_id, window_minutes, "DB: spy_reports::has_recent_duplicate");
+    let pool = get_pool().await;
+    let count: i64 = sqlx::query_scalar(sql!(spy_reports, count_duplicates))
+        .bind(content_hash)
+        .bind(external_id)
+        .bind(format!("-{} minutes", window_minutes))
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// SHA-256 over coordinates, type, report time and the report's JSON columns.
+/// JSON is re-serialized first so key order doesn't change the hash.
+pub fn content_hash(coordinates: &str, planet_type: &str, report_time: Option<&str>, json_columns: &[Option<&str>]) -> String {
+    let mut hasher = Sha256::new();
+    for part in [Some(coordinates), Some(planet_type), report_time] {
+        hasher.update(part.unwrap_or_default());
+        hasher.update([0]);
+    }
+    for json in json_columns {
+        let canonical = json
+            .and_then(|j| serde_json::from_str::<serde_json::Value>(j).ok())
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        hasher.update(canonical);
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::connection::connect_memory;
+    use crate::db::models::{BotSpyReportRow, SpyReportHistoryRow};
+    use sqlx::Row;
+
+    #[tokio::test]
+    async fn test_coordinate_lookup_uses_index() {
+        let pool = connect_memory().await;
+
+        let plan: Vec<String> = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", sql!(spy_reports, get_by_coordinates)))
+            .bind(1)
+            .bind(2)
+            .bind(3)
+            .bind("PLANET")
+            .bind(1)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get::<String, _>("detail"))
+            .collect();
+
+        // Index seek on the coordinates, newest first without a separate sort
+        assert!(plan.iter().any(|d| d.contains("idx_spy_reports_coords_time")), "{plan:?}");
+        assert!(!plan.iter().any(|d| d.contains("TEMP B-TREE")), "{plan:?}");
+    }
+
+    #[test]
+    fn test_content_hash_ignores_key_order() {
+        let hash = |resources: &str| super::content_hash("1:2:3", "PLANET", Some("2026-01-01 12:00:00"), &[Some(resources), None]);
+        assert_eq!(hash(r#"{"901": 1, "902": 2}"#), hash(r#"{"902":2,"901":1}"#));
+        assert_ne!(hash(r#"{"901": 1, "902": 2}"#), hash(r#"{"901": 1, "902": 3}"#));
+        // Same content at other coordinates is a different report
+        assert_ne!(
+            super::content_hash("1:2:4", "PLANET", None, &[None]),
+            super::content_hash("1:2:3", "PLANET", None, &[None]),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_duplicates_need_another_external_id() {
+        let pool = connect_memory().await;
+        sqlx::query("INSERT INTO spy_reports (external_id, coordinates, galaxy, system, planet, content_hash) VALUES (1, '1:2:3', 1, 2, 3, 'abc')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        for (hash, external_id, expected) in [("abc", 2, 1), ("abc", 1, 0), ("def", 2, 0)] {
+            let count: i64 = sqlx::query_scalar(sql!(spy_reports, count_duplicates))
+                .bind(hash)
+                .bind(external_id)
+                .bind("-60 minutes")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(count, expected, "{hash} {external_id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_planet_and_moon_histories_are_separate() {
+        let pool = connect_memory().await;
+        sqlx::query(
+            r#"INSERT INTO spy_reports (external_id, coordinates, galaxy, system, planet, type, resources)
+               VALUES (1, '1:2:3', 1, 2, 3, 'PLANET', '{"901": 1}'),
+                      (2, '1:2:3', 1, 2, 3, 'PLANET', '{"901": 2}'),
+                      (3, '1:2:3', 1, 2, 3, 'MOON', '{"901": 3}')"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        for (planet_type, expected) in [("PLANET", vec![2, 1]), ("MOON", vec![3])] {
+            for query in [sql!(spy_reports, get_with_names), sql!(spy_reports, get_by_coordinates)] {
+                let mut ids: Vec<i64> = sqlx::query(query)
+                    .bind(1)
+                    .bind(2)
+                    .bind(3)
+                    .bind(planet_type)
+                    .bind(10)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+                    .iter()
+                    .map(|row| row.get("id"))
+                    .collect();
+                ids.sort_unstable_by(|a, b| b.cmp(a));
+                assert_eq!(ids, expected, "{planet_type}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reporter_resolves_the_same_for_bot_and_api() {
+        let pool = connect_memory().await;
+        // The user's own ID differs from the player ID stored in reported_by
+        sqlx::query(
+            r#"INSERT INTO players (id, name) VALUES (42, 'Bob'), (7, 'Target');
+               INSERT INTO users (id, api_key, player_id) VALUES (1, 'key', 42);
+               INSERT INTO planets (player_id, coordinates, galaxy, system, planet, type) VALUES (7, '1:2:3', 1, 2, 3, 'PLANET');
+               INSERT INTO spy_reports (external_id, coordinates, galaxy, system, planet, type, reported_by)
+               VALUES (1, '1:2:3', 1, 2, 3, 'PLANET', 42)"#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let latest = sqlx::query_as::<_, BotSpyReportRow>(sql!(spy_reports, get_with_names))
+            .bind(1).bind(2).bind(3).bind("PLANET").bind(1)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let history = sqlx::query_as::<_, SpyReportHistoryRow>(sql!(spy_reports, get_with_names))
+            .bind(1).bind(2).bind(3).bind("PLANET").bind(10)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let nearest = sqlx::query_as::<_, BotSpyReportRow>(sql!(bot, get_nearest_spy_report))
+            .bind(1).bind(2).bind(4)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(latest.player_name.as_deref(), Some("Target"));
+        assert_eq!(latest.reporter_name.as_deref(), Some("Bob"));
+        assert_eq!(history[0].reporter_name.as_deref(), Some("Bob"));
+        assert_eq!(nearest.reporter_name.as_deref(), Some("Bob"));
+    }
+}
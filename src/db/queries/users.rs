@@ -1,27 +1,34 @@
 use crate::db::models::{UserRow, UserListRow, UserRole};
 use crate::get_pool;
+use sqlx::SqlitePool;
 use crate::api::auth::mask_api_key;
 use tracing::debug;
-use super::sql;
 
-pub async fn get_by_api_key(api_key: &str) -> Result<Option<UserRow>, sqlx::Error> {
+pub async fn get_by_api_key(pool: &SqlitePool, api_key: &str) -> Result<Option<UserRow>, sqlx::Error> {
     debug!(api_key_len = api_key.len(), api_key_masked = %mask_api_key(api_key), "DB: users::get_by_api_key");
-    let pool = get_pool().await;
     sqlx::query_as::<_, UserRow>(sql!(users, get_by_api_key))
         .bind(api_key)
         .fetch_optional(pool)
         .await
 }
 
-pub async fn get_by_player_id(player_id: i64) -> Result<Option<UserRow>, sqlx::Error> {
+pub async fn get_by_player_id(pool: &SqlitePool, player_id: i64) -> Result<Option<UserRow>, sqlx::Error> {
     debug!(player_id, "DB: users::get_by_player_id");
-    let pool = get_pool().await;
     sqlx::query_as::<_, UserRow>(sql!(users, get_by_player_id))
         .bind(player_id)
         .fetch_optional(pool)
         .await
 }
 
+/// Soft-deleted user by id
+pub async fn get_deleted_by_id(pool: &SqlitePool, user_id: i64) -> Result<Option<UserRow>, sqlx::Error> {
+    debug!(user_id, "DB: users::get_deleted_by_id");
+    sqlx::query_as::<_, UserRow>(sql!(users, get_deleted_by_id))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
 pub async fn get_all() -> Result<Vec<UserListRow>, sqlx::Error> {
     debug!("DB: users::get_all");
     let pool = get_pool().await;
@@ -30,9 +37,29 @@ pub async fn get_all() -> Result<Vec<UserListRow>, sqlx::Error> {
         .await
 }
 
-pub async fn update_activity(user_id: i64) -> Result<(), sqlx::Error> {
-    debug!(user_id, "DB: users::update_activity");
+/// One page of users, optionally filtered by player name (`limit` -1 = no limit)
+pub async fn get_paged(q: Option<&str>, limit: i64, offset: i64) -> Result<Vec<UserListRow>, sqlx::Error> {
+    debug!(?q, limit, offset, "DB: users::get_paged");
+    let pool = get_pool().await;
+    sqlx::query_as::<_, UserListRow>(sql!(users, get_paged))
+        .bind(q)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn count(q: Option<&str>) -> Result<i64, sqlx::Error> {
+    debug!(?q, "DB: users::count");
     let pool = get_pool().await;
+    sqlx::query_scalar(sql!(users, count))
+        .bind(q)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn update_activity(pool: &SqlitePool, user_id: i64) -> Result<(), sqlx::Error> {
+    debug!(user_id, "DB: users::update_activity");
     sqlx::query(sql!(users, update_activity))
         .bind(user_id)
         .execute(pool)
@@ -40,9 +67,8 @@ pub async fn update_activity(user_id: i64) -> Result<(), sqlx::Error> {
     Ok(())
 }
 
-pub async fn create(api_key: &str, player_id: Option<i64>, alliance_id: Option<i64>) -> Result<i64, sqlx::Error> {
+pub async fn create(pool: &SqlitePool, api_key: &str, player_id: Option<i64>, alliance_id: Option<i64>) -> Result<i64, sqlx::Error> {
     debug!(?player_id, ?alliance_id, "DB: users::create");
-    let pool = get_pool().await;
     let result = sqlx::query(sql!(users, create))
         .bind(api_key)
         .bind(player_id)
@@ -52,6 +78,7 @@ pub async fn create(api_key: &str, player_id: Option<i64>, alliance_id: Option<i
     Ok(result.last_insert_rowid())
 }
 
+/// Soft-delete a user (sets `deleted_at`, the key stops working)
 pub async fn delete(user_id: i64) -> Result<bool, sqlx::Error> {
     debug!(user_id, "DB: users::delete");
     let pool = get_pool().await;
@@ -62,6 +89,27 @@ pub async fn delete(user_id: i64) -> Result<bool, sqlx::Error> {
     Ok(result.rows_affected() > 0)
 }
 
+/// Undo a soft delete
+pub async fn restore(pool: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    debug!(user_id, "DB: users::restore");
+    let result = sqlx::query(sql!(users, restore))
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Permanently remove a user row
+pub async fn purge(user_id: i64) -> Result<bool, sqlx::Error> {
+    debug!(user_id, "DB: users::purge");
+    let pool = get_pool().await;
+    let result = sqlx::query(sql!(users, purge))
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn update_role(user_id: i64, role: UserRole) -> Result<bool, sqlx::Error> {
     debug!(user_id, ?role, "DB: users::update_role");
     let pool = get_pool().await;
@@ -74,6 +122,17 @@ pub async fn update_role(user_id: i64, role: UserRole) -> Result<bool, sqlx::Err
     Ok(result.rows_affected() > 0)
 }
 
+pub async fn set_player_id(user_id: i64, player_id: i64) -> Result<bool, sqlx::Error> {
+    debug!(user_id, player_id, "DB: users::set_player_id");
+    let pool = get_pool().await;
+    let result = sqlx::query(sql!(users, set_player_id))
+        .bind(player_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 pub async fn update_language(user_id: i64, language: &str) -> Result<(), sqlx::Error> {
     debug!(user_id, language, "DB: users::update_language");
     let pool = get_pool().await;
@@ -84,3 +143,77 @@ pub async fn update_language(user_id: i64, language: &str) -> Result<(), sqlx::E
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::db::connection::connect_memory;
+    use crate::db::models::{UserListRow, UserRow};
+    use sqlx::SqlitePool;
+
+    async fn by_key(pool: &SqlitePool, key: &str) -> Option<UserRow> {
+        sqlx::query_as::<_, UserRow>(sql!(users, get_by_api_key))
+            .bind(key)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_soft_delete_and_restore() {
+        let pool = connect_memory().await;
+        sqlx::query(sql!(users, create))
+            .bind("key-1")
+            .bind(Some(42))
+            .bind(None::<i64>)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let id = by_key(&pool, "key-1").await.unwrap().id;
+
+        sqlx::query(sql!(users, delete)).bind(id).execute(&pool).await.unwrap();
+        assert!(by_key(&pool, "key-1").await.is_none());
+
+        // The row is kept, so the user can be restored with the same key
+        sqlx::query(sql!(users, restore)).bind(id).execute(&pool).await.unwrap();
+        assert_eq!(by_key(&pool, "key-1").await.map(|u| u.id), Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_paged_search() {
+        let pool = connect_memory().await;
+        sqlx::query("INSERT INTO players (id, name) VALUES (1, 'Alpha'), (2, 'Beta'), (3, 'Alphonse')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (api_key, player_id) VALUES ('a', 1), ('b', 2), ('c', 3)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let page = |q: Option<&'static str>, limit: i64, offset: i64| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, UserListRow>(sql!(users, get_paged))
+                    .bind(q)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|u| u.player_name.unwrap())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(page(None, -1, 0).await, ["Alpha", "Beta", "Alphonse"]);
+        assert_eq!(page(Some("alph"), 1, 1).await, ["Alphonse"]);
+
+        let total: i64 = sqlx::query_scalar(sql!(users, count))
+            .bind(Some("alph"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+    }
+}
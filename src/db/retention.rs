@@ -0,0 +1,53 @@
+use std::time::Duration;
+use tracing::{error, info};
+
+use crate::db::queries::maintenance;
+use crate::CONFIG;
+
+/// How often the retention cleanup runs
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Periodically delete reports and score history older than `RETENTION_DAYS`
+///
+/// This function runs indefinitely and should be spawned as a tokio task.
+pub async fn run_retention_job() {
+    let days = CONFIG.retention_days;
+    let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+
+    loop {
+        // First tick completes immediately, so cleanup also runs on startup
+        interval.tick().await;
+
+        match maintenance::delete_older_than(days).await {
+            Ok(counts) => info!(
+                days,
+                total = counts.total(),
+                spy_reports = counts.spy_reports,
+                battle_reports = counts.battle_reports,
+                expedition_reports = counts.expedition_reports,
+                recycle_reports = counts.recycle_reports,
+                hostile_spying = counts.hostile_spying,
+                player_scores = counts.player_scores,
+                "Retention cleanup finished"
+            ),
+            Err(e) => error!(error = %e, "Retention cleanup failed"),
+        }
+    }
+}
+
+/// Periodically reduce score history older than `SCORE_DOWNSAMPLE_AFTER_DAYS` to one point per day
+///
+/// This function runs indefinitely and should be spawned as a tokio task.
+pub async fn run_score_downsample_job() {
+    let days = CONFIG.score_downsample_after_days;
+    let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match maintenance::downsample_player_scores(days).await {
+            Ok(deleted) => info!(days, deleted, "Score downsampling finished"),
+            Err(e) => error!(error = %e, "Score downsampling failed"),
+        }
+    }
+}
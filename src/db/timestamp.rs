@@ -0,0 +1,42 @@
+use chrono::{DateTime, NaiveDateTime};
+
+/// Timestamp format of the `created_at` columns (SQLite CURRENT_TIMESTAMP)
+pub const DB_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parse a timestamp read from the database as naive UTC.
+///
+/// Accepts SQLite's `YYYY-MM-DD HH:MM:SS` (optionally with fractional seconds) as well as
+/// RFC3339, so values written by other drivers or tools don't silently count as missing.
+pub fn parse_db_timestamp(ts: &str) -> Option<NaiveDateTime> {
+    let ts = ts.trim();
+    NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .or_else(|| DateTime::parse_from_rfc3339(ts).ok().map(|dt| dt.naive_utc()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2026-03-10 12:30:45", DB_TIMESTAMP_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sqlite_format() {
+        assert_eq!(parse_db_timestamp("2026-03-10 12:30:45"), Some(expected()));
+        assert_eq!(parse_db_timestamp("2026-03-10 12:30:45.000"), Some(expected()));
+    }
+
+    #[test]
+    fn test_parse_rfc3339() {
+        assert_eq!(parse_db_timestamp("2026-03-10T12:30:45Z"), Some(expected()));
+        assert_eq!(parse_db_timestamp("2026-03-10T14:30:45+02:00"), Some(expected()));
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse_db_timestamp(""), None);
+        assert_eq!(parse_db_timestamp("10.03.2026 12:30"), None);
+    }
+}
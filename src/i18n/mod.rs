@@ -10,6 +10,33 @@ use std::sync::{LazyLock, RwLock};
 pub const SUPPORTED_LANGUAGES: &[&str] = &["en", "de"];
 pub const DEFAULT_LANGUAGE: &str = "en";
 
+/// Fallbacks used unless LANGUAGE_FALLBACKS is set (see [`parse_fallbacks`])
+pub const DEFAULT_LANGUAGE_FALLBACKS: &str = "de:en";
+
+/// Where to look next when a key is missing in a language (`de -> en`).
+/// The chain always ends with `DEFAULT_LANGUAGE`, then the raw key.
+static LANGUAGE_FALLBACKS: LazyLock<RwLock<Vec<(String, String)>>> = LazyLock::new(|| {
+    RwLock::new(parse_fallbacks(DEFAULT_LANGUAGE_FALLBACKS))
+});
+
+/// Parse fallback chains like `de:en` or `fr:de:en,nl:de` into `(from, to)` steps
+pub fn parse_fallbacks(spec: &str) -> Vec<(String, String)> {
+    spec.split(',')
+        .flat_map(|chain| {
+            let langs: Vec<&str> = chain.split(':').map(str::trim).filter(|l| !l.is_empty()).collect();
+            langs
+                .windows(2)
+                .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Replace the fallbacks, e.g. with the configured ones at startup
+pub fn set_language_fallbacks(fallbacks: Vec<(String, String)>) {
+    *LANGUAGE_FALLBACKS.write().unwrap() = fallbacks;
+}
+
 /// Current bot language (runtime modifiable)
 static BOT_LANGUAGE: LazyLock<RwLock<String>> = LazyLock::new(|| {
     RwLock::new(crate::CONFIG.bot_language.clone())
@@ -63,6 +90,9 @@ pub fn get_locale_json(lang: &str) -> &'static str {
 
 /// Translate a key with optional parameters
 ///
+/// Keys missing in `lang` are looked up along the language fallbacks (LANGUAGE_FALLBACKS);
+/// if no language has them, the key itself is returned.
+///
 /// # Arguments
 /// * `lang` - Language code ("en", "de")
 /// * `key` - Dot-notated key like "bot.errors.noPermission"
@@ -70,39 +100,92 @@ pub fn get_locale_json(lang: &str) -> &'static str {
 ///
 /// # Example
 /// ```
+/// use hub::i18n::t;
 /// let msg = t("en", "bot.user.created", &[("name", "Player1")]);
 /// // Returns: "User for **Player1** created!"
 /// ```
 pub fn t(lang: &str, key: &str, params: &[(&str, &str)]) -> String {
-    let lang = if is_valid_language(lang) { lang } else { DEFAULT_LANGUAGE };
-
-    let locale = match LOCALES.get(lang) {
-        Some(l) => l,
+    let text = match resolve(&LOCALES, &current_chain(lang), key) {
+        Some(s) => s.to_string(),
         None => return key.to_string(),
     };
 
-    // Navigate to the key
-    let mut current = locale;
-    for part in key.split('.') {
-        match current.get(part) {
-            Some(v) => current = v,
-            None => return key.to_string(),
+    interpolate(&text, params)
+}
+
+/// Replace `{{name}}` placeholders in a single pass. Substituted values are never
+/// scanned again, so a value containing `{{other}}` stays literal; unknown
+/// placeholders are kept as they are.
+fn interpolate(text: &str, params: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+
+        result.push_str(&rest[..start]);
+        let name = &after[..end];
+        match params.iter().find(|(n, _)| *n == name) {
+            Some((_, value)) => result.push_str(value),
+            None => result.push_str(&rest[start..start + 2 + end + 2]),
         }
+        rest = &after[end + 2..];
     }
 
-    // Get the string value
-    let text = match current.as_str() {
-        Some(s) => s.to_string(),
-        None => return key.to_string(),
-    };
+    result.push_str(rest);
+    result
+}
+
+/// Translate a count-dependent key: uses `key.one` for a count of 1 and `key.other`
+/// otherwise (falling back to `key` itself). `{{count}}` is filled in automatically.
+pub fn t_plural(lang: &str, key: &str, count: i64, params: &[(&str, &str)]) -> String {
+    let form = if count == 1 { "one" } else { "other" };
+    let plural_key = format!("{}.{}", key, form);
+    let key = if resolve(&LOCALES, &current_chain(lang), &plural_key).is_some() { plural_key.as_str() } else { key };
 
-    // Replace parameters {{param}}
-    let mut result = text;
-    for (name, value) in params {
-        result = result.replace(&format!("{{{{{}}}}}", name), value);
+    let count = count.to_string();
+    let mut all = vec![("count", count.as_str())];
+    all.extend_from_slice(params);
+    t(lang, key, &all)
+}
+
+/// Fallback chain of `lang` along the current language fallbacks
+fn current_chain(lang: &str) -> Vec<String> {
+    let fallbacks = LANGUAGE_FALLBACKS.read().unwrap();
+    fallback_chain(lang, &fallbacks).into_iter().map(str::to_string).collect()
+}
+
+/// Languages to try for `lang`, in order: the language itself, its fallbacks, then the default
+fn fallback_chain<'a>(lang: &'a str, fallbacks: &'a [(String, String)]) -> Vec<&'a str> {
+    let mut chain = Vec::new();
+    let mut next = Some(if is_valid_language(lang) { lang } else { DEFAULT_LANGUAGE });
+
+    while let Some(current) = next.filter(|l| !chain.contains(l)) {
+        chain.push(current);
+        next = fallbacks
+            .iter()
+            .find(|(from, _)| from == current)
+            .map(|(_, to)| to.as_str());
+    }
+    if !chain.contains(&DEFAULT_LANGUAGE) {
+        chain.push(DEFAULT_LANGUAGE);
     }
 
-    result
+    chain
+}
+
+/// Find the string for `key` in the first language of `chain` that has it
+fn resolve<'a, S: AsRef<str>>(locales: &'a HashMap<&str, Value>, chain: &[S], key: &str) -> Option<&'a str> {
+    chain.iter().find_map(|l| {
+        let mut current = locales.get(l.as_ref())?;
+        for part in key.split('.') {
+            current = current.get(part)?;
+        }
+        current.as_str()
+    })
 }
 
 /// Convenience macro for translation without parameters
@@ -116,6 +199,17 @@ macro_rules! tr {
     };
 }
 
+/// Like `tr!`, but picks the singular/plural form for `$count` (see [`t_plural`])
+#[macro_export]
+macro_rules! tr_plural {
+    ($lang:expr, $key:expr, $count:expr) => {
+        $crate::i18n::t_plural($lang, $key, $count as i64, &[])
+    };
+    ($lang:expr, $key:expr, $count:expr, $($name:expr => $value:expr),+ $(,)?) => {
+        $crate::i18n::t_plural($lang, $key, $count as i64, &[$(($name, $value)),+])
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,6 +235,61 @@ mod tests {
         assert_eq!(msg, "Pong!"); // Falls back to English
     }
 
+    #[test]
+    fn test_fallback_chain() {
+        let mut locales = HashMap::new();
+        locales.insert("en", serde_json::json!({"a": {"onlyEn": "English", "both": "Both EN"}}));
+        locales.insert("de", serde_json::json!({"a": {"both": "Beide DE"}}));
+
+        let fallbacks = parse_fallbacks(DEFAULT_LANGUAGE_FALLBACKS);
+        let chain = fallback_chain("de", &fallbacks);
+        assert_eq!(chain, vec!["de", "en"]);
+        assert_eq!(resolve(&locales, &chain, "a.both"), Some("Beide DE"));
+        assert_eq!(resolve(&locales, &chain, "a.onlyEn"), Some("English"));
+        assert_eq!(resolve(&locales, &chain, "a.missing"), None);
+    }
+
+    #[test]
+    fn test_fallback_chain_from_config() {
+        let fallbacks = parse_fallbacks(" en:de , fr:de:en,nl");
+        let pairs: Vec<(&str, &str)> = fallbacks.iter().map(|(a, b)| (a.as_str(), b.as_str())).collect();
+        assert_eq!(pairs, vec![("en", "de"), ("fr", "de"), ("de", "en")]);
+
+        let mut locales = HashMap::new();
+        locales.insert("en", serde_json::json!({"a": {"both": "Both EN"}}));
+        locales.insert("de", serde_json::json!({"a": {"both": "Beide DE", "onlyDe": "Deutsch"}}));
+
+        // English now falls back to German; the cycle back to en stops the chain
+        let chain = fallback_chain("en", &fallbacks);
+        assert_eq!(chain, vec!["en", "de"]);
+        assert_eq!(resolve(&locales, &chain, "a.onlyDe"), Some("Deutsch"));
+        assert_eq!(resolve(&locales, &chain, "a.both"), Some("Both EN"));
+
+        assert_eq!(crate::Config::default().language_fallbacks, parse_fallbacks("de:en"));
+
+        // Without any fallbacks only the default language is left
+        assert_eq!(fallback_chain("de", &[]), vec!["de", "en"]);
+        assert_eq!(fallback_chain("xx", &fallbacks), vec!["en", "de"]);
+    }
+
+    #[test]
+    fn test_plural() {
+        assert_eq!(t_plural("en", "bot.planets.markedSeen", 1, &[]), "1 planet marked as seen.");
+        assert_eq!(t_plural("en", "bot.planets.markedSeen", 3, &[]), "3 planets marked as seen.");
+        assert_eq!(t_plural("de", "bot.planets.markedSeen", 0, &[]), "0 Planeten wurden als gesehen markiert.");
+        // Plain keys without plural forms still work
+        assert_eq!(t_plural("en", "bot.util.pong", 2, &[]), "Pong!");
+    }
+
+    #[test]
+    fn test_interpolate_single_pass() {
+        let msg = interpolate("{{a}} and {{b}}", &[("a", "{{b}}"), ("b", "B")]);
+        assert_eq!(msg, "{{b}} and B");
+
+        assert_eq!(interpolate("{{unknown}} {{a}}", &[("a", "x")]), "{{unknown}} x");
+        assert_eq!(interpolate("open {{a", &[("a", "x")]), "open {{a");
+    }
+
     #[test]
     fn test_missing_key() {
         let msg = t("en", "nonexistent.key", &[]);
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::LazyLock;
 use sqlx::SqlitePool;
 use tokio::sync::OnceCell;
@@ -8,11 +10,41 @@ pub mod api;
 pub mod bot;
 pub mod i18n;
 
+#[derive(Clone)]
 pub struct Config {
     pub database_url: String,
     pub log_level: String,
-    pub host: String,
+    pub log_format: String,
+    pub host: IpAddr,
     pub port: u16,
+    /// Serve on this Unix domain socket instead of HOST:PORT (e.g. behind nginx)
+    pub listen_uds: Option<String>,
+    /// URL prefix of the API routes (API_BASE_PATH, default `/api`)
+    pub api_base_path: String,
+    /// URL prefix of the static files (STATIC_BASE_PATH, default `/static`)
+    pub static_base_path: String,
+    /// Directory the static files are served from (STATIC_DIR, default `static`)
+    pub static_dir: String,
+    /// Reject API keys unused for this many days (0 = never expire)
+    pub api_key_ttl_days: u32,
+    /// Delete reports and score history older than this many days (0 = keep forever)
+    pub retention_days: u32,
+    /// Keep only the last score per player and day once it is older than this many days (0 = keep all)
+    pub score_downsample_after_days: u32,
+    /// Reject requests containing unknown game IDs or negative amounts instead of dropping those entries
+    pub strict_game_ids: bool,
+    /// Skip spy reports whose content matches one stored within this many minutes (0 = off)
+    pub spy_dedup_window_minutes: u32,
+    /// Origins allowed by CORS (empty = any origin)
+    pub cors_allowed_origins: Vec<String>,
+    /// Only show research and planets of a player to members of the same alliance
+    pub alliance_private: bool,
+    /// Planets at or below this temperature count as cold (good for deuterium)
+    pub planet_cold_temperature: i64,
+    /// Planets at or above this temperature count as hot (good for solar satellites)
+    pub planet_hot_temperature: i64,
+    /// Where to look next when a translation is missing, as `from:to` chains (LANGUAGE_FALLBACKS, default `de:en`)
+    pub language_fallbacks: Vec<(String, String)>,
     // Bot config
     pub bot_token: Option<String>,
     pub bot_ally_id: u32,
@@ -20,7 +52,23 @@ pub struct Config {
     pub bot_user_role_ids: Vec<u64>,
     pub bot_spy_channel_id: Option<u64>,
     pub bot_channel_id: Option<u64>,
+    /// Per-command channel overrides from `<COMMAND>_CHANNEL_ID` (e.g. `INACTIVE_CHANNEL_ID`)
+    pub bot_command_channel_ids: HashMap<String, u64>,
     pub bot_language: String,
+    /// Track /newplanets "seen" state globally instead of per Discord user
+    pub bot_newplanets_global: bool,
+    /// Alliances (e.g. allies) whose inactive members /inactive doesn't list as farms
+    pub bot_farm_exclude_alliance_ids: Vec<i64>,
+    /// Share of a target's resources a raid takes, for the /spy loot estimate (percent)
+    pub bot_raid_loot_percent: u32,
+    /// Cargo capacity of one Large Cargo, for the /spy loot estimate
+    pub bot_large_cargo_capacity: i64,
+    /// Alert the bot channel once this many hostile spy events arrive within the window (0 = off)
+    pub bot_spy_alert_threshold: u32,
+    /// Window the hostile spy alert counts over, also the minimum time between two alerts
+    pub bot_spy_alert_window_minutes: u32,
+    /// Config values that were fixed up while loading, logged once tracing is initialized
+    pub startup_warnings: Vec<String>,
 }
 static DB_POOL: OnceCell<SqlitePool> = OnceCell::const_new();
 pub async fn get_pool() -> &'static SqlitePool {
@@ -48,8 +96,8 @@ pub async fn get_pool() -> &'static SqlitePool {
         pool
     }).await
 }
-/// Parse comma-separated list of u64 values from env var
-fn parse_role_ids(var_name: &str) -> Vec<u64> {
+/// Parse a comma-separated list of IDs, skipping invalid entries
+fn parse_id_list<T: std::str::FromStr>(var_name: &str) -> Vec<T> {
     std::env::var(var_name)
         .unwrap_or_default()
         .split(',')
@@ -57,26 +105,253 @@ fn parse_role_ids(var_name: &str) -> Vec<u64> {
         .collect()
 }
 
+/// Bot commands whose target channel can be overridden with `<COMMAND>_CHANNEL_ID`
+const CHANNEL_OVERRIDE_COMMANDS: &[&str] = &["estimate", "inactive", "ranking", "defense", "newplanets", "export", "exportstats"];
+
+fn parse_command_channel_ids() -> HashMap<String, u64> {
+    CHANNEL_OVERRIDE_COMMANDS
+        .iter()
+        .filter_map(|command| {
+            let var_name = format!("{}_CHANNEL_ID", command.to_uppercase());
+            let id = std::env::var(var_name).ok()?.trim().parse().ok()?;
+            Some((command.to_string(), id))
+        })
+        .collect()
+}
+
+/// Defaults of every setting, as if no environment variable was set (DATABASE_URL empty)
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            database_url: String::new(),
+            log_level: "info".to_string(),
+            log_format: "pretty".to_string(),
+            host: IpAddr::from([127, 0, 0, 1]),
+            port: 3000,
+            listen_uds: None,
+            api_base_path: "/api".to_string(),
+            static_base_path: "/static".to_string(),
+            static_dir: "static".to_string(),
+            api_key_ttl_days: 0,
+            retention_days: 0,
+            score_downsample_after_days: 0,
+            strict_game_ids: false,
+            spy_dedup_window_minutes: 0,
+            cors_allowed_origins: Vec::new(),
+            alliance_private: false,
+            planet_cold_temperature: DEFAULT_PLANET_TEMPERATURES.0,
+            planet_hot_temperature: DEFAULT_PLANET_TEMPERATURES.1,
+            language_fallbacks: i18n::parse_fallbacks(i18n::DEFAULT_LANGUAGE_FALLBACKS),
+            bot_token: None,
+            bot_ally_id: 0,
+            bot_admin_role_ids: Vec::new(),
+            bot_user_role_ids: Vec::new(),
+            bot_spy_channel_id: None,
+            bot_channel_id: None,
+            bot_command_channel_ids: HashMap::new(),
+            bot_language: "en".to_string(),
+            bot_newplanets_global: false,
+            bot_farm_exclude_alliance_ids: Vec::new(),
+            bot_raid_loot_percent: 50,
+            bot_large_cargo_capacity: 25_000,
+            bot_spy_alert_threshold: 0,
+            bot_spy_alert_window_minutes: 60,
+            startup_warnings: Vec::new(),
+        }
+    }
+}
+
+/// Parse HOST as an IP address. `localhost` is accepted as 127.0.0.1 with a warning,
+/// anything else that isn't an IP is rejected with a message naming the bad value.
+fn parse_host(value: &str) -> Result<(IpAddr, Option<String>), String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("localhost") {
+        let warning = "HOST=localhost is not an IP address, binding to 127.0.0.1".to_string();
+        return Ok((IpAddr::from([127, 0, 0, 1]), Some(warning)));
+    }
+    value
+        .parse()
+        .map(|ip| (ip, None))
+        .map_err(|_| format!("HOST must be an IP like 0.0.0.0 or 127.0.0.1, got '{}'", value))
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.trim().parse() {
+        Ok(0) | Err(_) => Err(format!("PORT must be a number between 1 and 65535, got '{}'", value)),
+        Ok(port) => Ok(port),
+    }
+}
+
+/// Normalize a URL prefix to `/a/b`: leading slash added, trailing slashes dropped.
+/// The root is rejected, API and static files are always mounted below it.
+fn parse_base_path(var_name: &str, value: &str) -> Result<String, String> {
+    let trimmed = value.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        return Err(format!("{} must be a sub path like /hub/api, got '{}'", var_name, value));
+    }
+    Ok(format!("/{}", trimmed))
+}
+
+/// Default (cold, hot) planet temperature thresholds in °C
+const DEFAULT_PLANET_TEMPERATURES: (i64, i64) = (-20, 80);
+
+/// Parse the cold/hot temperature thresholds, falling back to the defaults
+/// with a warning when cold isn't below hot
+fn parse_planet_temperatures(cold: Option<&str>, hot: Option<&str>) -> ((i64, i64), Option<String>) {
+    let (default_cold, default_hot) = DEFAULT_PLANET_TEMPERATURES;
+    let cold = cold.and_then(|s| s.trim().parse().ok()).unwrap_or(default_cold);
+    let hot = hot.and_then(|s| s.trim().parse().ok()).unwrap_or(default_hot);
+    if cold < hot {
+        return ((cold, hot), None);
+    }
+    let warning = format!(
+        "PLANET_COLD_TEMPERATURE ({}) must be below PLANET_HOT_TEMPERATURE ({}), using {} and {}",
+        cold, hot, default_cold, default_hot
+    );
+    (DEFAULT_PLANET_TEMPERATURES, Some(warning))
+}
+
 pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
     dotenvy::dotenv().ok();
+    let mut startup_warnings = Vec::new();
+
+    let (host, host_warning) = parse_host(&std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()))
+        .unwrap_or_else(|e| panic!("{}", e));
+    startup_warnings.extend(host_warning);
+    let port = parse_port(&std::env::var("PORT").unwrap_or_else(|_| "3000".to_string()))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let api_base_path = parse_base_path("API_BASE_PATH", &std::env::var("API_BASE_PATH").unwrap_or_else(|_| "/api".to_string()))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let static_base_path = parse_base_path("STATIC_BASE_PATH", &std::env::var("STATIC_BASE_PATH").unwrap_or_else(|_| "/static".to_string()))
+        .unwrap_or_else(|e| panic!("{}", e));
+    let ((planet_cold_temperature, planet_hot_temperature), temperature_warning) = parse_planet_temperatures(
+        std::env::var("PLANET_COLD_TEMPERATURE").ok().as_deref(),
+        std::env::var("PLANET_HOT_TEMPERATURE").ok().as_deref(),
+    );
+    startup_warnings.extend(temperature_warning);
+
     Config {
         database_url: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
         log_level: std::env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
-        host: std::env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-        port: std::env::var("PORT")
-            .unwrap_or_else(|_| "3000".to_string())
-            .parse()
-            .expect("PORT must be a valid number"),
+        log_format: std::env::var("LOG_FORMAT").unwrap_or_else(|_| "pretty".to_string()),
+        host,
+        port,
+        listen_uds: std::env::var("LISTEN_UDS").ok().filter(|s| !s.trim().is_empty()),
+        api_base_path,
+        static_base_path,
+        static_dir: std::env::var("STATIC_DIR").unwrap_or_else(|_| "static".to_string()),
+        api_key_ttl_days: std::env::var("API_KEY_TTL_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        retention_days: std::env::var("RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        score_downsample_after_days: std::env::var("SCORE_DOWNSAMPLE_AFTER_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        strict_game_ids: std::env::var("STRICT_GAME_IDS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false),
+        spy_dedup_window_minutes: std::env::var("SPY_DEDUP_WINDOW_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        cors_allowed_origins: std::env::var("CORS_ALLOWED_ORIGINS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().trim_end_matches('/').to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        alliance_private: std::env::var("ALLIANCE_PRIVATE")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false),
+        planet_cold_temperature,
+        planet_hot_temperature,
+        language_fallbacks: i18n::parse_fallbacks(
+            &std::env::var("LANGUAGE_FALLBACKS").unwrap_or_else(|_| i18n::DEFAULT_LANGUAGE_FALLBACKS.to_string()),
+        ),
         // Bot config
         bot_token: std::env::var("BOT_TOKEN").ok(),
         bot_ally_id: std::env::var("ALLY_ID")
             .unwrap_or_else(|_| "0".to_string())
             .parse()
             .unwrap_or(0),
-        bot_admin_role_ids: parse_role_ids("ADMIN_ROLE_IDS"),
-        bot_user_role_ids: parse_role_ids("USER_ROLE_IDS"),
+        bot_admin_role_ids: parse_id_list("ADMIN_ROLE_IDS"),
+        bot_user_role_ids: parse_id_list("USER_ROLE_IDS"),
         bot_spy_channel_id: std::env::var("SPY_CHANNEL_ID").ok().and_then(|s| s.parse().ok()),
         bot_channel_id: std::env::var("BOT_CHANNEL_ID").ok().and_then(|s| s.parse().ok()),
+        bot_command_channel_ids: parse_command_channel_ids(),
         bot_language: std::env::var("BOT_LANGUAGE").unwrap_or_else(|_| "en".to_string()),
+        bot_newplanets_global: std::env::var("NEWPLANETS_GLOBAL")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(false),
+        bot_farm_exclude_alliance_ids: parse_id_list("FARM_EXCLUDE_ALLIANCE_IDS"),
+        bot_raid_loot_percent: std::env::var("RAID_LOOT_PERCENT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|p| (1..=100).contains(p))
+            .unwrap_or(50),
+        bot_large_cargo_capacity: std::env::var("LARGE_CARGO_CAPACITY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&c| c > 0)
+            .unwrap_or(25_000),
+        bot_spy_alert_threshold: std::env::var("SPY_ALERT_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0),
+        bot_spy_alert_window_minutes: std::env::var("SPY_ALERT_WINDOW_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|&m| m > 0)
+            .unwrap_or(60),
+        startup_warnings,
+    }
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_host() {
+        assert_eq!(parse_host("0.0.0.0").unwrap(), (IpAddr::from([0, 0, 0, 0]), None));
+        assert_eq!(parse_host("::1").unwrap().0, "::1".parse::<IpAddr>().unwrap());
+
+        let (ip, warning) = parse_host("localhost").unwrap();
+        assert_eq!(ip, IpAddr::from([127, 0, 0, 1]));
+        assert!(warning.is_some());
+
+        let err = parse_host("example.com").unwrap_err();
+        assert!(err.contains("got 'example.com'"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_base_path() {
+        assert_eq!(parse_base_path("API_BASE_PATH", "/api").unwrap(), "/api");
+        assert_eq!(parse_base_path("API_BASE_PATH", "hub/api/").unwrap(), "/hub/api");
+        assert!(parse_base_path("API_BASE_PATH", "/").unwrap_err().contains("API_BASE_PATH"));
+        assert!(parse_base_path("API_BASE_PATH", "").is_err());
+    }
+
+    #[test]
+    fn test_parse_planet_temperatures() {
+        assert_eq!(parse_planet_temperatures(None, None), (DEFAULT_PLANET_TEMPERATURES, None));
+        assert_eq!(parse_planet_temperatures(Some("-50"), Some(" 120")), ((-50, 120), None));
+
+        let (temperatures, warning) = parse_planet_temperatures(Some("100"), Some("50"));
+        assert_eq!(temperatures, DEFAULT_PLANET_TEMPERATURES);
+        assert!(warning.unwrap().contains("PLANET_COLD_TEMPERATURE (100)"));
+    }
+
+    #[test]
+    fn test_parse_port() {
+        assert_eq!(parse_port("3000"), Ok(3000));
+        assert!(parse_port("0").is_err());
+        assert!(parse_port("70000").unwrap_err().contains("got '70000'"));
+        assert!(parse_port("http").is_err());
     }
-});
\ No newline at end of file
+}
\ No newline at end of file
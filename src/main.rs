@@ -1,6 +1,6 @@
-use hub::{get_pool, api, bot, CONFIG};
+use hub::{get_pool, api, bot, db, i18n, CONFIG};
 use std::net::SocketAddr;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
@@ -9,16 +9,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let filter = EnvFilter::try_new(&CONFIG.log_level)
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
-    tracing_subscriber::registry()
-        .with(filter)
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // LOG_FORMAT=json emits one JSON object per line for log aggregators
+    let registry = tracing_subscriber::registry().with(filter);
+    match CONFIG.log_format.as_str() {
+        "json" => registry.with(tracing_subscriber::fmt::layer().json()).init(),
+        _ => registry.with(tracing_subscriber::fmt::layer()).init(),
+    }
+
+    info!(log_level = %CONFIG.log_level, log_format = %CONFIG.log_format, "Tracing initialized");
+    for warning in &CONFIG.startup_warnings {
+        warn!("{}", warning);
+    }
 
-    info!(log_level = %CONFIG.log_level, "Tracing initialized");
+    i18n::set_language_fallbacks(CONFIG.language_fallbacks.clone());
 
     // Pool initialisieren
     let _pool = get_pool().await;
 
+    // Start retention cleanup if configured
+    if CONFIG.retention_days > 0 {
+        info!(retention_days = CONFIG.retention_days, "Retention cleanup enabled");
+        tokio::spawn(db::retention::run_retention_job());
+    }
+
+    // Start score history downsampling if configured
+    if CONFIG.score_downsample_after_days > 0 {
+        info!(after_days = CONFIG.score_downsample_after_days, "Score downsampling enabled");
+        tokio::spawn(db::retention::run_score_downsample_job());
+    }
+
     // Start Discord bot as tokio task if configured
     if bot::bot_enabled() {
         info!("Discord bot enabled, starting...");
@@ -27,11 +46,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Discord bot disabled (missing configuration)");
     }
 
-    let app = api::routes::create_router();
+    let state = api::state::AppState::new(get_pool().await.clone(), CONFIG.clone());
+    // A relative STATIC_DIR resolves against the working directory, which differs under systemd
+    if !std::path::Path::new(&CONFIG.static_dir).is_dir() {
+        warn!(static_dir = %CONFIG.static_dir, cwd = ?std::env::current_dir().ok(), "STATIC_DIR does not exist, static files will 404");
+    }
+
+    let app = api::routes::create_router(state);
+
+    if let Some(path) = &CONFIG.listen_uds {
+        serve_uds(path, app).await?;
+        return Ok(());
+    }
 
-    let host: std::net::IpAddr = CONFIG.host.parse()
-        .expect("HOST must be a valid IP address");
-    let addr = SocketAddr::from((host, CONFIG.port));
+    let addr = SocketAddr::from((CONFIG.host, CONFIG.port));
     info!(%addr, "Server running");
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -39,3 +67,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Serve on a Unix domain socket. A socket file left over from a previous run is replaced,
+/// any other file at the path is left alone.
+#[cfg(unix)]
+async fn serve_uds(path: &str, app: axum::Router) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Cannot replace LISTEN_UDS socket '{}': {}", path, e))?;
+            info!(path, "Removed stale Unix socket");
+        }
+        Ok(_) => return Err(format!("Cannot replace LISTEN_UDS socket '{}': not a socket", path).into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("Cannot replace LISTEN_UDS socket '{}': {}", path, e).into()),
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| format!("Cannot bind LISTEN_UDS socket '{}': {}", path, e))?;
+    info!(path, "Server running on Unix socket");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn serve_uds(_path: &str, _app: axum::Router) -> Result<(), Box<dyn std::error::Error>> {
+    Err("LISTEN_UDS is only supported on Unix".into())
+}
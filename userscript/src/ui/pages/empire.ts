@@ -32,10 +32,18 @@ interface EmpirePlanet {
 interface EmpireData {
   player_id: number;
   player_name: string;
+  alliance_name: string | null;
   research: Record<string, number>;
   planets: EmpirePlanet[];
 }
 
+interface EmpireSyncResponse {
+  success: boolean;
+  planets_synced: number;
+  research_updated: boolean;
+  new_planets: number;
+}
+
 /**
  * Initialize empire page enhancements
  */
@@ -102,11 +110,21 @@ function addSyncButton(): void {
       debugLog('Parsed empire data:', data);
       syncBtn.innerHTML = `⏳ ${t('sync.syncing')}`;
 
-      const result = await api.post('/empire', data);
+      const result = await api.post<EmpireSyncResponse>('/empire', data);
 
       if (result.ok) {
         syncBtn.innerHTML = `✓ ${t('sync.done')}`;
         syncBtn.style.color = '#8f8';
+        if (result.data) {
+          const summary = [t('sync.empireSummary', {
+            count: result.data.planets_synced,
+            new: result.data.new_planets,
+          })];
+          if (result.data.research_updated) {
+            summary.push(t('sync.researchUpdated'));
+          }
+          syncBtn.title = summary.join('\n');
+        }
         // Keep button in "done" state - user can reload page to sync again
         syncBtn.disabled = true;
       } else {
@@ -153,6 +171,23 @@ function parseEmpireData(): EmpireData | null {
     const playerNameEl = document.querySelector('.planetImage b');
     const playerName = playerNameEl?.textContent?.trim() || 'Unknown';
 
+    // Full alliance name (optional) - backend keeps the stored name if missing
+    let allianceName: string | null = null;
+    const alliancePatterns = [
+      /allianceName["\s:=]+"([^"]+)"/i,
+      /ally_?name["\s:=]+"([^"]+)"/i,
+    ];
+
+    for (const pattern of alliancePatterns) {
+      const match = document.body.innerHTML.match(pattern);
+      if (match) {
+        allianceName = match[1].trim() || null;
+        break;
+      }
+    }
+
+    debugLog('Alliance name:', allianceName || '(not on page)');
+
     // Get planet selector options to get planet IDs
     const planetSelector = document.querySelector('#planetSelector');
     const planetOptions = planetSelector?.querySelectorAll('option') || [];
@@ -180,6 +215,7 @@ function parseEmpireData(): EmpireData | null {
     return {
       player_id: playerId,
       player_name: playerName,
+      alliance_name: allianceName,
       research,
       planets,
     };
@@ -17,6 +17,14 @@ interface GalaxyPlanetInfo {
   planet: number;
   type: string;
   planet_id: number | null;  // pr0game internal planet ID for sync comparison
+  has_moon: boolean;
+  moon: GalaxyMoonInfo | null;
+}
+
+interface GalaxyMoonInfo {
+  id: number;
+  name: string | null;
+  planet_id: number | null;
 }
 
 interface GalaxySpyReport {
@@ -41,6 +49,7 @@ interface PagePlanet {
   playerName: string | null;
   allianceId: number | null;  // Alliance ID from galaxy page
   allianceTag: string | null;  // Alliance tag from galaxy page
+  allianceName: string | null;  // Full alliance name from the alliance tooltip
   hasMoon: boolean;
   moonName: string | null;
   planetId: number | null;  // pr0game internal planet ID (from spy links)
@@ -201,6 +210,25 @@ function findGalaxyContainer(): HTMLElement | null {
   return null;
 }
 
+/**
+ * Full alliance name from the tooltip around the alliance tag.
+ * The tooltip header reads "Allianz <name> auf Platz <rank> ..." (or the English equivalent).
+ */
+function parseAllianceName(row: Element): string | null {
+  const allianceEl = row.querySelector('span[allianceid]') || row.querySelector('a[href*="page=alliance"]');
+  const tooltipEl = allianceEl?.closest('[data-tooltip-content]');
+  if (!tooltipEl) return null;
+
+  const tooltipHtml = tooltipEl.getAttribute('data-tooltip-content') || '';
+  const header = new DOMParser().parseFromString(tooltipHtml, 'text/html').querySelector('th');
+  const name = header?.textContent
+    ?.trim()
+    .replace(/^(Allianz|Alliance)\s+/i, '')
+    .replace(/\s+(auf Platz|at rank|on rank)\s.*$/i, '')
+    .trim();
+  return name || null;
+}
+
 function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
   const planets: PagePlanet[] = [];
 
@@ -227,6 +255,7 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
       let playerName: string | null = null;
       let allianceId: number | null = null;
       let allianceTag: string | null = null;
+      let allianceName: string | null = null;
       let hasMoon = false;
       let moonName: string | null = null;
       let planetId: number | null = null;
@@ -266,6 +295,9 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
             allianceTag = allianceLink.textContent?.trim() || null;
           }
         }
+        if (allianceId) {
+          allianceName = parseAllianceName(row);
+        }
 
         // Look for planet name in the planet column
         const planetItem = row.querySelector('.galaxy-grid-item.galaxy-planet');
@@ -357,6 +389,7 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
         playerName,
         allianceId,
         allianceTag,
+        allianceName,
         hasMoon,
         moonName,
         planetId,
@@ -386,6 +419,7 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
       let playerName: string | null = null;
       let allianceId: number | null = null;
       let allianceTag: string | null = null;
+      let allianceName: string | null = null;
       let hasMoon = false;
       let moonName: string | null = null;
       let planetId: number | null = null;
@@ -423,6 +457,9 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
           allianceTag = allianceLink.textContent?.trim() || null;
         }
       }
+      if (allianceId) {
+        allianceName = parseAllianceName(row);
+      }
 
       // Get planet name from tooltip or text
       if (hasPlanetContent) {
@@ -488,6 +525,7 @@ function parsePlanetsFromPage(container: HTMLElement): PagePlanet[] {
         playerName,
         allianceId,
         allianceTag,
+        allianceName,
         hasMoon,
         moonName,
         planetId,
@@ -647,7 +685,11 @@ function detectDifferences(pagePlanets: PagePlanet[], dbPlanets: GalaxyPlanetInf
   dbPlanets.forEach(p => {
     if (p.type === 'PLANET') {
       dbPlanetMap.set(p.planet, p);
+      if (p.moon) {
+        dbMoonMap.set(p.planet, { ...p, ...p.moon, type: 'MOON', has_moon: false, moon: null });
+      }
     } else if (p.type === 'MOON') {
+      // Moon without a known planet at this position
       dbMoonMap.set(p.planet, p);
     }
   });
@@ -833,7 +875,7 @@ async function syncSystem(galaxy: number, system: number): Promise<void> {
   button.textContent = '...';
 
   try {
-    // Prepare sync payload - backend expects position, player_id, player_name, planet_name, moon_name, has_moon, planet_id, moon_id, alliance_id, alliance_tag
+    // Prepare sync payload - backend expects position, player_id, player_name, planet_name, moon_name, has_moon, planet_id, moon_id, alliance_id, alliance_tag, alliance_name
     const planets = pendingSyncData
       .filter(p => p.hasPlanet)  // Only planets that exist
       .map(p => ({
@@ -846,7 +888,8 @@ async function syncSystem(galaxy: number, system: number): Promise<void> {
         planet_id: p.planetId || null,  // pr0game internal planet ID
         moon_id: p.moonId || null,  // pr0game internal moon ID
         alliance_id: p.allianceId || null,  // Alliance ID from galaxy page
-        alliance_tag: p.allianceTag || null  // Alliance tag from galaxy page
+        alliance_tag: p.allianceTag || null,  // Alliance tag from galaxy page
+        alliance_name: p.allianceName || null  // Full alliance name from the tooltip
       }));
 
     // Get destroyed positions from diffs
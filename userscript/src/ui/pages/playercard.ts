@@ -14,6 +14,7 @@ interface PlayerData {
   name: string;
   allianceId: number | null;
   allianceTag: string | null;
+  allianceName: string | null;
   mainCoordinates: string | null;
   // Scores with ranks
   scoreBuildings: number | null;
@@ -289,6 +290,7 @@ function parsePlayerData(): PlayerData | null {
   const allianceRow = rows[playerProfileStart + 3];
   let allianceId: number | null = null;
   let allianceTag: string | null = null;
+  let allianceName: string | null = null;
   if (allianceRow) {
     const allianceLink = allianceRow.querySelector('a');
     if (allianceLink) {
@@ -298,10 +300,12 @@ function parsePlayerData(): PlayerData | null {
       if (idMatch) {
         allianceId = parseInt(idMatch[1], 10);
       }
-      // Extract alliance tag/name from link text
-      const tagText = allianceLink.textContent?.trim();
-      if (tagText) {
-        allianceTag = tagText;
+      // Link text is the full alliance name, sometimes followed by the tag: "Name [TAG]"
+      const linkText = allianceLink.textContent?.trim();
+      if (linkText) {
+        const tagMatch = linkText.match(/^(.+?)\s*\[([^\]]+)]$/);
+        allianceName = tagMatch ? tagMatch[1] : linkText;
+        allianceTag = tagMatch ? tagMatch[2] : linkText;
       }
     }
   }
@@ -521,6 +525,7 @@ function parsePlayerData(): PlayerData | null {
     name,
     allianceId,
     allianceTag,
+    allianceName,
     mainCoordinates,
     scoreBuildings: buildings.points,
     scoreBuildingsRank: buildings.rank,
@@ -659,6 +664,7 @@ async function syncPlayer(playerData: PlayerData): Promise<void> {
       name: playerData.name,
       alliance_id: playerData.allianceId,
       alliance_tag: playerData.allianceTag,
+      alliance_name: playerData.allianceName,
       main_coordinates: playerData.mainCoordinates,
       notice: currentNotice,  // Send empty string to clear notice
       // Scores